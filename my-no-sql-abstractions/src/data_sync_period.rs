@@ -8,3 +8,30 @@ pub enum DataSynchronizationPeriod {
     Min1,
     Asap,
 }
+
+impl DataSynchronizationPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataSynchronizationPeriod::Immediately => "i",
+            DataSynchronizationPeriod::Sec1 => "1",
+            DataSynchronizationPeriod::Sec5 => "5",
+            DataSynchronizationPeriod::Sec15 => "15",
+            DataSynchronizationPeriod::Sec30 => "30",
+            DataSynchronizationPeriod::Min1 => "60",
+            DataSynchronizationPeriod::Asap => "a",
+        }
+    }
+
+    pub fn try_from_str(src: &str) -> Option<Self> {
+        match src {
+            "i" => Some(DataSynchronizationPeriod::Immediately),
+            "1" => Some(DataSynchronizationPeriod::Sec1),
+            "5" => Some(DataSynchronizationPeriod::Sec5),
+            "15" => Some(DataSynchronizationPeriod::Sec15),
+            "30" => Some(DataSynchronizationPeriod::Sec30),
+            "60" => Some(DataSynchronizationPeriod::Min1),
+            "a" => Some(DataSynchronizationPeriod::Asap),
+            _ => None,
+        }
+    }
+}
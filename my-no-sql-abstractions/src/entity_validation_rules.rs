@@ -0,0 +1,262 @@
+/// Characters allowed in PartitionKey and RowKey
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityKeyCharset {
+    Any,
+    /// Control characters are not allowed
+    Printable,
+    /// Printable characters except '/', '\', '#' and '?'
+    PrintableWithoutReserved,
+    /// ASCII letters and digits plus the listed characters
+    AsciiAlphanumeric(String),
+}
+
+impl EntityKeyCharset {
+    pub fn is_allowed(&self, c: char) -> bool {
+        match self {
+            EntityKeyCharset::Any => true,
+            EntityKeyCharset::Printable => !c.is_control(),
+            EntityKeyCharset::PrintableWithoutReserved => {
+                !c.is_control() && !matches!(c, '/' | '\\' | '#' | '?')
+            }
+            EntityKeyCharset::AsciiAlphanumeric(extra_chars) => {
+                c.is_ascii_alphanumeric() || extra_chars.contains(c)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityValidationError {
+    PartitionKeyIsTooLong { len: usize, max_len: usize },
+    RowKeyIsTooLong { len: usize, max_len: usize },
+    PartitionKeyHasForbiddenChar { value: char, position: usize },
+    RowKeyHasForbiddenChar { value: char, position: usize },
+    EntityIsTooBig { size: usize, max_size: usize },
+    JsonIsTooDeep { max_depth: usize },
+}
+
+/// Limits of the entity. Same rules are checked by the writer before the request is sent
+/// and by the node when the entity is parsed. Lengths of the keys are in bytes.
+/// Default rules check nothing, so every limit is opted in explicitly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityValidationRules {
+    pub max_partition_key_len: Option<usize>,
+    pub max_row_key_len: Option<usize>,
+    pub key_charset: EntityKeyCharset,
+    /// Size of the serialized entity
+    pub max_entity_size: Option<usize>,
+    /// Flat object has depth 1
+    pub max_json_depth: Option<usize>,
+}
+
+impl Default for EntityValidationRules {
+    fn default() -> Self {
+        Self {
+            max_partition_key_len: None,
+            max_row_key_len: None,
+            key_charset: EntityKeyCharset::Any,
+            max_entity_size: None,
+            max_json_depth: None,
+        }
+    }
+}
+
+impl EntityValidationRules {
+    pub fn validate_partition_key(&self, partition_key: &str) -> Result<(), EntityValidationError> {
+        if let Some(max_len) = self.max_partition_key_len {
+            if partition_key.len() > max_len {
+                return Err(EntityValidationError::PartitionKeyIsTooLong {
+                    len: partition_key.len(),
+                    max_len,
+                });
+            }
+        }
+
+        if let Some((position, value)) = self.find_forbidden_char(partition_key) {
+            return Err(EntityValidationError::PartitionKeyHasForbiddenChar { value, position });
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_row_key(&self, row_key: &str) -> Result<(), EntityValidationError> {
+        if let Some(max_len) = self.max_row_key_len {
+            if row_key.len() > max_len {
+                return Err(EntityValidationError::RowKeyIsTooLong {
+                    len: row_key.len(),
+                    max_len,
+                });
+            }
+        }
+
+        if let Some((position, value)) = self.find_forbidden_char(row_key) {
+            return Err(EntityValidationError::RowKeyHasForbiddenChar { value, position });
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_size(&self, size: usize) -> Result<(), EntityValidationError> {
+        if let Some(max_size) = self.max_entity_size {
+            if size > max_size {
+                return Err(EntityValidationError::EntityIsTooBig { size, max_size });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_json_depth(&self, raw: &[u8]) -> Result<(), EntityValidationError> {
+        if let Some(max_depth) = self.max_json_depth {
+            if json_depth_is_bigger(raw, max_depth) {
+                return Err(EntityValidationError::JsonIsTooDeep { max_depth });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keys are only required to be read if their length or charset is limited
+    pub fn has_key_rules(&self) -> bool {
+        self.max_partition_key_len.is_some()
+            || self.max_row_key_len.is_some()
+            || self.key_charset != EntityKeyCharset::Any
+    }
+
+    /// Serialized entity is only required if size or depth is limited
+    pub fn requires_serialized_entity(&self) -> bool {
+        self.max_entity_size.is_some() || self.max_json_depth.is_some()
+    }
+
+    pub fn validate_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        raw: &[u8],
+    ) -> Result<(), EntityValidationError> {
+        self.validate_size(raw.len())?;
+        self.validate_partition_key(partition_key)?;
+        self.validate_row_key(row_key)?;
+        self.validate_json_depth(raw)
+    }
+
+    fn find_forbidden_char(&self, key: &str) -> Option<(usize, char)> {
+        if self.key_charset == EntityKeyCharset::Any {
+            return None;
+        }
+
+        key.char_indices()
+            .find(|(_, c)| !self.key_charset.is_allowed(*c))
+    }
+}
+
+/// Content of the strings is skipped. Scan stops as soon as the depth is bigger than max_depth
+fn json_depth_is_bigger(raw: &[u8], max_depth: usize) -> bool {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for b in raw {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if *b == b'\\' {
+                escaped = true;
+            } else if *b == b'"' {
+                in_string = false;
+            }
+
+            continue;
+        }
+
+        match *b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntityKeyCharset, EntityValidationError, EntityValidationRules};
+
+    #[test]
+    fn test_keys() {
+        let rules = EntityValidationRules {
+            max_partition_key_len: Some(5),
+            max_row_key_len: Some(3),
+            key_charset: EntityKeyCharset::AsciiAlphanumeric("-_".to_string()),
+            max_entity_size: None,
+            max_json_depth: None,
+        };
+
+        assert!(rules.validate_partition_key("ab-_1").is_ok());
+        assert_eq!(
+            Err(EntityValidationError::PartitionKeyIsTooLong { len: 6, max_len: 5 }),
+            rules.validate_partition_key("abcdef")
+        );
+        assert_eq!(
+            Err(EntityValidationError::RowKeyHasForbiddenChar {
+                value: '/',
+                position: 1
+            }),
+            rules.validate_row_key("a/b")
+        );
+
+        let rules = EntityValidationRules {
+            key_charset: EntityKeyCharset::PrintableWithoutReserved,
+            ..Default::default()
+        };
+
+        assert!(rules.validate_row_key("Київ 1").is_ok());
+        assert!(rules.validate_row_key("a#b").is_err());
+        assert!(rules.validate_row_key("a\nb").is_err());
+    }
+
+    #[test]
+    fn test_default_rules_check_nothing() {
+        let rules = EntityValidationRules::default();
+
+        assert!(!rules.has_key_rules());
+        assert!(!rules.requires_serialized_entity());
+        assert!(rules
+            .validate_entity("a".repeat(1024).as_str(), "rk\n", b"{}")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_size_and_depth() {
+        let rules = EntityValidationRules {
+            max_entity_size: Some(64),
+            max_json_depth: Some(2),
+            ..Default::default()
+        };
+
+        let raw = br#"{"PartitionKey":"pk","RowKey":"rk","A":{"B":"{[{["}}"#;
+        assert!(rules.validate_entity("pk", "rk", raw).is_ok());
+
+        let raw = br#"{"PartitionKey":"pk","RowKey":"rk","A":{"B":[1]}}"#;
+        assert_eq!(
+            Err(EntityValidationError::JsonIsTooDeep { max_depth: 2 }),
+            rules.validate_entity("pk", "rk", raw)
+        );
+
+        let raw = [b' '; 65];
+        assert_eq!(
+            Err(EntityValidationError::EntityIsTooBig {
+                size: 65,
+                max_size: 64
+            }),
+            rules.validate_entity("pk", "rk", &raw)
+        );
+    }
+}
@@ -4,3 +4,7 @@ pub use data_sync_period::DataSynchronizationPeriod;
 pub use my_no_sql_entity::*;
 mod timestamp_type;
 pub use timestamp_type::*;
+mod table_attributes;
+pub use table_attributes::*;
+mod entity_validation_rules;
+pub use entity_validation_rules::*;
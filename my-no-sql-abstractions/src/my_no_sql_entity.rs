@@ -1,8 +1,9 @@
-use crate::Timestamp;
+use crate::{MyNoSqlTableAttributes, Timestamp};
 
 pub trait MyNoSqlEntity {
     const TABLE_NAME: &'static str;
     const LAZY_DESERIALIZATION: bool;
+    const TABLE_ATTRIBUTES: Option<MyNoSqlTableAttributes> = None;
     fn get_partition_key(&self) -> &str;
     fn get_row_key(&self) -> &str;
     fn get_time_stamp(&self) -> Timestamp;
@@ -11,6 +12,11 @@ pub trait MyNoSqlEntity {
 pub trait MyNoSqlEntitySerializer: Sized {
     fn serialize_entity(&self) -> Vec<u8>;
     fn deserialize_entity(src: &[u8]) -> Result<Self, String>;
+
+    /// Encodings which can fail return the error here instead of panic in serialize_entity
+    fn try_serialize_entity(&self) -> Result<Vec<u8>, String> {
+        Ok(self.serialize_entity())
+    }
 }
 
 pub trait GetMyNoSqlEntity {
@@ -0,0 +1,7 @@
+/// Attributes of the table declared next to the entity. Used to create the table and to detect the drift against the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MyNoSqlTableAttributes {
+    pub persist: bool,
+    pub max_partitions_amount: Option<usize>,
+    pub max_rows_per_partition_amount: Option<usize>,
+}
@@ -0,0 +1,68 @@
+//! Prints the bytes per row of the table which rows own their content and of the same table
+//! restored from the snapshot with the buffers shared per partition.
+//!
+//! cargo run --release -p my-no-sql-core --example memory_usage -- [partitions] [rows_per_partition]
+
+use std::sync::Arc;
+
+use my_no_sql_core::{
+    db::{DbRow, DbRowContent, DbRowsStorage, DbTable, DbTableSnapshotCompression},
+    db_json_entity::{DbJsonEntity, JsonTimeStamp},
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let partitions_amount = get_arg(args.next(), 100);
+    let rows_per_partition = get_arg(args.next(), 1000);
+
+    let db_table = create_table(partitions_amount, rows_per_partition);
+
+    let mut snapshot = Vec::new();
+    db_table
+        .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+        .unwrap();
+
+    let shared =
+        DbTable::read_snapshot_with_storage(snapshot.as_slice(), DbRowsStorage::SharedPerPartition)
+            .unwrap();
+
+    println!(
+        "DbRow: {} bytes. DbRowContent: {} bytes",
+        std::mem::size_of::<DbRow>(),
+        std::mem::size_of::<DbRowContent>()
+    );
+    println!("Owned rows:  {}", db_table.get_memory_usage());
+    println!("Shared rows: {}", shared.get_memory_usage());
+}
+
+fn get_arg(arg: Option<String>, default: usize) -> usize {
+    match arg {
+        Some(arg) => arg.parse().expect("Amount must be a number"),
+        None => default,
+    }
+}
+
+fn create_table(partitions_amount: usize, rows_per_partition: usize) -> DbTable {
+    let mut db_table = DbTable::new_with_default_attributes("memory-usage".into());
+    let time_stamp = JsonTimeStamp::now();
+
+    for partition_no in 0..partitions_amount {
+        for row_no in 0..rows_per_partition {
+            let json = format!(
+                r#"{{"PartitionKey":"instrument-{}","RowKey":"{:08}","Bid":1.1034,"Ask":1.1035}}"#,
+                partition_no, row_no
+            );
+
+            let db_row = Arc::new(
+                DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &time_stamp).unwrap(),
+            );
+
+            #[cfg(not(feature = "master-node"))]
+            db_table.insert_or_replace_row(&db_row);
+            #[cfg(feature = "master-node")]
+            db_table.insert_or_replace_row(&db_row, None);
+        }
+    }
+
+    db_table
+}
@@ -8,7 +8,10 @@ use crate::db::{DbRow, RowKeyParameter};
 
 use std::sync::Arc;
 
-use super::{DbRowsContainer, PartitionKey, PartitionKeyParameter};
+use super::{
+    intern_partition_key, DbRowsContainer, DbRowsRangeIterator, PartitionKey,
+    PartitionKeyParameter, RowKeysRange,
+};
 
 pub struct DbPartition {
     pub partition_key: PartitionKey,
@@ -31,7 +34,7 @@ impl EntityWithStrKey for DbPartition {
 impl DbPartition {
     pub fn new(partition_key: impl PartitionKeyParameter) -> Self {
         Self {
-            partition_key: partition_key.into_partition_key(),
+            partition_key: intern_partition_key(partition_key.into_partition_key()),
             rows: DbRowsContainer::new(),
             #[cfg(feature = "master-node")]
             last_read_moment: AtomicDateTimeAsMicroseconds::now(),
@@ -163,6 +166,15 @@ impl DbPartition {
         self.rows.get_highest_row_and_below(row_key)
     }
 
+    pub fn get_rows_range(&self, range: &RowKeysRange) -> DbRowsRangeIterator {
+        DbRowsRangeIterator::new(
+            self.rows.get_range(range),
+            range.reverse,
+            range.skip,
+            range.limit,
+        )
+    }
+
     pub fn is_empty(&self) -> bool {
         self.rows.len() == 0
     }
@@ -3,7 +3,7 @@ use rust_extensions::date_time::DateTimeAsMicroseconds;
 use rust_extensions::sorted_vec::SortedVecOfArcWithStrKey;
 use std::sync::Arc;
 
-use crate::db::DbRow;
+use crate::db::{DbRow, RowKeysRange};
 
 pub struct DbRowsContainer {
     data: SortedVecOfArcWithStrKey<DbRow>,
@@ -108,6 +108,11 @@ impl DbRowsContainer {
         self.data.get_from_bottom_to_key(row_key)
     }
 
+    /// Rows of the range ordered by row key. Paging parameters of the range are not applied
+    pub fn get_range(&self, range: &RowKeysRange) -> &[Arc<DbRow>] {
+        range.get_slice(self.data.iter().as_slice())
+    }
+
     #[cfg(feature = "master-node")]
     pub fn update_expiration_time(
         &mut self,
@@ -6,4 +6,8 @@ pub use db_rows_container::*;
 mod partition_key;
 pub use partition_key::*;
 mod partition_key_parameters;
+mod partition_keys_interner;
+pub use partition_keys_interner::*;
 pub use partition_key_parameters::*;
+mod row_keys_range;
+pub use row_keys_range::*;
@@ -4,6 +4,9 @@ use rust_extensions::sorted_vec::EntityWithStrKey;
 
 use super::PartitionKeyParameter;
 
+/// Key of the partition is interned, so partitions of all the tables with the same key share one allocation.
+/// Journal, GC and the results of the table operations get the clone of it.
+/// Rows keep the key inside of their json, since the content of the row is returned without copying
 #[derive(Clone)]
 pub struct PartitionKey(Arc<String>);
 
@@ -27,6 +30,10 @@ impl PartitionKey {
     pub fn to_arc_of_string(&self) -> Arc<String> {
         self.0.clone()
     }
+
+    pub(crate) fn get_references_amount(&self) -> usize {
+        Arc::strong_count(&self.0)
+    }
 }
 
 impl EntityWithStrKey for PartitionKey {
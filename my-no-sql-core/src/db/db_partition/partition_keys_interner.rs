@@ -0,0 +1,119 @@
+use std::{
+    borrow::Borrow,
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    sync::{Mutex, OnceLock},
+};
+
+use super::PartitionKey;
+
+/// Keys of the interner are not removed until the amount of them reaches this value
+const MIN_KEYS_TO_PURGE: usize = 1024;
+
+static PARTITION_KEYS: OnceLock<Mutex<PartitionKeysInterner>> = OnceLock::new();
+
+/// Tables of the node often have the same partition keys (client id, instrument id, etc).
+/// Partitions with the same key share one allocation of it, no matter which table they belong to
+pub fn intern_partition_key(partition_key: PartitionKey) -> PartitionKey {
+    let interner = PARTITION_KEYS.get_or_init(|| Mutex::new(PartitionKeysInterner::new()));
+    interner.lock().unwrap().intern(partition_key)
+}
+
+struct InternedPartitionKey(PartitionKey);
+
+impl Hash for InternedPartitionKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_str().hash(state)
+    }
+}
+
+impl PartialEq for InternedPartitionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for InternedPartitionKey {}
+
+impl Borrow<str> for InternedPartitionKey {
+    fn borrow(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+struct PartitionKeysInterner {
+    keys: HashSet<InternedPartitionKey>,
+    /// Keys which are held only by the interner are removed once the amount of keys reaches this value
+    purge_at: usize,
+}
+
+impl PartitionKeysInterner {
+    fn new() -> Self {
+        Self {
+            keys: HashSet::new(),
+            purge_at: MIN_KEYS_TO_PURGE,
+        }
+    }
+
+    fn intern(&mut self, partition_key: PartitionKey) -> PartitionKey {
+        if let Some(interned) = self.keys.get(partition_key.as_str()) {
+            return interned.0.clone();
+        }
+
+        if self.keys.len() >= self.purge_at {
+            self.keys.retain(|itm| itm.0.get_references_amount() > 1);
+            self.purge_at = (self.keys.len() * 2).max(MIN_KEYS_TO_PURGE);
+        }
+
+        self.keys
+            .insert(InternedPartitionKey(partition_key.clone()));
+
+        partition_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::db::PartitionKey;
+
+    use super::{PartitionKeysInterner, MIN_KEYS_TO_PURGE};
+
+    #[test]
+    fn test_same_key_is_shared() {
+        let mut interner = PartitionKeysInterner::new();
+
+        let first = interner.intern(PartitionKey::new("pk".to_string()));
+        let second = interner.intern(PartitionKey::new("pk".to_string()));
+        let other = interner.intern(PartitionKey::new("other".to_string()));
+
+        assert!(Arc::ptr_eq(
+            &first.to_arc_of_string(),
+            &second.to_arc_of_string()
+        ));
+        assert!(!Arc::ptr_eq(
+            &first.to_arc_of_string(),
+            &other.to_arc_of_string()
+        ));
+    }
+
+    #[test]
+    fn test_unused_keys_are_purged() {
+        let mut interner = PartitionKeysInterner::new();
+
+        let used = interner.intern(PartitionKey::new("used".to_string()));
+
+        for no in 0..MIN_KEYS_TO_PURGE {
+            interner.intern(PartitionKey::new(format!("unused-{}", no)));
+        }
+
+        assert_eq!(2, interner.keys.len());
+
+        let interned = interner.intern(PartitionKey::new("used".to_string()));
+        assert!(Arc::ptr_eq(
+            &used.to_arc_of_string(),
+            &interned.to_arc_of_string()
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+use std::{ops::Bound, sync::Arc};
+
+use crate::db::DbRow;
+
+/// Row keys interval of the partition with the paging parameters.
+/// Rows are ordered by row key. If reverse is set - skip and limit are applied from the highest row key.
+#[derive(Debug, Clone)]
+pub struct RowKeysRange<'s> {
+    pub from: Bound<&'s str>,
+    pub to: Bound<&'s str>,
+    pub prefix: Option<&'s str>,
+    pub reverse: bool,
+    pub skip: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+impl<'s> RowKeysRange<'s> {
+    pub fn all() -> Self {
+        Self {
+            from: Bound::Unbounded,
+            to: Bound::Unbounded,
+            prefix: None,
+            reverse: false,
+            skip: None,
+            limit: None,
+        }
+    }
+
+    pub fn with_prefix(prefix: &'s str) -> Self {
+        let mut result = Self::all();
+        result.prefix = Some(prefix);
+        result
+    }
+
+    pub fn from_included(mut self, row_key: &'s str) -> Self {
+        self.from = Bound::Included(row_key);
+        self
+    }
+
+    pub fn from_excluded(mut self, row_key: &'s str) -> Self {
+        self.from = Bound::Excluded(row_key);
+        self
+    }
+
+    pub fn to_included(mut self, row_key: &'s str) -> Self {
+        self.to = Bound::Included(row_key);
+        self
+    }
+
+    pub fn to_excluded(mut self, row_key: &'s str) -> Self {
+        self.to = Bound::Excluded(row_key);
+        self
+    }
+
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    pub fn skip(mut self, skip: usize) -> Self {
+        self.skip = Some(skip);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn is_in_range(&self, row_key: &str) -> bool {
+        let from_ok = match self.from {
+            Bound::Included(from) => row_key >= from,
+            Bound::Excluded(from) => row_key > from,
+            Bound::Unbounded => true,
+        };
+
+        let to_ok = match self.to {
+            Bound::Included(to) => row_key <= to,
+            Bound::Excluded(to) => row_key < to,
+            Bound::Unbounded => true,
+        };
+
+        let prefix_ok = match self.prefix {
+            Some(prefix) => row_key.starts_with(prefix),
+            None => true,
+        };
+
+        from_ok && to_ok && prefix_ok
+    }
+
+    /// Rows must be sorted by row key. Paging parameters are not applied
+    pub fn get_slice<'r>(&self, rows: &'r [Arc<DbRow>]) -> &'r [Arc<DbRow>] {
+        let mut start = match self.from {
+            Bound::Included(from) => rows.partition_point(|itm| itm.get_row_key() < from),
+            Bound::Excluded(from) => rows.partition_point(|itm| itm.get_row_key() <= from),
+            Bound::Unbounded => 0,
+        };
+
+        let mut end = match self.to {
+            Bound::Included(to) => rows.partition_point(|itm| itm.get_row_key() <= to),
+            Bound::Excluded(to) => rows.partition_point(|itm| itm.get_row_key() < to),
+            Bound::Unbounded => rows.len(),
+        };
+
+        if let Some(prefix) = self.prefix {
+            // Keys with the same prefix go one by one right after the prefix itself
+            start = start.max(rows.partition_point(|itm| itm.get_row_key() < prefix));
+            end = end.min(rows.partition_point(|itm| {
+                let row_key = itm.get_row_key();
+                row_key < prefix || row_key.starts_with(prefix)
+            }));
+        }
+
+        if start >= end {
+            return &[];
+        }
+
+        &rows[start..end]
+    }
+}
+
+pub struct DbRowsRangeIterator<'a> {
+    iterator: std::slice::Iter<'a, Arc<DbRow>>,
+    reverse: bool,
+}
+
+impl<'a> DbRowsRangeIterator<'a> {
+    /// Skip and limit are applied to the rows in the order of iteration
+    pub fn new(
+        rows: &'a [Arc<DbRow>],
+        reverse: bool,
+        skip: Option<usize>,
+        limit: Option<usize>,
+    ) -> Self {
+        let skip = skip.unwrap_or(0).min(rows.len());
+        let len = match limit {
+            Some(limit) => limit.min(rows.len() - skip),
+            None => rows.len() - skip,
+        };
+
+        let rows = if reverse {
+            let end = rows.len() - skip;
+            &rows[end - len..end]
+        } else {
+            &rows[skip..skip + len]
+        };
+
+        Self {
+            iterator: rows.iter(),
+            reverse,
+        }
+    }
+}
+
+impl<'a> Iterator for DbRowsRangeIterator<'a> {
+    type Item = &'a Arc<DbRow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reverse {
+            self.iterator.next_back()
+        } else {
+            self.iterator.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iterator.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for DbRowsRangeIterator<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::DbRow,
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::{DbRowsRangeIterator, RowKeysRange};
+
+    fn create_rows(row_keys: &[&str]) -> Vec<Arc<DbRow>> {
+        let time_stamp = JsonTimeStamp::now();
+
+        row_keys
+            .iter()
+            .map(|row_key| {
+                let json = format!(r#"{{"PartitionKey":"pk","RowKey":"{}"}}"#, row_key);
+                Arc::new(
+                    DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &time_stamp).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn get_row_keys(range: &RowKeysRange, rows: &[Arc<DbRow>]) -> Vec<String> {
+        DbRowsRangeIterator::new(
+            range.get_slice(rows),
+            range.reverse,
+            range.skip,
+            range.limit,
+        )
+        .map(|itm| itm.get_row_key().to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_bounds() {
+        let rows = create_rows(&["a", "b", "c", "d", "e"]);
+
+        let range = RowKeysRange::all().from_included("b").to_excluded("d");
+        assert_eq!(vec!["b", "c"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::all().from_excluded("b").to_included("d");
+        assert_eq!(vec!["c", "d"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::all().from_included("bb").to_included("zz");
+        assert_eq!(vec!["c", "d", "e"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::all().from_included("d").to_included("b");
+        assert!(get_row_keys(&range, &rows).is_empty());
+    }
+
+    #[test]
+    fn test_prefix() {
+        let rows = create_rows(&["a", "ab", "abc", "abd", "ac", "b"]);
+
+        let range = RowKeysRange::with_prefix("ab");
+        assert_eq!(vec!["ab", "abc", "abd"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::with_prefix("ab").from_excluded("ab");
+        assert_eq!(vec!["abc", "abd"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::with_prefix("x");
+        assert!(get_row_keys(&range, &rows).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_and_paging() {
+        let rows = create_rows(&["a", "b", "c", "d", "e"]);
+
+        let range = RowKeysRange::all().skip(1).limit(2);
+        assert_eq!(vec!["b", "c"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::all().reverse().skip(1).limit(2);
+        assert_eq!(vec!["d", "c"], get_row_keys(&range, &rows));
+
+        let range = RowKeysRange::all().reverse().skip(10);
+        assert!(get_row_keys(&range, &rows).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use my_json::json_writer::JsonObject;
 #[cfg(feature = "master-node")]
@@ -8,26 +8,36 @@ use rust_extensions::date_time::DateTimeAsMicroseconds;
 use rust_extensions::sorted_vec::EntityWithStrKey;
 
 use crate::db::PartitionKeyParameter;
-use crate::db_json_entity::DbJsonEntity;
+use crate::db_json_entity::{get_json_field_slice, DbJsonEntity, RawJsonValue};
 
-use super::RowKeyParameter;
+use super::{
+    DbRowContent, DbRowContentPosition, DbRowFieldsIndex, DbRowKeyValuePosition, RowKeyParameter,
+    HEAP_ALLOCATION_OVERHEAD,
+};
 
+/// Positions are kept as u32 and the fields index is boxed, since tables with millions of rows are memory bound
 pub struct DbRow {
-    partition_key: crate::db_json_entity::KeyValueContentPosition,
-    row_key: crate::db_json_entity::KeyValueContentPosition,
-    raw: Vec<u8>,
+    partition_key: DbRowContentPosition,
+    row_key: DbRowContentPosition,
+    raw: DbRowContent,
     #[cfg(feature = "master-node")]
     expires_value: AtomicDateTimeAsMicroseconds,
     #[cfg(feature = "master-node")]
-    expires: Option<crate::db_json_entity::JsonKeyValuePosition>,
-    #[cfg(feature = "master-node")]
-    pub time_stamp: crate::db_json_entity::KeyValueContentPosition,
+    expires: Option<DbRowKeyValuePosition>,
+    /// Row can have no TimeStamp only without master-node
+    pub time_stamp: DbRowContentPosition,
     #[cfg(feature = "master-node")]
     last_read_access: AtomicDateTimeAsMicroseconds,
+    fields_index: OnceLock<Box<DbRowFieldsIndex>>,
 }
 
 impl DbRow {
     pub fn new(db_json_entity: DbJsonEntity, raw: Vec<u8>) -> Self {
+        Self::new_with_content(db_json_entity, raw.into())
+    }
+
+    /// Positions of db_json_entity are the positions inside of the content
+    pub fn new_with_content(db_json_entity: DbJsonEntity, raw: DbRowContent) -> Self {
         #[cfg(feature = "debug_db_row")]
         println!(
             "Created DbRow: PK:{}. RK:{}. Expires{:?}",
@@ -44,10 +54,15 @@ impl DbRow {
 
         Self {
             raw,
-            partition_key: db_json_entity.partition_key.value,
-            row_key: db_json_entity.row_key.value,
+            partition_key: db_json_entity.partition_key.value.into(),
+            row_key: db_json_entity.row_key.value.into(),
             #[cfg(feature = "master-node")]
-            time_stamp: time_stamp.value,
+            time_stamp: time_stamp.value.into(),
+            #[cfg(not(feature = "master-node"))]
+            time_stamp: match db_json_entity.time_stamp {
+                Some(time_stamp) => time_stamp.value.into(),
+                None => DbRowContentPosition::NONE,
+            },
             #[cfg(feature = "master-node")]
             expires_value: if let Some(expires_value) = db_json_entity.expires_value {
                 AtomicDateTimeAsMicroseconds::new(expires_value.unix_microseconds)
@@ -55,9 +70,10 @@ impl DbRow {
                 AtomicDateTimeAsMicroseconds::new(0)
             },
             #[cfg(feature = "master-node")]
-            expires: db_json_entity.expires,
+            expires: db_json_entity.expires.map(|itm| itm.into()),
             #[cfg(feature = "master-node")]
             last_read_access: AtomicDateTimeAsMicroseconds::new(time_stamp_value.unix_microseconds),
+            fields_index: OnceLock::new(),
         }
     }
 
@@ -69,13 +85,78 @@ impl DbRow {
         self.row_key.get_str_value(&self.raw)
     }
 
+    #[deprecated(note = "Row may have no TimeStamp. Use get_time_stamp_opt")]
     pub fn get_time_stamp(&self) -> &str {
-        self.row_key.get_str_value(&self.raw)
+        self.get_time_stamp_opt().unwrap_or_default()
     }
+
+    pub fn get_time_stamp_opt(&self) -> Option<&str> {
+        if self.time_stamp.is_none() {
+            return None;
+        }
+
+        Some(self.time_stamp.get_str_value(&self.raw))
+    }
+
     pub fn get_src_as_slice(&self) -> &[u8] {
         self.raw.as_slice()
     }
 
+    pub fn get_content(&self) -> &DbRowContent {
+        &self.raw
+    }
+
+    /// Bytes the row holds alone: the struct, the counters of Arc, owned content and the fields index if it is built.
+    /// Shared buffer of the bulk loaded rows is not included
+    pub fn get_memory_size(&self) -> usize {
+        let mut result = HEAP_ALLOCATION_OVERHEAD
+            + std::mem::size_of::<DbRow>()
+            + 2 * std::mem::size_of::<usize>();
+
+        if !self.raw.is_shared() {
+            result += HEAP_ALLOCATION_OVERHEAD + self.raw.get_owned_heap_size();
+        }
+
+        if let Some(fields_index) = self.fields_index.get() {
+            result += 2 * HEAP_ALLOCATION_OVERHEAD
+                + std::mem::size_of::<DbRowFieldsIndex>()
+                + fields_index.get_heap_size();
+        }
+
+        result
+    }
+
+    /// Raw json value of the field. Nested fields are separated by dots: Address.City.
+    /// Fields index is used if it is already built, otherwise the row is scanned.
+    /// Value is taken from the stored content, so under master-node Expires may differ from get_expires()
+    pub fn get_field(&self, path: &str) -> Option<&[u8]> {
+        if let Some(fields_index) = self.fields_index.get() {
+            return fields_index.get_by_path(&self.raw, path);
+        }
+
+        let names: Vec<&str> = path.split('.').collect();
+        get_json_field_slice(&self.raw, names.as_slice())
+    }
+
+    pub fn get_field_value(&self, path: &str) -> Option<RawJsonValue> {
+        RawJsonValue::from_slice(self.get_field(path)?)
+    }
+
+    /// Raw json values of the fields in the order of the paths. Builds the fields index on the first call
+    pub fn project(&self, paths: &[&str]) -> Vec<Option<&[u8]>> {
+        let fields_index = self.get_fields_index();
+
+        paths
+            .iter()
+            .map(|path| fields_index.get_by_path(&self.raw, path))
+            .collect()
+    }
+
+    pub fn get_fields_index(&self) -> &DbRowFieldsIndex {
+        self.fields_index
+            .get_or_init(|| Box::new(DbRowFieldsIndex::new(&self.raw)))
+    }
+
     #[cfg(feature = "master-node")]
     pub fn update_last_read_access(
         &self,
@@ -120,23 +201,23 @@ impl DbRow {
         if expires_value.is_none() {
             if let Some(expires) = &self.expires {
                 if let Some(before_separator) =
-                    find_json_separator_before(&self.raw, expires.key.start - 1)
+                    find_json_separator_before(&self.raw, expires.key.start() - 1)
                 {
                     unsafe {
                         out.push_str(std::str::from_utf8_unchecked(&self.raw[..before_separator]));
                         out.push_str(std::str::from_utf8_unchecked(
-                            &self.raw[expires.value.end..],
+                            &self.raw[expires.value.end()..],
                         ));
                     }
                     return;
                 }
 
                 if let Some(after_separator) =
-                    find_json_separator_after(&self.raw, expires.value.end)
+                    find_json_separator_after(&self.raw, expires.value.end())
                 {
                     unsafe {
                         out.push_str(std::str::from_utf8_unchecked(
-                            &self.raw[..expires.key.start],
+                            &self.raw[..expires.key.start()],
                         ));
                         out.push_str(std::str::from_utf8_unchecked(&self.raw[after_separator..]));
                     }
@@ -145,10 +226,10 @@ impl DbRow {
 
                 unsafe {
                     out.push_str(std::str::from_utf8_unchecked(
-                        &self.raw[..expires.key.start],
+                        &self.raw[..expires.key.start()],
                     ));
                     out.push_str(std::str::from_utf8_unchecked(
-                        &self.raw[expires.value.end..],
+                        &self.raw[expires.value.end()..],
                     ));
                 }
             } else {
@@ -165,11 +246,11 @@ impl DbRow {
         unsafe {
             if let Some(expires) = &self.expires {
                 out.push_str(std::str::from_utf8_unchecked(
-                    &self.raw[..expires.key.start],
+                    &self.raw[..expires.key.start()],
                 ));
                 inject_expires(out, expires_value);
                 out.push_str(std::str::from_utf8_unchecked(
-                    &self.raw[expires.value.end..],
+                    &self.raw[expires.value.end()..],
                 ));
             } else {
                 let end_of_json = crate::db_json_entity::get_the_end_of_the_json(&self.raw);
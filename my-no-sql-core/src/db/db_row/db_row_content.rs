@@ -0,0 +1,128 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::db_json_entity::{JsonKeyValuePosition, KeyValueContentPosition};
+
+/// Approximate bookkeeping and alignment bytes of the allocator per heap allocation.
+/// Used by the memory usage estimations, since small allocations are the main overhead of the rows
+pub const HEAP_ALLOCATION_OVERHEAD: usize = 16;
+
+/// Json of the row. Rows which are loaded in bulk (snapshot restore) point to the buffer of the whole partition
+/// instead of having an allocation each. Shared buffer is released only when all the rows pointing to it are dropped
+pub enum DbRowContent {
+    Owned(Box<[u8]>),
+    Shared {
+        buffer: Arc<[u8]>,
+        start: u32,
+        end: u32,
+    },
+}
+
+impl DbRowContent {
+    /// Range must be inside of the buffer and end must fit into u32. Snapshot reader guarantees both
+    pub fn new_shared(buffer: Arc<[u8]>, start: usize, end: usize) -> Self {
+        debug_assert!(start <= end && end <= buffer.len());
+        debug_assert!(end <= u32::MAX as usize);
+
+        Self::Shared {
+            buffer,
+            start: start as u32,
+            end: end as u32,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            DbRowContent::Owned(content) => content,
+            DbRowContent::Shared { buffer, start, end } => &buffer[*start as usize..*end as usize],
+        }
+    }
+
+    pub fn is_shared(&self) -> bool {
+        matches!(self, DbRowContent::Shared { .. })
+    }
+
+    pub fn get_shared_buffer(&self) -> Option<&Arc<[u8]>> {
+        match self {
+            DbRowContent::Owned(_) => None,
+            DbRowContent::Shared { buffer, .. } => Some(buffer),
+        }
+    }
+
+    /// Heap allocated by the row alone. Shared buffer is not included
+    pub fn get_owned_heap_size(&self) -> usize {
+        match self {
+            DbRowContent::Owned(content) => content.len(),
+            DbRowContent::Shared { .. } => 0,
+        }
+    }
+}
+
+impl From<Vec<u8>> for DbRowContent {
+    fn from(src: Vec<u8>) -> Self {
+        Self::Owned(src.into_boxed_slice())
+    }
+}
+
+impl Deref for DbRowContent {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+/// Same as KeyValueContentPosition, but with u32 offsets. Content of the row is never bigger than 4Gb
+#[derive(Debug, Clone, Copy)]
+pub struct DbRowContentPosition {
+    start: u32,
+    end: u32,
+}
+
+impl DbRowContentPosition {
+    /// Position of the value which is not present in the content
+    pub const NONE: Self = Self { start: 0, end: 0 };
+
+    pub fn is_none(&self) -> bool {
+        self.end == 0
+    }
+
+    pub fn start(&self) -> usize {
+        self.start as usize
+    }
+
+    pub fn end(&self) -> usize {
+        self.end as usize
+    }
+
+    pub fn get_value<'s>(&self, raw: &'s [u8]) -> &'s str {
+        std::str::from_utf8(&raw[self.start()..self.end()]).unwrap()
+    }
+
+    pub fn get_str_value<'s>(&self, raw: &'s [u8]) -> &'s str {
+        std::str::from_utf8(&raw[self.start() + 1..self.end() - 1]).unwrap()
+    }
+}
+
+impl From<KeyValueContentPosition> for DbRowContentPosition {
+    fn from(src: KeyValueContentPosition) -> Self {
+        Self {
+            start: src.start as u32,
+            end: src.end as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DbRowKeyValuePosition {
+    pub key: DbRowContentPosition,
+    pub value: DbRowContentPosition,
+}
+
+impl From<JsonKeyValuePosition> for DbRowKeyValuePosition {
+    fn from(src: JsonKeyValuePosition) -> Self {
+        Self {
+            key: src.key.into(),
+            value: src.value.into(),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::db_json_entity::{get_json_field_slice, RawJsonObjectIterator};
+
+#[derive(Debug, Clone, Copy)]
+struct DbRowFieldOffset {
+    name_start: u32,
+    name_end: u32,
+    value_start: u32,
+    value_end: u32,
+}
+
+impl DbRowFieldOffset {
+    fn get_name<'s>(&self, raw: &'s [u8]) -> &'s [u8] {
+        &raw[self.name_start as usize..self.name_end as usize]
+    }
+
+    fn get_value<'s>(&self, raw: &'s [u8]) -> &'s [u8] {
+        &raw[self.value_start as usize..self.value_end as usize]
+    }
+}
+
+/// Positions of the top level fields of the row, sorted by the field name.
+/// Names are compared as they are written in json, so escaped names are not found through the index
+#[derive(Debug)]
+pub struct DbRowFieldsIndex {
+    fields: Vec<DbRowFieldOffset>,
+}
+
+impl DbRowFieldsIndex {
+    pub fn new(raw: &[u8]) -> Self {
+        let mut fields: Vec<DbRowFieldOffset> = RawJsonObjectIterator::new(raw)
+            .map(|itm| DbRowFieldOffset {
+                name_start: itm.name_start as u32,
+                name_end: (itm.name_start + itm.name.len()) as u32,
+                value_start: itm.value_start as u32,
+                value_end: itm.value_end as u32,
+            })
+            .collect();
+
+        // Sort is stable, so the first of the duplicated fields wins - same as the scan does
+        fields.sort_by(|a, b| a.get_name(raw).cmp(b.get_name(raw)));
+        fields.shrink_to_fit();
+
+        Self { fields }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get_heap_size(&self) -> usize {
+        self.fields.capacity() * std::mem::size_of::<DbRowFieldOffset>()
+    }
+
+    /// Raw json value of the top level field
+    pub fn get<'s>(&self, raw: &'s [u8], name: &str) -> Option<&'s [u8]> {
+        let index = self
+            .fields
+            .partition_point(|itm| itm.get_name(raw) < name.as_bytes());
+
+        let field = self.fields.get(index)?;
+
+        if field.get_name(raw) != name.as_bytes() {
+            return None;
+        }
+
+        Some(field.get_value(raw))
+    }
+
+    /// Raw json value by the path. Nested fields are separated by dots and are found by scanning the nested object
+    pub fn get_by_path<'s>(&self, raw: &'s [u8], path: &str) -> Option<&'s [u8]> {
+        let mut names = path.split('.');
+        let value = self.get(raw, names.next()?)?;
+
+        let rest: Vec<&str> = names.collect();
+
+        if rest.is_empty() {
+            return Some(value);
+        }
+
+        if value.first() != Some(&b'{') {
+            return None;
+        }
+
+        get_json_field_slice(value, rest.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db_json_entity::{DbJsonEntity, JsonTimeStamp, RawJsonValue};
+
+    const JSON: &str = r#"{"PartitionKey":"pk","RowKey":"rk","Status":"Active","Amount":15,
+        "Address":{"City":"Kyiv","Geo":{"Lat":50.45}},"Tags":["a","b"],"Status":"Duplicated"}"#;
+
+    #[test]
+    fn test_get_field_with_and_without_index() {
+        let db_row =
+            DbJsonEntity::parse_into_db_row(JSON.as_bytes().into(), &JsonTimeStamp::now()).unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(Some(&b"\"Active\""[..]), db_row.get_field("Status"));
+            assert_eq!(Some(&b"15"[..]), db_row.get_field("Amount"));
+            assert_eq!(Some(&br#"["a","b"]"#[..]), db_row.get_field("Tags"));
+            assert_eq!(Some(&b"50.45"[..]), db_row.get_field("Address.Geo.Lat"));
+            assert_eq!(None, db_row.get_field("Address.Missing"));
+            assert_eq!(None, db_row.get_field("Amount.Value"));
+            assert_eq!(None, db_row.get_field("Missing"));
+
+            assert_eq!(
+                Some(RawJsonValue::Number("15")),
+                db_row.get_field_value("Amount")
+            );
+
+            // Second pass goes through the index built by project
+            db_row.project(&["Status"]);
+        }
+    }
+
+    #[test]
+    fn test_project() {
+        let db_row =
+            DbJsonEntity::parse_into_db_row(JSON.as_bytes().into(), &JsonTimeStamp::now()).unwrap();
+
+        let result = db_row.project(&["RowKey", "Address.City", "Missing", "Status"]);
+
+        assert_eq!(
+            vec![
+                Some(&b"\"rk\""[..]),
+                Some(&b"\"Kyiv\""[..]),
+                None,
+                Some(&b"\"Active\""[..]),
+            ],
+            result
+        );
+    }
+}
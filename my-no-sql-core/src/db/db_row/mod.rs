@@ -5,3 +5,7 @@ mod row_key_parameter;
 #[cfg(feature = "master-node")]
 mod test_expires_update;
 pub use row_key_parameter::*;
+mod db_row_fields_index;
+pub use db_row_fields_index::*;
+mod db_row_content;
+pub use db_row_content::*;
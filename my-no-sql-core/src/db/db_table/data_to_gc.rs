@@ -4,9 +4,41 @@ use rust_extensions::sorted_vec::{EntityWithStrKey, SortedVecWithStrKey};
 
 use crate::db::{DbRow, PartitionKey};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataToGcReason {
+    Expired,
+    MaxPartitionsAmount,
+    MaxRowsPerPartitionAmount,
+    MaxTableSize,
+}
+
+pub struct PartitionToExpire {
+    pub partition_key: PartitionKey,
+    pub reason: DataToGcReason,
+}
+
+impl EntityWithStrKey for PartitionToExpire {
+    fn get_key(&self) -> &str {
+        self.partition_key.as_str()
+    }
+}
+
 pub struct DbRowsToExpire {
     pub partition_key: PartitionKey,
     pub rows: Vec<Arc<DbRow>>,
+    /// Reason the row with the same index was selected by
+    pub reasons: Vec<DataToGcReason>,
+}
+
+impl DbRowsToExpire {
+    pub fn get_row_reason(&self, row_key: &str) -> Option<DataToGcReason> {
+        let index = self
+            .rows
+            .iter()
+            .position(|itm| itm.get_row_key() == row_key)?;
+
+        self.reasons.get(index).copied()
+    }
 }
 
 impl EntityWithStrKey for DbRowsToExpire {
@@ -16,7 +48,7 @@ impl EntityWithStrKey for DbRowsToExpire {
 }
 
 pub struct DataToGc {
-    pub partitions: SortedVecWithStrKey<PartitionKey>,
+    pub partitions: SortedVecWithStrKey<PartitionToExpire>,
     pub db_rows: SortedVecWithStrKey<DbRowsToExpire>,
 }
 
@@ -28,19 +60,29 @@ impl DataToGc {
         }
     }
 
-    pub fn add_partition_to_expire(&mut self, partition_key: PartitionKey) {
+    /// If partition is already selected - the first reason is kept
+    pub fn add_partition_to_expire(&mut self, partition_key: PartitionKey, reason: DataToGcReason) {
         match self
             .partitions
             .insert_or_if_not_exists(partition_key.as_str())
         {
             rust_extensions::sorted_vec::InsertIfNotExists::Insert(entry) => {
-                entry.insert(partition_key)
+                entry.insert(PartitionToExpire {
+                    partition_key,
+                    reason,
+                })
             }
             rust_extensions::sorted_vec::InsertIfNotExists::Exists(_) => {}
         }
     }
 
-    pub fn add_rows_to_expire(&mut self, partition_key: &PartitionKey, rows: Vec<Arc<DbRow>>) {
+    /// If row is already selected - the first reason is kept
+    pub fn add_rows_to_expire(
+        &mut self,
+        partition_key: &PartitionKey,
+        rows: Vec<Arc<DbRow>>,
+        reason: DataToGcReason,
+    ) {
         if self.partitions.contains(partition_key.as_str()) {
             return;
         }
@@ -49,11 +91,19 @@ impl DataToGc {
             rust_extensions::sorted_vec::InsertOrUpdateEntry::Insert(entry) => {
                 entry.insert(DbRowsToExpire {
                     partition_key: partition_key.clone(),
+                    reasons: vec![reason; rows.len()],
                     rows,
                 })
             }
             rust_extensions::sorted_vec::InsertOrUpdateEntry::Update(entry) => {
-                entry.item.rows.extend(rows)
+                for db_row in rows {
+                    if entry.item.get_row_reason(db_row.get_row_key()).is_some() {
+                        continue;
+                    }
+
+                    entry.item.rows.push(db_row);
+                    entry.item.reasons.push(reason);
+                }
             }
         }
     }
@@ -62,7 +112,64 @@ impl DataToGc {
         self.partitions.contains(partition_key)
     }
 
+    pub fn get_partition_reason(&self, partition_key: &str) -> Option<DataToGcReason> {
+        self.partitions.get(partition_key).map(|itm| itm.reason)
+    }
+
     pub fn has_data_to_gc(&self) -> bool {
         self.partitions.len() > 0 || self.db_rows.len() > 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::PartitionKey,
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::{DataToGc, DataToGcReason};
+
+    #[test]
+    fn test_reason_is_kept_per_row() {
+        let now = JsonTimeStamp::now();
+
+        let rows: Vec<_> = ["rk1", "rk2"]
+            .into_iter()
+            .map(|row_key| {
+                let test_json = format!(r#"{{"PartitionKey":"pk","RowKey":"{}"}}"#, row_key);
+                let db_row =
+                    DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+                Arc::new(db_row)
+            })
+            .collect();
+
+        let partition_key: PartitionKey = "pk".into();
+
+        let mut data_to_gc = DataToGc::new();
+        data_to_gc.add_rows_to_expire(
+            &partition_key,
+            vec![rows[0].clone()],
+            DataToGcReason::Expired,
+        );
+        data_to_gc.add_rows_to_expire(
+            &partition_key,
+            rows.clone(),
+            DataToGcReason::MaxRowsPerPartitionAmount,
+        );
+
+        let rows_to_expire = data_to_gc.db_rows.get("pk").unwrap();
+
+        assert_eq!(2, rows_to_expire.rows.len());
+        assert_eq!(
+            Some(DataToGcReason::Expired),
+            rows_to_expire.get_row_reason("rk1")
+        );
+        assert_eq!(
+            Some(DataToGcReason::MaxRowsPerPartitionAmount),
+            rows_to_expire.get_row_reason("rk2")
+        );
+    }
+}
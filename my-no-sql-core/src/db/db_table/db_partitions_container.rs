@@ -110,6 +110,46 @@ impl DbPartitionsContainer {
         Some(result)
     }
 
+    /// Least recently read partitions which have to be removed to make the size of the table not bigger than max_table_size.
+    /// Partitions for which is_already_selected returns true are considered as removed
+    #[cfg(feature = "master-node")]
+    pub fn get_partitions_to_gc_by_max_size(
+        &self,
+        max_table_size: usize,
+        is_already_selected: impl Fn(&str) -> bool,
+    ) -> Option<Vec<PartitionKey>> {
+        let mut table_size = 0;
+        let mut candidates = Vec::new();
+
+        for db_partition in self.partitions.iter() {
+            if is_already_selected(db_partition.partition_key.as_str()) {
+                continue;
+            }
+
+            table_size += db_partition.get_content_size();
+            candidates.push(db_partition);
+        }
+
+        if table_size <= max_table_size {
+            return None;
+        }
+
+        candidates.sort_by_key(|itm| itm.get_last_read_moment().unix_microseconds);
+
+        let mut result = Vec::new();
+
+        for db_partition in candidates {
+            if table_size <= max_table_size {
+                break;
+            }
+
+            table_size -= db_partition.get_content_size();
+            result.push(db_partition.partition_key.clone());
+        }
+
+        Some(result)
+    }
+
     #[cfg(feature = "master-node")]
     pub fn get_partitions_to_gc_by_max_amount(
         &self,
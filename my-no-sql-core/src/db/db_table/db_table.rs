@@ -4,11 +4,17 @@ use rust_extensions::date_time::DateTimeAsMicroseconds;
 use rust_extensions::sorted_vec::{EntityWithStrKey, SortedVecWithStrKey};
 use std::sync::Arc;
 
-use crate::db::{DbPartition, DbRow, PartitionKey, PartitionKeyParameter, RowKeyParameter};
+use crate::{
+    db::{DbPartition, DbRow, PartitionKey, PartitionKeyParameter, RowKeyParameter, RowKeysRange},
+    query::FilterExpression,
+};
 
 #[cfg(feature = "master-node")]
 use super::DbTableAttributes;
-use super::{AllDbRowsIterator, AvgSize, ByRowKeyIterator, DbPartitionsContainer, DbTableName};
+use super::{
+    AllDbRowsIterator, AvgSize, ByRowKeyIterator, DbPartitionsContainer, DbTableJournal,
+    DbTableJournalOperation, DbTableJournalSettings, DbTableName, RangeDbRowsIterator,
+};
 
 pub struct DbTable {
     pub name: DbTableName,
@@ -18,6 +24,7 @@ pub struct DbTable {
     pub last_write_moment: DateTimeAsMicroseconds,
     #[cfg(feature = "master-node")]
     pub attributes: DbTableAttributes,
+    pub journal: Option<DbTableJournal>,
 }
 
 impl EntityWithStrKey for DbTable {
@@ -33,11 +40,40 @@ impl DbTable {
             name,
             partitions: DbPartitionsContainer::new(),
             avg_size: AvgSize::new(),
+            journal: None,
         }
     }
 
+    /// Same as new, but compiles with or without "master-node" feature
+    pub fn new_with_default_attributes(name: DbTableName) -> Self {
+        #[cfg(not(feature = "master-node"))]
+        {
+            Self::new(name)
+        }
+        #[cfg(feature = "master-node")]
+        {
+            Self::new(name, DbTableAttributes::create_default())
+        }
+    }
+
+    /// Every change of the table is recorded with the sequence number from now on
+    pub fn enable_journal(&mut self, settings: DbTableJournalSettings) {
+        self.journal = Some(DbTableJournal::new(settings));
+    }
+
+    pub fn get_journal(&self) -> Option<&DbTableJournal> {
+        self.journal.as_ref()
+    }
+
+    fn add_to_journal(&mut self, operation: impl FnOnce() -> DbTableJournalOperation) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.add(operation());
+        }
+    }
+
+    /// Partition is restored as is. Journal gets all the rows of the restored partition
     pub fn restore_partition(&mut self, db_partition: DbPartition) {
-        self.partitions.insert(db_partition);
+        self.replace_partition(db_partition);
     }
 
     pub fn get_partitions_amount(&self) -> usize {
@@ -66,13 +102,31 @@ impl DbTable {
         ByRowKeyIterator::new(self.partitions.get_partitions(), row_key, skip, limit)
     }
 
+    /// Range is applied to the rows of every partition. Skip and limit - to the rows of the whole table
+    pub fn get_rows_range<'s>(&'s self, range: RowKeysRange<'s>) -> RangeDbRowsIterator<'s> {
+        RangeDbRowsIterator::new(self.partitions.get_partitions(), range)
+    }
+
+    /// Rows of the whole table which match the filter. Iteration stops when limit of rows is found
+    pub fn query<'s>(
+        &'s self,
+        filter: &'s FilterExpression,
+        limit: Option<usize>,
+    ) -> impl Iterator<Item = (&'s DbPartition, &'s Arc<DbRow>)> + 's {
+        self.get_all_rows(None, None)
+            .filter(move |(_, db_row)| filter.is_match_db_row(db_row))
+            .take(limit.unwrap_or(usize::MAX))
+    }
+
     pub fn get_table_as_json_array(&self) -> JsonArrayWriter {
+        self.get_table_range_as_json_array(&RowKeysRange::all())
+    }
+
+    pub fn get_table_range_as_json_array(&self, range: &RowKeysRange) -> JsonArrayWriter {
         let mut json_array_writer = JsonArrayWriter::new();
 
-        for db_partition in self.partitions.get_partitions() {
-            for db_row in db_partition.get_all_rows() {
-                json_array_writer.write(db_row.as_ref())
-            }
+        for (_, db_row) in self.get_rows_range(range.clone()) {
+            json_array_writer.write(db_row.as_ref())
         }
 
         json_array_writer
@@ -96,10 +150,18 @@ impl DbTable {
     }
 
     pub fn get_partition_as_json_array(&self, partition_key: &str) -> Option<JsonArrayWriter> {
+        self.get_partition_range_as_json_array(partition_key, &RowKeysRange::all())
+    }
+
+    pub fn get_partition_range_as_json_array(
+        &self,
+        partition_key: &str,
+        range: &RowKeysRange,
+    ) -> Option<JsonArrayWriter> {
         let mut json_array_writer = JsonArrayWriter::new();
 
         if let Some(db_partition) = self.partitions.get(partition_key) {
-            for db_row in db_partition.get_all_rows() {
+            for db_row in db_partition.get_rows_range(range) {
                 json_array_writer.write(db_row.as_ref())
             }
         }
@@ -132,6 +194,7 @@ impl DbTable {
         #[cfg(feature = "master-node")] set_last_write_moment: Option<DateTimeAsMicroseconds>,
     ) -> (PartitionKey, Option<Arc<DbRow>>) {
         self.avg_size.add(db_row);
+        self.add_to_journal(|| DbTableJournalOperation::InsertOrReplace(db_row.clone()));
 
         let db_partition = self.partitions.add_partition_if_not_exists(db_row);
 
@@ -165,7 +228,9 @@ impl DbTable {
             }
         }
         if result {
-            Some(db_partition.partition_key.clone())
+            let partition_key = db_partition.partition_key.clone();
+            self.add_to_journal(|| DbTableJournalOperation::InsertOrReplace(db_row.clone()));
+            Some(partition_key)
         } else {
             None
         }
@@ -180,6 +245,7 @@ impl DbTable {
     ) -> (PartitionKey, Vec<Arc<DbRow>>) {
         for db_row in db_rows {
             self.avg_size.add(db_row);
+            self.add_to_journal(|| DbTableJournalOperation::InsertOrReplace(db_row.clone()));
         }
 
         let db_partition = self.partitions.add_partition_if_not_exists(partition_key);
@@ -194,8 +260,28 @@ impl DbTable {
         (db_partition.partition_key.clone(), result)
     }
 
+    /// Journal gets the removal of the partition if it exists and all the rows of the new one
     #[inline]
     pub fn init_partition(&mut self, db_partition: DbPartition) {
+        self.replace_partition(db_partition);
+    }
+
+    fn replace_partition(&mut self, db_partition: DbPartition) {
+        if let Some(journal) = self.journal.as_mut() {
+            if self
+                .partitions
+                .has_partition(db_partition.partition_key.as_str())
+            {
+                journal.add(DbTableJournalOperation::DeletePartition(
+                    db_partition.partition_key.clone(),
+                ));
+            }
+
+            for db_row in db_partition.get_all_rows() {
+                journal.add(DbTableJournalOperation::InsertOrReplace(db_row.clone()));
+            }
+        }
+
         self.partitions.insert(db_partition);
     }
 }
@@ -233,6 +319,11 @@ impl DbTable {
             self.partitions.remove(partition_key.as_str());
         }
 
+        self.add_to_journal(|| DbTableJournalOperation::DeleteRow {
+            partition_key: partition_key.clone(),
+            row_key: removed_row.get_row_key().to_string(),
+        });
+
         return Some((partition_key, removed_row, partition_is_empty));
     }
 
@@ -265,6 +356,15 @@ impl DbTable {
             self.partitions.remove(partition_key.as_str());
         }
 
+        if let Some(journal) = self.journal.as_mut() {
+            for removed_row in removed_rows.iter() {
+                journal.add(DbTableJournalOperation::DeleteRow {
+                    partition_key: partition_key.clone(),
+                    row_key: removed_row.get_row_key().to_string(),
+                });
+            }
+        }
+
         return Some((partition_key, removed_rows, partition_is_empty));
     }
 
@@ -276,6 +376,11 @@ impl DbTable {
     ) -> Option<DbPartition> {
         let removed_partition = self.partitions.remove(partition_key.as_str());
 
+        if let Some(removed_partition) = removed_partition.as_ref() {
+            let partition_key = removed_partition.partition_key.clone();
+            self.add_to_journal(|| DbTableJournalOperation::DeletePartition(partition_key));
+        }
+
         #[cfg(feature = "master-node")]
         if removed_partition.is_some() {
             if let Some(set_last_write_moment) = set_last_write_moment {
@@ -287,6 +392,12 @@ impl DbTable {
     }
 
     pub fn clear_table(&mut self) -> Option<SortedVecWithStrKey<DbPartition>> {
-        self.partitions.clear()
+        let result = self.partitions.clear();
+
+        if result.is_some() {
+            self.add_to_journal(|| DbTableJournalOperation::ClearTable);
+        }
+
+        result
     }
 }
@@ -5,6 +5,8 @@ pub struct DbTableAttributes {
     pub persist: bool,
     pub max_partitions_amount: Option<usize>,
     pub max_rows_per_partition_amount: Option<usize>,
+    /// Least recently read partitions are garbage collected if table is bigger
+    pub max_table_size_bytes: Option<usize>,
     pub created: DateTimeAsMicroseconds,
 }
 
@@ -15,6 +17,7 @@ impl DbTableAttributes {
             persist: true,
             max_partitions_amount: None,
             max_rows_per_partition_amount: None,
+            max_table_size_bytes: None,
         }
     }
 }
@@ -37,9 +40,15 @@ impl DbTableAttributes {
             created,
             max_partitions_amount,
             max_rows_per_partition_amount,
+            max_table_size_bytes: None,
         }
     }
 
+    pub fn with_max_table_size_bytes(mut self, max_table_size_bytes: Option<usize>) -> Self {
+        self.max_table_size_bytes = max_table_size_bytes;
+        self
+    }
+
     pub fn update(
         &mut self,
         persist_table: bool,
@@ -65,4 +74,13 @@ impl DbTableAttributes {
 
         return result;
     }
+
+    pub fn update_max_table_size_bytes(&mut self, max_table_size_bytes: Option<usize>) -> bool {
+        if self.max_table_size_bytes == max_table_size_bytes {
+            return false;
+        }
+
+        self.max_table_size_bytes = max_table_size_bytes;
+        true
+    }
 }
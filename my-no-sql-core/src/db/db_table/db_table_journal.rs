@@ -0,0 +1,291 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use crate::db::{DbRow, PartitionKey};
+
+#[derive(Debug, Clone, Default)]
+pub struct DbTableJournalSettings {
+    /// Oldest changes are truncated if journal has more items
+    pub max_items: Option<usize>,
+    /// Oldest changes are truncated if size of the journal is bigger
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Clone)]
+pub enum DbTableJournalOperation {
+    InsertOrReplace(Arc<DbRow>),
+    DeleteRow {
+        partition_key: PartitionKey,
+        row_key: String,
+    },
+    DeletePartition(PartitionKey),
+    ClearTable,
+}
+
+impl DbTableJournalOperation {
+    pub fn get_partition_key(&self) -> Option<&str> {
+        match self {
+            DbTableJournalOperation::InsertOrReplace(db_row) => Some(db_row.get_partition_key()),
+            DbTableJournalOperation::DeleteRow { partition_key, .. } => {
+                Some(partition_key.as_str())
+            }
+            DbTableJournalOperation::DeletePartition(partition_key) => Some(partition_key.as_str()),
+            DbTableJournalOperation::ClearTable => None,
+        }
+    }
+
+    pub fn get_row_key(&self) -> Option<&str> {
+        match self {
+            DbTableJournalOperation::InsertOrReplace(db_row) => Some(db_row.get_row_key()),
+            DbTableJournalOperation::DeleteRow { row_key, .. } => Some(row_key.as_str()),
+            DbTableJournalOperation::DeletePartition(_) => None,
+            DbTableJournalOperation::ClearTable => None,
+        }
+    }
+
+    fn get_size(&self) -> usize {
+        match self {
+            DbTableJournalOperation::InsertOrReplace(db_row) => db_row.get_src_as_slice().len(),
+            DbTableJournalOperation::DeleteRow {
+                partition_key,
+                row_key,
+            } => partition_key.as_str().len() + row_key.len(),
+            DbTableJournalOperation::DeletePartition(partition_key) => partition_key.as_str().len(),
+            DbTableJournalOperation::ClearTable => 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DbTableJournalItem {
+    pub sequence: u64,
+    pub operation: DbTableJournalOperation,
+}
+
+/// Requested changes were truncated. Consumer has to resync the whole table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalIsTruncated {
+    pub requested_sequence: u64,
+    /// Changes since this sequence are still available
+    pub first_available_sequence: u64,
+}
+
+/// Bounded list of the changes of the table. Every change gets the next sequence number, starting from 1
+pub struct DbTableJournal {
+    items: VecDeque<DbTableJournalItem>,
+    settings: DbTableJournalSettings,
+    next_sequence: u64,
+    size: usize,
+}
+
+impl DbTableJournal {
+    pub fn new(settings: DbTableJournalSettings) -> Self {
+        Self {
+            items: VecDeque::new(),
+            settings,
+            next_sequence: 1,
+            size: 0,
+        }
+    }
+
+    /// Sequence of the last change. 0 - if table has not been changed yet
+    pub fn get_last_sequence(&self) -> u64 {
+        self.next_sequence - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn add(&mut self, operation: DbTableJournalOperation) -> u64 {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        self.size += operation.get_size();
+        self.items.push_back(DbTableJournalItem {
+            sequence,
+            operation,
+        });
+
+        self.truncate();
+
+        sequence
+    }
+
+    /// Changes with sequence bigger than the given one
+    pub fn get_changes_since(
+        &self,
+        sequence: u64,
+    ) -> Result<impl Iterator<Item = &DbTableJournalItem>, JournalIsTruncated> {
+        let first_available_sequence = match self.items.front() {
+            Some(item) => item.sequence,
+            None => self.next_sequence,
+        };
+
+        let next_sequence = sequence.saturating_add(1);
+
+        if next_sequence < first_available_sequence {
+            return Err(JournalIsTruncated {
+                requested_sequence: sequence,
+                first_available_sequence: first_available_sequence - 1,
+            });
+        }
+
+        let skip = usize::try_from(next_sequence - first_available_sequence).unwrap_or(usize::MAX);
+        Ok(self.items.iter().skip(skip))
+    }
+
+    fn truncate(&mut self) {
+        while self.items.len() > 1 && self.is_over_limit() {
+            if let Some(item) = self.items.pop_front() {
+                self.size -= item.operation.get_size();
+            }
+        }
+    }
+
+    fn is_over_limit(&self) -> bool {
+        if let Some(max_items) = self.settings.max_items {
+            if self.items.len() > max_items {
+                return true;
+            }
+        }
+
+        if let Some(max_bytes) = self.settings.max_bytes {
+            if self.size > max_bytes {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::{DbPartition, DbTable, PartitionKey},
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::{DbTableJournal, DbTableJournalOperation, DbTableJournalSettings};
+
+    fn delete_partition(partition_key: &str) -> DbTableJournalOperation {
+        DbTableJournalOperation::DeletePartition(PartitionKey::new(partition_key.to_string()))
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let mut journal = DbTableJournal::new(DbTableJournalSettings::default());
+
+        assert_eq!(0, journal.get_changes_since(0).unwrap().count());
+
+        journal.add(delete_partition("pk1"));
+        journal.add(delete_partition("pk2"));
+        journal.add(delete_partition("pk3"));
+
+        assert_eq!(3, journal.get_last_sequence());
+
+        let changes: Vec<u64> = journal
+            .get_changes_since(1)
+            .unwrap()
+            .map(|itm| itm.sequence)
+            .collect();
+        assert_eq!(vec![2, 3], changes);
+
+        assert_eq!(0, journal.get_changes_since(3).unwrap().count());
+        assert_eq!(0, journal.get_changes_since(u64::MAX).unwrap().count());
+    }
+
+    #[test]
+    fn test_truncation_by_items_amount() {
+        let mut journal = DbTableJournal::new(DbTableJournalSettings {
+            max_items: Some(2),
+            max_bytes: None,
+        });
+
+        for i in 0..5 {
+            journal.add(delete_partition(format!("pk{}", i).as_str()));
+        }
+
+        assert_eq!(2, journal.len());
+        assert_eq!(2, journal.get_changes_since(3).unwrap().count());
+
+        let err = journal.get_changes_since(2).err().unwrap();
+        assert_eq!(3, err.first_available_sequence);
+    }
+
+    #[test]
+    fn test_truncation_by_size() {
+        let mut journal = DbTableJournal::new(DbTableJournalSettings {
+            max_items: None,
+            max_bytes: Some(10),
+        });
+
+        journal.add(delete_partition("12345"));
+        journal.add(delete_partition("12345"));
+        assert_eq!(2, journal.len());
+
+        journal.add(delete_partition("1"));
+        assert_eq!(2, journal.len());
+        assert_eq!(6, journal.get_size());
+        assert!(journal.get_changes_since(0).is_err());
+    }
+
+    fn create_partition(partition_key: &str) -> DbPartition {
+        let test_json = format!(r#"{{"PartitionKey":"{}","RowKey":"rk"}}"#, partition_key);
+        let db_row =
+            DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        let mut db_partition = DbPartition::new(partition_key.to_string());
+        db_partition.insert_or_replace_row(Arc::new(db_row));
+        db_partition
+    }
+
+    fn get_operations(db_table: &DbTable) -> Vec<(&'static str, String)> {
+        db_table
+            .get_journal()
+            .unwrap()
+            .get_changes_since(0)
+            .unwrap()
+            .map(|itm| match &itm.operation {
+                DbTableJournalOperation::InsertOrReplace(db_row) => {
+                    ("insert", db_row.get_partition_key().to_string())
+                }
+                DbTableJournalOperation::DeletePartition(partition_key) => {
+                    ("delete_partition", partition_key.to_string())
+                }
+                _ => ("other", String::new()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_init_and_restore_partition_are_journaled() {
+        let mut db_table = DbTable::new_with_default_attributes("test-table".into());
+        db_table.enable_journal(DbTableJournalSettings::default());
+
+        db_table.restore_partition(create_partition("pk1"));
+        db_table.init_partition(create_partition("pk1"));
+        db_table.init_partition(create_partition("pk2"));
+
+        assert_eq!(
+            vec![
+                ("insert", "pk1".to_string()),
+                ("delete_partition", "pk1".to_string()),
+                ("insert", "pk1".to_string()),
+                ("insert", "pk2".to_string()),
+            ],
+            get_operations(&db_table)
+        );
+    }
+}
@@ -5,7 +5,10 @@ use rust_extensions::{
 
 use crate::db::PartitionKey;
 
-use super::{AvgSize, DataToGc, DbPartitionsContainer, DbTable, DbTableAttributes, DbTableName};
+use super::{
+    AvgSize, DataToGc, DataToGcReason, DbPartitionsContainer, DbTable, DbTableAttributes,
+    DbTableName,
+};
 
 pub struct PartitionLastWriteMoment {
     pub partition_key: PartitionKey,
@@ -26,6 +29,7 @@ impl DbTable {
             last_write_moment: DateTimeAsMicroseconds::now(),
             attributes,
             avg_size: AvgSize::new(),
+            journal: None,
         }
     }
 
@@ -63,13 +67,29 @@ impl DbTable {
                 .get_partitions_to_gc_by_max_amount(max_partitions_amount)
             {
                 for item in partitions_to_expire {
-                    result.add_partition_to_expire(item.partition_key);
+                    result.add_partition_to_expire(
+                        item.partition_key,
+                        DataToGcReason::MaxPartitionsAmount,
+                    );
                 }
             }
         }
 
         for partition_key in self.partitions.get_partitions_to_expire(now) {
-            result.add_partition_to_expire(partition_key);
+            result.add_partition_to_expire(partition_key, DataToGcReason::Expired);
+        }
+
+        if let Some(max_table_size_bytes) = self.attributes.max_table_size_bytes {
+            if let Some(partitions_to_gc) = self
+                .partitions
+                .get_partitions_to_gc_by_max_size(max_table_size_bytes, |partition_key| {
+                    result.has_partition_to_gc(partition_key)
+                })
+            {
+                for partition_key in partitions_to_gc {
+                    result.add_partition_to_expire(partition_key, DataToGcReason::MaxTableSize);
+                }
+            }
         }
 
         //Find DbRows to expire
@@ -81,7 +101,11 @@ impl DbTable {
             let rows_to_expire = db_partition.get_rows_to_expire(now);
 
             if rows_to_expire.len() > 0 {
-                result.add_rows_to_expire(&db_partition.partition_key, rows_to_expire);
+                result.add_rows_to_expire(
+                    &db_partition.partition_key,
+                    rows_to_expire,
+                    DataToGcReason::Expired,
+                );
             }
 
             //Find DBRows to GC by max amount
@@ -90,7 +114,11 @@ impl DbTable {
                     .rows
                     .get_rows_to_gc_by_max_amount(max_rows_per_partition)
                 {
-                    result.add_rows_to_expire(&db_partition.partition_key, rows_to_gc);
+                    result.add_rows_to_expire(
+                        &db_partition.partition_key,
+                        rows_to_gc,
+                        DataToGcReason::MaxRowsPerPartitionAmount,
+                    );
                 }
             }
         }
@@ -164,4 +192,39 @@ mod tests {
         assert_eq!(db_table.get_table_size(), db_row2.get_src_as_slice().len());
         assert_eq!(db_table.get_partitions_amount(), 1);
     }
+
+    #[test]
+    fn test_gc_by_max_table_size() {
+        let mut attributes = DbTableAttributes::create_default();
+        let now = JsonTimeStamp::now();
+
+        let mut db_table = DbTable::new("test-table".into(), attributes.clone());
+
+        for partition_key in ["pk1", "pk2", "pk3"] {
+            let test_json = format!(r#"{{"PartitionKey":"{}","RowKey":"rk"}}"#, partition_key);
+            let db_row =
+                DbJsonEntity::parse_into_db_row(test_json.as_bytes().into(), &now).unwrap();
+            db_table.insert_or_replace_row(&Arc::new(db_row), None);
+        }
+
+        for (partition_key, last_read_moment) in [("pk1", 3), ("pk2", 1), ("pk3", 2)] {
+            db_table
+                .get_partition(partition_key)
+                .unwrap()
+                .update_last_read_moment(DateTimeAsMicroseconds::new(last_read_moment));
+        }
+
+        let partition_size = db_table.get_partition("pk1").unwrap().get_content_size();
+
+        attributes.max_table_size_bytes = Some(partition_size * 2);
+        db_table.attributes = attributes;
+
+        let data_to_gc = db_table.get_data_to_gc(DateTimeAsMicroseconds::now());
+
+        assert_eq!(1, data_to_gc.partitions.len());
+        assert_eq!(
+            Some(DataToGcReason::MaxTableSize),
+            data_to_gc.get_partition_reason("pk2")
+        );
+    }
 }
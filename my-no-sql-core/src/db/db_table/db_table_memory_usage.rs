@@ -0,0 +1,281 @@
+use std::{collections::HashSet, fmt::Display, sync::Arc};
+
+use crate::db::{DbPartition, DbRow, HEAP_ALLOCATION_OVERHEAD};
+
+use super::DbTable;
+
+/// Estimation of the memory the table holds. Indexes of the expirations are not included
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DbTableMemoryUsage {
+    pub partitions_amount: usize,
+    pub rows_amount: usize,
+    /// Partitions with their keys
+    pub partitions_bytes: usize,
+    /// Rows with their owned content, fields indexes and the references to them
+    pub rows_bytes: usize,
+    /// Buffers of the bulk loaded rows. Every buffer is counted once
+    pub shared_buffers_bytes: usize,
+    pub shared_buffers_amount: usize,
+}
+
+impl DbTableMemoryUsage {
+    pub fn get_total_bytes(&self) -> usize {
+        self.partitions_bytes + self.rows_bytes + self.shared_buffers_bytes
+    }
+
+    pub fn get_bytes_per_row(&self) -> usize {
+        if self.rows_amount == 0 {
+            return 0;
+        }
+
+        self.get_total_bytes() / self.rows_amount
+    }
+}
+
+impl Display for DbTableMemoryUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Partitions: {}. Rows: {}. Total: {} bytes. Per row: {} bytes. Shared buffers: {} ({} bytes)",
+            self.partitions_amount,
+            self.rows_amount,
+            self.get_total_bytes(),
+            self.get_bytes_per_row(),
+            self.shared_buffers_amount,
+            self.shared_buffers_bytes
+        )
+    }
+}
+
+impl DbTable {
+    /// Walks through all the rows of the table, so it is meant for diagnostics rather than for every request
+    pub fn get_memory_usage(&self) -> DbTableMemoryUsage {
+        let mut result = DbTableMemoryUsage::default();
+        let mut shared_buffers = HashSet::new();
+
+        for db_partition in self.partitions.get_partitions() {
+            result.partitions_amount += 1;
+            result.partitions_bytes += std::mem::size_of::<DbPartition>()
+                + 2 * HEAP_ALLOCATION_OVERHEAD
+                + std::mem::size_of::<String>()
+                + 2 * std::mem::size_of::<usize>()
+                + db_partition.partition_key.as_str().len();
+
+            for db_row in db_partition.get_all_rows() {
+                result.rows_amount += 1;
+                result.rows_bytes += std::mem::size_of::<Arc<DbRow>>() + db_row.get_memory_size();
+
+                if let Some(buffer) = db_row.get_content().get_shared_buffer() {
+                    if shared_buffers.insert(Arc::as_ptr(buffer) as *const u8) {
+                        result.shared_buffers_amount += 1;
+                        result.shared_buffers_bytes += HEAP_ALLOCATION_OVERHEAD
+                            + 2 * std::mem::size_of::<usize>()
+                            + buffer.len();
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::{
+            DbRowsStorage, DbTable, DbTableJournalOperation, DbTableJournalSettings,
+            DbTableSnapshotCompression, HEAP_ALLOCATION_OVERHEAD,
+        },
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    const PARTITIONS_AMOUNT: usize = 10;
+    const ROWS_PER_PARTITION: usize = 100;
+
+    fn create_table() -> DbTable {
+        let mut db_table = DbTable::new_with_default_attributes("test-table".into());
+        let time_stamp = JsonTimeStamp::now();
+
+        for partition_no in 0..PARTITIONS_AMOUNT {
+            for row_no in 0..ROWS_PER_PARTITION {
+                let json = format!(
+                    r#"{{"PartitionKey":"instrument-{}","RowKey":"{:05}","Bid":1.1034,"Ask":1.1035}}"#,
+                    partition_no, row_no
+                );
+
+                let db_row = Arc::new(
+                    DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &time_stamp).unwrap(),
+                );
+
+                #[cfg(not(feature = "master-node"))]
+                db_table.insert_or_replace_row(&db_row);
+                #[cfg(feature = "master-node")]
+                db_table.insert_or_replace_row(&db_row, None);
+            }
+        }
+
+        db_table
+    }
+
+    #[test]
+    fn test_bytes_per_row_of_owned_and_shared_rows() {
+        let db_table = create_table();
+
+        let mut snapshot = Vec::new();
+        db_table
+            .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+            .unwrap();
+
+        let owned =
+            DbTable::read_snapshot_with_storage(snapshot.as_slice(), DbRowsStorage::Owned).unwrap();
+        let shared = DbTable::read_snapshot_with_storage(
+            snapshot.as_slice(),
+            DbRowsStorage::SharedPerPartition,
+        )
+        .unwrap();
+
+        let owned_usage = owned.get_memory_usage();
+        let shared_usage = shared.get_memory_usage();
+
+        assert_eq!(
+            PARTITIONS_AMOUNT * ROWS_PER_PARTITION,
+            owned_usage.rows_amount
+        );
+        assert_eq!(owned_usage.rows_amount, shared_usage.rows_amount);
+        assert_eq!(0, owned_usage.shared_buffers_amount);
+        assert_eq!(PARTITIONS_AMOUNT, shared_usage.shared_buffers_amount);
+
+        // Shared rows save the allocation of the content, buffer of the partition is amortized between its rows
+        assert!(
+            shared_usage.get_bytes_per_row() + HEAP_ALLOCATION_OVERHEAD
+                <= owned_usage.get_bytes_per_row() + 1
+        );
+
+        let content = |db_table: &DbTable| -> Vec<Vec<u8>> {
+            db_table
+                .get_all_rows(None, None)
+                .map(|(_, db_row)| db_row.to_vec())
+                .collect()
+        };
+
+        assert_eq!(content(&owned), content(&shared));
+    }
+
+    #[test]
+    fn test_partition_key_is_shared() {
+        let mut db_table = create_table();
+        db_table.enable_journal(DbTableJournalSettings::default());
+
+        let partition_key = db_table
+            .get_partition("instrument-1")
+            .unwrap()
+            .partition_key
+            .to_arc_of_string();
+
+        let json = r#"{"PartitionKey":"instrument-1","RowKey":"new","Bid":1.1}"#;
+        let db_row = Arc::new(
+            DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &JsonTimeStamp::now()).unwrap(),
+        );
+
+        #[cfg(not(feature = "master-node"))]
+        let (inserted_key, _) = db_table.insert_or_replace_row(&db_row);
+        #[cfg(feature = "master-node")]
+        let (inserted_key, _) = db_table.insert_or_replace_row(&db_row, None);
+
+        #[cfg(not(feature = "master-node"))]
+        let (removed_key, _, _) = db_table
+            .remove_row(&"instrument-1".to_string(), &"00001".to_string(), false)
+            .unwrap();
+        #[cfg(feature = "master-node")]
+        let (removed_key, _, _) = db_table
+            .remove_row(
+                &"instrument-1".to_string(),
+                &"00001".to_string(),
+                false,
+                None,
+            )
+            .unwrap();
+
+        assert!(Arc::ptr_eq(
+            &partition_key,
+            &inserted_key.to_arc_of_string()
+        ));
+        assert!(Arc::ptr_eq(&partition_key, &removed_key.to_arc_of_string()));
+
+        let journal_key = match &db_table
+            .get_journal()
+            .unwrap()
+            .get_changes_since(0)
+            .unwrap()
+            .last()
+            .unwrap()
+            .operation
+        {
+            DbTableJournalOperation::DeleteRow { partition_key, .. } => {
+                partition_key.to_arc_of_string()
+            }
+            _ => panic!("Row is removed last"),
+        };
+
+        assert!(Arc::ptr_eq(&partition_key, &journal_key));
+
+        // Key is interned, so the partition of the other table gets the same allocation
+        let mut other_table = DbTable::new_with_default_attributes("other-table".into());
+
+        #[cfg(not(feature = "master-node"))]
+        other_table.insert_or_replace_row(&db_row);
+        #[cfg(feature = "master-node")]
+        other_table.insert_or_replace_row(&db_row, None);
+
+        let other_key = other_table
+            .get_partition("instrument-1")
+            .unwrap()
+            .partition_key
+            .to_arc_of_string();
+
+        assert!(Arc::ptr_eq(&partition_key, &other_key));
+    }
+
+    #[test]
+    fn test_shared_buffer_is_released_with_the_last_row() {
+        let db_table = create_table();
+
+        let mut snapshot = Vec::new();
+        db_table
+            .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+            .unwrap();
+
+        let mut shared = DbTable::read_snapshot_with_storage(
+            snapshot.as_slice(),
+            DbRowsStorage::SharedPerPartition,
+        )
+        .unwrap();
+
+        let buffer = shared
+            .get_partition("instrument-0")
+            .unwrap()
+            .get_all_rows()
+            .next()
+            .unwrap()
+            .get_content()
+            .get_shared_buffer()
+            .unwrap()
+            .clone();
+
+        assert_eq!(ROWS_PER_PARTITION + 1, Arc::strong_count(&buffer));
+
+        #[cfg(not(feature = "master-node"))]
+        shared.remove_partition(&"instrument-0".to_string());
+        #[cfg(feature = "master-node")]
+        shared.remove_partition(&"instrument-0".to_string(), None);
+
+        assert_eq!(1, Arc::strong_count(&buffer));
+        assert_eq!(
+            PARTITIONS_AMOUNT - 1,
+            shared.get_memory_usage().shared_buffers_amount
+        );
+    }
+}
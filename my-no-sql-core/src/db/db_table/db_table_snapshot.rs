@@ -0,0 +1,659 @@
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+#[cfg(feature = "master-node")]
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::{
+    db::{DbPartition, DbRow},
+    db_json_entity::{DbEntityParseFail, DbJsonEntity},
+};
+
+#[cfg(feature = "master-node")]
+use super::DbTableAttributes;
+use super::{DbTable, SnapshotBinaryReader, SnapshotBinaryWriter};
+
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"MYNOSQL\x01";
+pub const SNAPSHOT_FORMAT_VERSION: u16 = 1;
+
+const RECORD_END: u8 = 0;
+const RECORD_PARTITION: u8 = 1;
+
+#[derive(Debug)]
+pub enum DbTableSnapshotError {
+    IoError(std::io::Error),
+    InvalidFormat(String),
+    UnsupportedVersion(u16),
+    ChecksumMismatch { expected: u32, calculated: u32 },
+    DbEntityParseFail(DbEntityParseFail),
+}
+
+impl From<std::io::Error> for DbTableSnapshotError {
+    fn from(src: std::io::Error) -> Self {
+        Self::IoError(src)
+    }
+}
+
+impl From<DbEntityParseFail> for DbTableSnapshotError {
+    fn from(src: DbEntityParseFail) -> Self {
+        Self::DbEntityParseFail(src)
+    }
+}
+
+/// How the content of the restored rows is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbRowsStorage {
+    /// Every row owns its content
+    Owned,
+    /// Rows of the partition point to one buffer. Saves an allocation per row, but the buffer is kept
+    /// until the last row restored from it is replaced or deleted
+    SharedPerPartition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbTableSnapshotCompression {
+    None,
+    Deflate,
+}
+
+impl DbTableSnapshotCompression {
+    fn as_u8(&self) -> u8 {
+        match self {
+            DbTableSnapshotCompression::None => 0,
+            DbTableSnapshotCompression::Deflate => 1,
+        }
+    }
+
+    fn from_u8(src: u8) -> Result<Self, DbTableSnapshotError> {
+        match src {
+            0 => Ok(DbTableSnapshotCompression::None),
+            1 => Ok(DbTableSnapshotCompression::Deflate),
+            _ => Err(DbTableSnapshotError::InvalidFormat(format!(
+                "Unknown compression {}",
+                src
+            ))),
+        }
+    }
+}
+
+/// Header is never compressed, so it can be read without reading the whole snapshot.
+/// Header ends with the checksum of its bytes
+#[derive(Debug, Clone)]
+pub struct DbTableSnapshotHeader {
+    pub version: u16,
+    pub compression: DbTableSnapshotCompression,
+    pub table_name: String,
+    #[cfg(feature = "master-node")]
+    pub attributes: Option<DbTableAttributes>,
+}
+
+impl DbTableSnapshotHeader {
+    pub fn read(src: &mut impl Read) -> Result<Self, DbTableSnapshotError> {
+        let mut reader = SnapshotBinaryReader::new(src);
+
+        let mut magic = [0u8; 8];
+        reader.read_slice(&mut magic)?;
+
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(DbTableSnapshotError::InvalidFormat(
+                "Not a table snapshot".to_string(),
+            ));
+        }
+
+        let version = reader.read_u16()?;
+
+        if version != SNAPSHOT_FORMAT_VERSION {
+            return Err(DbTableSnapshotError::UnsupportedVersion(version));
+        }
+
+        let compression = DbTableSnapshotCompression::from_u8(reader.read_u8()?)?;
+        let table_name = reader.read_string()?;
+
+        let has_attributes = reader.read_bool()?;
+
+        #[cfg(feature = "master-node")]
+        let mut attributes = None;
+
+        if has_attributes {
+            let persist = reader.read_bool()?;
+            let max_partitions_amount = reader.read_opt_u64()?;
+            let max_rows_per_partition_amount = reader.read_opt_u64()?;
+            let max_table_size_bytes = reader.read_opt_u64()?;
+            let created = reader.read_i64()?;
+
+            #[cfg(feature = "master-node")]
+            {
+                let db_table_attributes = DbTableAttributes::new(
+                    persist,
+                    max_partitions_amount.map(|itm| itm as usize),
+                    max_rows_per_partition_amount.map(|itm| itm as usize),
+                    DateTimeAsMicroseconds::new(created),
+                )
+                .with_max_table_size_bytes(max_table_size_bytes.map(|itm| itm as usize));
+
+                attributes = Some(db_table_attributes);
+            }
+
+            #[cfg(not(feature = "master-node"))]
+            let _ = (
+                persist,
+                max_partitions_amount,
+                max_rows_per_partition_amount,
+                max_table_size_bytes,
+                created,
+            );
+        }
+
+        let calculated = reader.get_checksum();
+        let expected = reader.read_u32()?;
+
+        if expected != calculated {
+            return Err(DbTableSnapshotError::ChecksumMismatch {
+                expected,
+                calculated,
+            });
+        }
+
+        Ok(Self {
+            version,
+            compression,
+            table_name,
+            #[cfg(feature = "master-node")]
+            attributes,
+        })
+    }
+
+    fn write(&self, out: &mut impl Write) -> Result<(), DbTableSnapshotError> {
+        let mut writer = SnapshotBinaryWriter::new(out);
+
+        writer.write_slice(SNAPSHOT_MAGIC)?;
+        writer.write_u16(self.version)?;
+        writer.write_u8(self.compression.as_u8())?;
+        writer.write_str(self.table_name.as_str())?;
+
+        #[cfg(feature = "master-node")]
+        match self.attributes.as_ref() {
+            Some(attributes) => {
+                writer.write_bool(true)?;
+                writer.write_bool(attributes.persist)?;
+                writer.write_opt_u64(attributes.max_partitions_amount.map(|itm| itm as u64))?;
+                writer.write_opt_u64(
+                    attributes
+                        .max_rows_per_partition_amount
+                        .map(|itm| itm as u64),
+                )?;
+                writer.write_opt_u64(attributes.max_table_size_bytes.map(|itm| itm as u64))?;
+                writer.write_i64(attributes.created.unix_microseconds)?;
+            }
+            None => writer.write_bool(false)?,
+        }
+
+        #[cfg(not(feature = "master-node"))]
+        writer.write_bool(false)?;
+
+        let checksum = writer.get_checksum();
+        writer.write_u32(checksum)?;
+
+        Ok(())
+    }
+}
+
+/// Snapshot is: header, then the body (compressed if it is set in the header).
+/// Body is the list of partitions with their rows, and the end record with the amounts and the checksum of the body.
+/// Expiration of the rows is the part of the json of the row.
+impl DbTable {
+    pub fn write_snapshot(
+        &self,
+        mut out: impl Write,
+        compression: DbTableSnapshotCompression,
+    ) -> Result<(), DbTableSnapshotError> {
+        let header = DbTableSnapshotHeader {
+            version: SNAPSHOT_FORMAT_VERSION,
+            compression,
+            table_name: self.name.to_string(),
+            #[cfg(feature = "master-node")]
+            attributes: Some(self.attributes.clone()),
+        };
+
+        header.write(&mut out)?;
+
+        match compression {
+            DbTableSnapshotCompression::None => {
+                self.write_snapshot_body(&mut out)?;
+            }
+            DbTableSnapshotCompression::Deflate => {
+                let mut encoder = DeflateEncoder::new(&mut out, Compression::default());
+                self.write_snapshot_body(&mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+
+        out.flush()?;
+
+        Ok(())
+    }
+
+    fn write_snapshot_body(&self, out: &mut impl Write) -> Result<(), DbTableSnapshotError> {
+        let mut writer = SnapshotBinaryWriter::new(out);
+
+        let mut partitions_amount = 0;
+        let mut rows_amount = 0;
+
+        for db_partition in self.partitions.get_partitions() {
+            writer.write_u8(RECORD_PARTITION)?;
+            writer.write_str(db_partition.partition_key.as_str())?;
+
+            #[cfg(feature = "master-node")]
+            {
+                writer.write_opt_i64(db_partition.expires.map(|itm| itm.unix_microseconds))?;
+                writer.write_opt_i64(Some(db_partition.last_write_moment.unix_microseconds))?;
+                writer
+                    .write_opt_i64(Some(db_partition.get_last_read_moment().unix_microseconds))?;
+            }
+
+            #[cfg(not(feature = "master-node"))]
+            {
+                writer.write_opt_i64(None)?;
+                writer.write_opt_i64(None)?;
+                writer.write_opt_i64(None)?;
+            }
+
+            writer.write_u64(db_partition.rows_count() as u64)?;
+
+            for db_row in db_partition.get_all_rows() {
+                writer.write_bytes(db_row.get_src_as_slice())?;
+                rows_amount += 1;
+            }
+
+            partitions_amount += 1;
+        }
+
+        writer.write_u8(RECORD_END)?;
+        writer.write_u64(partitions_amount)?;
+        writer.write_u64(rows_amount)?;
+
+        let checksum = writer.get_checksum();
+        writer.write_u32(checksum)?;
+
+        Ok(())
+    }
+
+    /// Restored table has no journal. Attributes are taken from the snapshot if they are there
+    pub fn read_snapshot(src: impl Read) -> Result<DbTable, DbTableSnapshotError> {
+        Self::read_snapshot_with_storage(src, DbRowsStorage::Owned)
+    }
+
+    pub fn read_snapshot_with_storage(
+        mut src: impl Read,
+        storage: DbRowsStorage,
+    ) -> Result<DbTable, DbTableSnapshotError> {
+        let header = DbTableSnapshotHeader::read(&mut src)?;
+
+        #[cfg(not(feature = "master-node"))]
+        let mut result = DbTable::new(header.table_name.into());
+
+        #[cfg(feature = "master-node")]
+        let mut result = DbTable::new(
+            header.table_name.into(),
+            header.attributes.unwrap_or_default(),
+        );
+
+        match header.compression {
+            DbTableSnapshotCompression::None => {
+                result.read_snapshot_body(&mut src, storage)?;
+            }
+            DbTableSnapshotCompression::Deflate => {
+                result.read_snapshot_body(&mut DeflateDecoder::new(src), storage)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn read_snapshot_body(
+        &mut self,
+        src: &mut impl Read,
+        storage: DbRowsStorage,
+    ) -> Result<(), DbTableSnapshotError> {
+        let mut reader = SnapshotBinaryReader::new(src);
+
+        let mut partitions_amount = 0;
+        let mut rows_amount = 0;
+
+        loop {
+            match reader.read_u8()? {
+                RECORD_PARTITION => {
+                    let mut db_partition = DbPartition::new(reader.read_string()?);
+
+                    let expires = reader.read_opt_i64()?;
+                    let last_write_moment = reader.read_opt_i64()?;
+                    let last_read_moment = reader.read_opt_i64()?;
+
+                    #[cfg(feature = "master-node")]
+                    {
+                        db_partition.expires = expires.map(DateTimeAsMicroseconds::new);
+
+                        if let Some(last_write_moment) = last_write_moment {
+                            db_partition.last_write_moment =
+                                DateTimeAsMicroseconds::new(last_write_moment);
+                        }
+
+                        if let Some(last_read_moment) = last_read_moment {
+                            db_partition.update_last_read_moment(DateTimeAsMicroseconds::new(
+                                last_read_moment,
+                            ));
+                        }
+                    }
+
+                    #[cfg(not(feature = "master-node"))]
+                    let _ = (expires, last_write_moment, last_read_moment);
+
+                    let partition_rows_amount = reader.read_u64()?;
+
+                    let db_rows = read_partition_rows(&mut reader, partition_rows_amount, storage)?;
+
+                    for db_row in db_rows {
+                        self.avg_size.add(&db_row);
+                        db_partition.insert_or_replace_row(Arc::new(db_row));
+                    }
+
+                    self.restore_partition(db_partition);
+
+                    partitions_amount += 1;
+                    rows_amount += partition_rows_amount;
+                }
+                RECORD_END => {
+                    let expected_partitions_amount = reader.read_u64()?;
+                    let expected_rows_amount = reader.read_u64()?;
+
+                    let calculated = reader.get_checksum();
+                    let expected = reader.read_u32()?;
+
+                    if expected != calculated {
+                        return Err(DbTableSnapshotError::ChecksumMismatch {
+                            expected,
+                            calculated,
+                        });
+                    }
+
+                    if expected_partitions_amount != partitions_amount
+                        || expected_rows_amount != rows_amount
+                    {
+                        return Err(DbTableSnapshotError::InvalidFormat(format!(
+                            "Snapshot has {} partitions and {} rows. Expected {} partitions and {} rows",
+                            partitions_amount,
+                            rows_amount,
+                            expected_partitions_amount,
+                            expected_rows_amount
+                        )));
+                    }
+
+                    return Ok(());
+                }
+                record_type => {
+                    return Err(DbTableSnapshotError::InvalidFormat(format!(
+                        "Unknown record type {}",
+                        record_type
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Capacity is not reserved by the amount from the snapshot, since it is not trusted until the checksum is checked
+const MAX_RESERVED_ROWS_AMOUNT: u64 = 1024;
+
+/// Offsets of the shared content are u32, so bigger partitions are split into several buffers
+const MAX_SHARED_BUFFER_SIZE: usize = u32::MAX as usize;
+
+fn read_partition_rows<R: Read>(
+    reader: &mut SnapshotBinaryReader<R>,
+    rows_amount: u64,
+    storage: DbRowsStorage,
+) -> Result<Vec<DbRow>, DbTableSnapshotError> {
+    let reserve = rows_amount.min(MAX_RESERVED_ROWS_AMOUNT) as usize;
+    let mut result = Vec::with_capacity(reserve);
+
+    match storage {
+        DbRowsStorage::Owned => {
+            for _ in 0..rows_amount {
+                result.push(DbJsonEntity::restore_into_db_row(reader.read_bytes()?)?);
+            }
+        }
+        DbRowsStorage::SharedPerPartition => {
+            let mut buffer = Vec::new();
+            let mut positions = Vec::with_capacity(reserve);
+
+            for _ in 0..rows_amount {
+                let (start, end) = reader.read_bytes_into(&mut buffer)?;
+
+                if end > MAX_SHARED_BUFFER_SIZE {
+                    let row = buffer.split_off(start);
+                    restore_shared_rows(&mut result, buffer, positions.as_slice())?;
+
+                    buffer = row;
+                    positions.clear();
+                    positions.push((0, buffer.len()));
+                } else {
+                    positions.push((start, end));
+                }
+            }
+
+            restore_shared_rows(&mut result, buffer, positions.as_slice())?;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Positions are produced by read_bytes_into, so they are inside of the buffer and fit into u32
+fn restore_shared_rows(
+    result: &mut Vec<DbRow>,
+    buffer: Vec<u8>,
+    positions: &[(usize, usize)],
+) -> Result<(), DbTableSnapshotError> {
+    if positions.is_empty() {
+        return Ok(());
+    }
+
+    let buffer: Arc<[u8]> = buffer.into();
+
+    for (start, end) in positions {
+        result.push(DbJsonEntity::restore_into_shared_db_row(
+            &buffer, *start, *end,
+        )?);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::DbTable,
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::{DbTableSnapshotCompression, DbTableSnapshotError, DbTableSnapshotHeader};
+
+    fn create_table() -> DbTable {
+        let mut db_table = DbTable::new_with_default_attributes("test-table".into());
+        let time_stamp = JsonTimeStamp::now();
+
+        for (partition_key, row_key) in [("pk1", "rk1"), ("pk1", "rk2"), ("pk2", "rk1")] {
+            let json = format!(
+                r#"{{"PartitionKey":"{}","RowKey":"{}","Expires":"2030-01-01T00:00:00","Value":1}}"#,
+                partition_key, row_key
+            );
+            let db_row =
+                DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &time_stamp).unwrap();
+
+            #[cfg(not(feature = "master-node"))]
+            db_table.insert_or_replace_row(&Arc::new(db_row));
+            #[cfg(feature = "master-node")]
+            db_table.insert_or_replace_row(&Arc::new(db_row), None);
+        }
+
+        db_table
+    }
+
+    fn get_content(db_table: &DbTable) -> Vec<Vec<u8>> {
+        db_table
+            .get_all_rows(None, None)
+            .map(|(_, db_row)| db_row.get_src_as_slice().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let db_table = create_table();
+
+        for compression in [
+            DbTableSnapshotCompression::None,
+            DbTableSnapshotCompression::Deflate,
+        ] {
+            let mut snapshot = Vec::new();
+            db_table.write_snapshot(&mut snapshot, compression).unwrap();
+
+            let header = DbTableSnapshotHeader::read(&mut snapshot.as_slice()).unwrap();
+            assert_eq!("test-table", header.table_name);
+            assert_eq!(compression, header.compression);
+
+            let restored = DbTable::read_snapshot(snapshot.as_slice()).unwrap();
+
+            assert_eq!("test-table", restored.name.as_str());
+            assert_eq!(2, restored.get_partitions_amount());
+            assert_eq!(get_content(&db_table), get_content(&restored));
+        }
+    }
+
+    #[cfg(feature = "master-node")]
+    #[test]
+    fn test_partition_moments_round_trip() {
+        use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+        let db_table = create_table();
+
+        let last_read_moment = DateTimeAsMicroseconds::new(1_600_000_000_000_000);
+        db_table
+            .get_partition("pk1")
+            .unwrap()
+            .update_last_read_moment(last_read_moment);
+
+        let mut snapshot = Vec::new();
+        db_table
+            .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+            .unwrap();
+
+        let restored = DbTable::read_snapshot(snapshot.as_slice()).unwrap();
+        let db_partition = restored.get_partition("pk1").unwrap();
+
+        assert_eq!(
+            last_read_moment.unix_microseconds,
+            db_partition.get_last_read_moment().unix_microseconds
+        );
+        assert_eq!(
+            db_table
+                .get_partition("pk1")
+                .unwrap()
+                .get_last_write_moment()
+                .unix_microseconds,
+            db_partition.get_last_write_moment().unix_microseconds
+        );
+    }
+
+    #[cfg(feature = "master-node")]
+    #[test]
+    fn test_attributes_round_trip() {
+        use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+        use crate::db::DbTableAttributes;
+
+        let attributes = DbTableAttributes::new(
+            false,
+            Some(10),
+            Some(100),
+            DateTimeAsMicroseconds::new(1_700_000_000_000_000),
+        )
+        .with_max_table_size_bytes(Some(1024 * 1024));
+
+        let db_table = DbTable::new("test-table".into(), attributes);
+
+        let mut snapshot = Vec::new();
+        db_table
+            .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+            .unwrap();
+
+        let header = DbTableSnapshotHeader::read(&mut snapshot.as_slice()).unwrap();
+        let restored = header.attributes.unwrap();
+
+        assert!(!restored.persist);
+        assert_eq!(Some(10), restored.max_partitions_amount);
+        assert_eq!(Some(100), restored.max_rows_per_partition_amount);
+        assert_eq!(Some(1024 * 1024), restored.max_table_size_bytes);
+        assert_eq!(1_700_000_000_000_000, restored.created.unix_microseconds);
+
+        let restored = DbTable::read_snapshot(snapshot.as_slice()).unwrap();
+        assert_eq!(Some(1024 * 1024), restored.attributes.max_table_size_bytes);
+        assert_eq!(
+            1_700_000_000_000_000,
+            restored.attributes.created.unix_microseconds
+        );
+    }
+
+    #[test]
+    fn test_corrupted_length_is_not_allocated() {
+        let src = [0xff, 0xff, 0xff, 0xff, b'{', b'}'];
+        let mut reader = super::SnapshotBinaryReader::new(&src[..]);
+
+        let mut buffer = Vec::new();
+        let result = reader.read_bytes_into(&mut buffer);
+
+        assert!(matches!(result, Err(DbTableSnapshotError::IoError(_))));
+        assert!(buffer.capacity() < 1024);
+    }
+
+    #[test]
+    fn test_corrupted_snapshot() {
+        let db_table = create_table();
+
+        let mut snapshot = Vec::new();
+        db_table
+            .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+            .unwrap();
+
+        let index = snapshot.len() - 20;
+        snapshot[index] ^= 0xff;
+
+        let result = DbTable::read_snapshot(snapshot.as_slice());
+        assert!(matches!(
+            result,
+            Err(DbTableSnapshotError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_header() {
+        let db_table = create_table();
+
+        let mut snapshot = Vec::new();
+        db_table
+            .write_snapshot(&mut snapshot, DbTableSnapshotCompression::None)
+            .unwrap();
+
+        // First byte of the table name: magic, version, compression and the length of the name go before it
+        snapshot[15] ^= 0x01;
+
+        let result = DbTableSnapshotHeader::read(&mut snapshot.as_slice());
+        assert!(matches!(
+            result,
+            Err(DbTableSnapshotError::ChecksumMismatch { .. })
+        ));
+    }
+}
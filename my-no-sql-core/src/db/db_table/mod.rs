@@ -27,5 +27,15 @@ mod all_db_rows_iterator;
 pub use all_db_rows_iterator::*;
 mod by_row_key_iterator;
 pub use by_row_key_iterator::*;
+mod range_db_rows_iterator;
+pub use range_db_rows_iterator::*;
 mod db_table_name;
 pub use db_table_name::*;
+mod db_table_journal;
+pub use db_table_journal::*;
+mod snapshot_binary;
+use snapshot_binary::*;
+mod db_table_snapshot;
+pub use db_table_snapshot::*;
+mod db_table_memory_usage;
+pub use db_table_memory_usage::*;
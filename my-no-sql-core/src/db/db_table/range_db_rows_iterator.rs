@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use crate::db::{DbPartition, DbRow, DbRowsRangeIterator, RowKeysRange};
+
+/// Rows of the range of every partition. Partitions go in the order of partition keys (reversed if range is reversed).
+/// Skip and limit are applied to the rows of the whole table.
+pub struct RangeDbRowsIterator<'a> {
+    partitions_iterator: std::slice::Iter<'a, DbPartition>,
+    db_rows_iterator: Option<(&'a DbPartition, DbRowsRangeIterator<'a>)>,
+    range: RowKeysRange<'a>,
+    to_skip: usize,
+    yielded: usize,
+}
+
+impl<'a> RangeDbRowsIterator<'a> {
+    pub fn new(
+        partitions_iterator: std::slice::Iter<'a, DbPartition>,
+        range: RowKeysRange<'a>,
+    ) -> Self {
+        Self {
+            partitions_iterator,
+            db_rows_iterator: None,
+            to_skip: range.skip.unwrap_or(0),
+            range,
+            yielded: 0,
+        }
+    }
+
+    fn next_partition(&mut self) -> Option<&'a DbPartition> {
+        if self.range.reverse {
+            self.partitions_iterator.next_back()
+        } else {
+            self.partitions_iterator.next()
+        }
+    }
+}
+
+impl<'a> Iterator for RangeDbRowsIterator<'a> {
+    type Item = (&'a DbPartition, &'a Arc<DbRow>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(limit) = self.range.limit {
+            if self.yielded >= limit {
+                return None;
+            }
+        }
+
+        loop {
+            if let Some((db_partition, db_rows_iterator)) = self.db_rows_iterator.as_mut() {
+                if let Some(db_row) = db_rows_iterator.next() {
+                    self.yielded += 1;
+                    return Some((*db_partition, db_row));
+                }
+
+                self.db_rows_iterator = None;
+            }
+
+            let db_partition = self.next_partition()?;
+            let db_rows = db_partition.rows.get_range(&self.range);
+
+            // Whole partition is skipped without iterating it
+            if self.to_skip >= db_rows.len() {
+                self.to_skip -= db_rows.len();
+                continue;
+            }
+
+            let db_rows_iterator =
+                DbRowsRangeIterator::new(db_rows, self.range.reverse, Some(self.to_skip), None);
+            self.to_skip = 0;
+
+            self.db_rows_iterator = Some((db_partition, db_rows_iterator));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::{DbPartition, RowKeysRange},
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::RangeDbRowsIterator;
+
+    fn create_partitions() -> Vec<DbPartition> {
+        let time_stamp = JsonTimeStamp::now();
+        let mut result = Vec::new();
+
+        for partition_key in ["pk1", "pk2", "pk3"] {
+            let mut db_partition = DbPartition::new(partition_key.to_string());
+
+            for row_key in ["a", "b", "c"] {
+                let json = format!(
+                    r#"{{"PartitionKey":"{}","RowKey":"{}"}}"#,
+                    partition_key, row_key
+                );
+                let db_row =
+                    DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &time_stamp).unwrap();
+                db_partition.insert_or_replace_row(Arc::new(db_row));
+            }
+
+            result.push(db_partition);
+        }
+
+        result
+    }
+
+    fn get_keys(partitions: &[DbPartition], range: RowKeysRange) -> Vec<String> {
+        RangeDbRowsIterator::new(partitions.iter(), range)
+            .map(|(db_partition, db_row)| {
+                format!(
+                    "{}/{}",
+                    db_partition.partition_key.as_str(),
+                    db_row.get_row_key()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paging_goes_through_partitions() {
+        let partitions = create_partitions();
+
+        let range = RowKeysRange::all().from_included("b").skip(1).limit(3);
+        assert_eq!(
+            vec!["pk1/c", "pk2/b", "pk2/c"],
+            get_keys(&partitions, range)
+        );
+    }
+
+    #[test]
+    fn test_reverse() {
+        let partitions = create_partitions();
+
+        let range = RowKeysRange::all().to_excluded("c").reverse().limit(3);
+        assert_eq!(
+            vec!["pk3/b", "pk3/a", "pk2/b"],
+            get_keys(&partitions, range)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::io::{Read, Write};
+
+use flate2::Crc;
+
+use super::DbTableSnapshotError;
+
+/// Little endian primitives of the snapshot. Checksum is calculated over every written byte
+pub struct SnapshotBinaryWriter<W: Write> {
+    out: W,
+    crc: Crc,
+}
+
+impl<W: Write> SnapshotBinaryWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            crc: Crc::new(),
+        }
+    }
+
+    pub fn get_checksum(&self) -> u32 {
+        self.crc.sum()
+    }
+
+    pub fn write_slice(&mut self, src: &[u8]) -> Result<(), DbTableSnapshotError> {
+        self.crc.update(src);
+        self.out.write_all(src)?;
+        Ok(())
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> Result<(), DbTableSnapshotError> {
+        self.write_slice(&[value])
+    }
+
+    pub fn write_bool(&mut self, value: bool) -> Result<(), DbTableSnapshotError> {
+        self.write_u8(value as u8)
+    }
+
+    pub fn write_u16(&mut self, value: u16) -> Result<(), DbTableSnapshotError> {
+        self.write_slice(&value.to_le_bytes())
+    }
+
+    pub fn write_u32(&mut self, value: u32) -> Result<(), DbTableSnapshotError> {
+        self.write_slice(&value.to_le_bytes())
+    }
+
+    pub fn write_u64(&mut self, value: u64) -> Result<(), DbTableSnapshotError> {
+        self.write_slice(&value.to_le_bytes())
+    }
+
+    pub fn write_i64(&mut self, value: i64) -> Result<(), DbTableSnapshotError> {
+        self.write_slice(&value.to_le_bytes())
+    }
+
+    #[cfg(feature = "master-node")]
+    pub fn write_opt_u64(&mut self, value: Option<u64>) -> Result<(), DbTableSnapshotError> {
+        match value {
+            Some(value) => {
+                self.write_bool(true)?;
+                self.write_u64(value)
+            }
+            None => self.write_bool(false),
+        }
+    }
+
+    pub fn write_opt_i64(&mut self, value: Option<i64>) -> Result<(), DbTableSnapshotError> {
+        match value {
+            Some(value) => {
+                self.write_bool(true)?;
+                self.write_i64(value)
+            }
+            None => self.write_bool(false),
+        }
+    }
+
+    /// Length is written as u32 before the content
+    pub fn write_bytes(&mut self, src: &[u8]) -> Result<(), DbTableSnapshotError> {
+        self.write_u32(src.len() as u32)?;
+        self.write_slice(src)
+    }
+
+    pub fn write_str(&mut self, src: &str) -> Result<(), DbTableSnapshotError> {
+        self.write_bytes(src.as_bytes())
+    }
+}
+
+pub struct SnapshotBinaryReader<R: Read> {
+    src: R,
+    crc: Crc,
+}
+
+impl<R: Read> SnapshotBinaryReader<R> {
+    pub fn new(src: R) -> Self {
+        Self {
+            src,
+            crc: Crc::new(),
+        }
+    }
+
+    pub fn get_checksum(&self) -> u32 {
+        self.crc.sum()
+    }
+
+    pub fn read_slice(&mut self, out: &mut [u8]) -> Result<(), DbTableSnapshotError> {
+        self.src.read_exact(out)?;
+        self.crc.update(out);
+        Ok(())
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DbTableSnapshotError> {
+        let mut result = [0u8; 1];
+        self.read_slice(&mut result)?;
+        Ok(result[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, DbTableSnapshotError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(DbTableSnapshotError::InvalidFormat(format!(
+                "Invalid bool value {}",
+                value
+            ))),
+        }
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, DbTableSnapshotError> {
+        let mut result = [0u8; 2];
+        self.read_slice(&mut result)?;
+        Ok(u16::from_le_bytes(result))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DbTableSnapshotError> {
+        let mut result = [0u8; 4];
+        self.read_slice(&mut result)?;
+        Ok(u32::from_le_bytes(result))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DbTableSnapshotError> {
+        let mut result = [0u8; 8];
+        self.read_slice(&mut result)?;
+        Ok(u64::from_le_bytes(result))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, DbTableSnapshotError> {
+        let mut result = [0u8; 8];
+        self.read_slice(&mut result)?;
+        Ok(i64::from_le_bytes(result))
+    }
+
+    pub fn read_opt_u64(&mut self) -> Result<Option<u64>, DbTableSnapshotError> {
+        if self.read_bool()? {
+            return Ok(Some(self.read_u64()?));
+        }
+
+        Ok(None)
+    }
+
+    pub fn read_opt_i64(&mut self) -> Result<Option<i64>, DbTableSnapshotError> {
+        if self.read_bool()? {
+            return Ok(Some(self.read_i64()?));
+        }
+
+        Ok(None)
+    }
+
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>, DbTableSnapshotError> {
+        let mut result = Vec::new();
+        self.read_bytes_into(&mut result)?;
+        Ok(result)
+    }
+
+    /// Bytes are appended to out. Result is the position of the bytes in out.
+    /// Length is not trusted: out grows while the bytes are read, so a corrupted length fails with the end of the stream
+    pub fn read_bytes_into(
+        &mut self,
+        out: &mut Vec<u8>,
+    ) -> Result<(usize, usize), DbTableSnapshotError> {
+        let len = self.read_u32()? as usize;
+        let start = out.len();
+
+        let read = (&mut self.src).take(len as u64).read_to_end(out)?;
+
+        if read != len {
+            return Err(DbTableSnapshotError::IoError(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("Expected {} bytes, but only {} bytes are read", len, read),
+            )));
+        }
+
+        self.crc.update(&out[start..]);
+        Ok((start, out.len()))
+    }
+
+    pub fn read_string(&mut self) -> Result<String, DbTableSnapshotError> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes).map_err(|_| {
+            DbTableSnapshotError::InvalidFormat("String is not a valid utf8".to_string())
+        })
+    }
+}
@@ -1,11 +1,15 @@
-use crate::db::DbRow;
+use crate::db::{DbRow, DbRowContent};
+
+use my_no_sql_abstractions::EntityValidationRules;
 
 use my_json::json_reader::JsonArrayIterator;
 
 use rust_extensions::date_time::DateTimeAsMicroseconds;
 
+use std::borrow::Cow;
 use std::sync::Arc;
 
+use super::unescape_json_string;
 use super::DbEntityParseFail;
 use super::DbJsonEntityWithContent;
 use super::DbRowContentCompiler;
@@ -109,6 +113,21 @@ impl DbJsonEntity {
         json_first_line_reader: JsonFirstLineIterator,
         now: &JsonTimeStamp,
     ) -> Result<DbRow, DbEntityParseFail> {
+        Self::parse_into_db_row_with_rules(
+            json_first_line_reader,
+            now,
+            &EntityValidationRules::default(),
+        )
+    }
+
+    pub fn parse_into_db_row_with_rules(
+        json_first_line_reader: JsonFirstLineIterator,
+        now: &JsonTimeStamp,
+        rules: &EntityValidationRules,
+    ) -> Result<DbRow, DbEntityParseFail> {
+        rules.validate_size(json_first_line_reader.as_slice().len())?;
+        rules.validate_json_depth(json_first_line_reader.as_slice())?;
+
         let mut partition_key = None;
         let mut row_key = None;
         let mut expires = None;
@@ -157,14 +176,16 @@ impl DbJsonEntity {
 
         let partition_key = partition_key.unwrap();
 
-        if partition_key.key.len() > 255 {
-            return Err(DbEntityParseFail::PartitionKeyIsTooLong);
-        }
-
         if partition_key.value.is_null(content.as_slice()) {
             return Err(DbEntityParseFail::FieldPartitionKeyCanNotBeNull);
         }
 
+        if rules.has_key_rules() {
+            rules.validate_partition_key(
+                get_key_value(&partition_key, content.as_slice()).as_ref(),
+            )?;
+        }
+
         if row_key.is_none() {
             return Err(DbEntityParseFail::FieldRowKeyIsRequired);
         }
@@ -175,6 +196,10 @@ impl DbJsonEntity {
             return Err(DbEntityParseFail::FieldRowKeyCanNotBeNull);
         }
 
+        if rules.has_key_rules() {
+            rules.validate_row_key(get_key_value(&row_key, content.as_slice()).as_ref())?;
+        }
+
         let db_json_entity = Self {
             partition_key,
             row_key,
@@ -218,9 +243,31 @@ impl DbJsonEntity {
         Ok(result)
     }
 
+    /// Row points to the range of the shared buffer instead of owning the content.
+    /// Buffer stays in memory until all the rows restored from it are dropped
+    pub fn restore_into_shared_db_row(
+        buffer: &Arc<[u8]>,
+        start: usize,
+        end: usize,
+    ) -> Result<DbRow, DbEntityParseFail> {
+        let json_first_line_reader = JsonFirstLineIterator::new(&buffer[start..end]);
+        let db_row = Self::new(json_first_line_reader)?;
+        let content = DbRowContent::new_shared(buffer.clone(), start, end);
+        let result = DbRow::new_with_content(db_row, content);
+        Ok(result)
+    }
+
     pub fn parse_as_vec(
         src: &[u8],
         inject_time_stamp: &JsonTimeStamp,
+    ) -> Result<Vec<Arc<DbRow>>, DbEntityParseFail> {
+        Self::parse_as_vec_with_rules(src, inject_time_stamp, &EntityValidationRules::default())
+    }
+
+    pub fn parse_as_vec_with_rules(
+        src: &[u8],
+        inject_time_stamp: &JsonTimeStamp,
+        rules: &EntityValidationRules,
     ) -> Result<Vec<Arc<DbRow>>, DbEntityParseFail> {
         let mut result = Vec::new();
 
@@ -228,9 +275,10 @@ impl DbJsonEntity {
 
         while let Some(json) = json_array_iterator.get_next() {
             let json = json?;
-            let db_row = DbJsonEntity::parse_into_db_row(
+            let db_row = DbJsonEntity::parse_into_db_row_with_rules(
                 json.unwrap_as_object().unwrap(),
                 inject_time_stamp,
+                rules,
             )?;
             result.push(Arc::new(db_row));
         }
@@ -253,6 +301,18 @@ impl DbJsonEntity {
     pub fn parse_grouped_by_partition_key<'s>(
         src: &'s [u8],
         inject_time_stamp: &JsonTimeStamp,
+    ) -> Result<Vec<(String, Vec<Arc<DbRow>>)>, DbEntityParseFail> {
+        Self::parse_grouped_by_partition_key_with_rules(
+            src,
+            inject_time_stamp,
+            &EntityValidationRules::default(),
+        )
+    }
+
+    pub fn parse_grouped_by_partition_key_with_rules<'s>(
+        src: &'s [u8],
+        inject_time_stamp: &JsonTimeStamp,
+        rules: &EntityValidationRules,
     ) -> Result<Vec<(String, Vec<Arc<DbRow>>)>, DbEntityParseFail> {
         let mut result = Vec::new();
 
@@ -260,9 +320,10 @@ impl DbJsonEntity {
 
         while let Some(json) = json_array_iterator.get_next() {
             let json = json?;
-            let db_row = DbJsonEntity::parse_into_db_row(
+            let db_row = DbJsonEntity::parse_into_db_row_with_rules(
                 json.unwrap_as_object().unwrap(),
                 inject_time_stamp,
+                rules,
             )?;
 
             let partition_key = db_row.get_partition_key();
@@ -353,6 +414,20 @@ impl DbJsonEntity {
     }
 }
 
+/// Unescaped value of PartitionKey or RowKey. None if the value is not a string
+/// Key which is not a string (or can not be unescaped) is validated as it is written in json
+fn get_key_value<'s>(key: &JsonKeyValuePosition, raw: &'s [u8]) -> Cow<'s, str> {
+    let value = &raw[key.value.start..key.value.end];
+
+    if value.len() >= 2 && value[0] == b'"' {
+        if let Some(result) = unescape_json_string(&value[1..value.len() - 1]) {
+            return result;
+        }
+    }
+
+    String::from_utf8_lossy(value)
+}
+
 fn replace_timestamp(
     raw: &mut Vec<u8>,
     time_stamp_position: &JsonKeyValuePosition,
@@ -415,6 +490,7 @@ pub fn get_the_end_of_the_json(data: &[u8]) -> usize {
 mod tests {
 
     use my_json::json_reader::{AsJsonSlice, JsonFirstLineIterator};
+    use my_no_sql_abstractions::{EntityKeyCharset, EntityValidationRules};
     use rust_extensions::date_time::DateTimeAsMicroseconds;
 
     use crate::db_json_entity::{DbEntityParseFail, JsonTimeStamp};
@@ -503,6 +579,67 @@ mod tests {
             panic!("Should not be here")
         }
     }
+    #[test]
+    pub fn parse_with_validation_rules() {
+        let rules = EntityValidationRules {
+            max_partition_key_len: Some(8),
+            max_row_key_len: Some(8),
+            key_charset: EntityKeyCharset::PrintableWithoutReserved,
+            max_entity_size: Some(128),
+            max_json_depth: Some(2),
+        };
+
+        let parse = |json: &str| {
+            DbJsonEntity::parse_into_db_row_with_rules(
+                json.as_bytes().into(),
+                &JsonTimeStamp::now(),
+                &rules,
+            )
+        };
+
+        assert!(parse(r#"{"PartitionKey":"pk","RowKey":"rk","A":{"B":1}}"#).is_ok());
+
+        let result = parse(r#"{"PartitionKey":"pk","RowKey":"too-long-rk"}"#);
+        assert!(matches!(result, Err(DbEntityParseFail::RowKeyIsTooLong)));
+
+        let result = parse(r#"{"PartitionKey":"p\/k","RowKey":"rk"}"#);
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::PartitionKeyHasForbiddenChar {
+                value: '/',
+                position: 1
+            })
+        ));
+
+        let result = parse(r#"{"PartitionKey":"pk","RowKey":123456789}"#);
+        assert!(matches!(result, Err(DbEntityParseFail::RowKeyIsTooLong)));
+
+        let result = parse(r#"{"PartitionKey":"pk","RowKey":"rk","A":{"B":[1]}}"#);
+        assert!(matches!(
+            result,
+            Err(DbEntityParseFail::JsonIsTooDeep { max_depth: 2 })
+        ));
+
+        let json = format!(
+            r#"{{"PartitionKey":"pk","RowKey":"rk","A":"{}"}}"#,
+            "a".repeat(128)
+        );
+        assert!(matches!(
+            parse(json.as_str()),
+            Err(DbEntityParseFail::EntityIsTooBig { max_size: 128, .. })
+        ));
+    }
+
+    #[test]
+    pub fn parse_long_keys_with_default_rules() {
+        let json = format!(r#"{{"PartitionKey":"{}","RowKey":"rk"}}"#, "p".repeat(1024));
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &JsonTimeStamp::now()).unwrap();
+
+        assert_eq!(1024, db_row.get_partition_key().len());
+    }
+
     #[test]
     pub fn parse_some_case_from_real_life() {
         let src_json = r#"{"value":{"is_enabled":true,"fee_percent":5.0,"min_balance_usd":100.0,"fee_period_days":30,"inactivity_period_days":90},"PartitionKey":"*","RowKey":"*"}"#;
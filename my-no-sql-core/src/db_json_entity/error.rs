@@ -1,6 +1,9 @@
 use my_json::json_reader::JsonParseError;
+use my_no_sql_abstractions::EntityValidationError;
 
+/// New reasons may be added, so matches have to handle the rest of the cases
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DbEntityParseFail {
     FieldPartitionKeyIsRequired,
     FieldRowKeyIsRequired,
@@ -8,6 +11,11 @@ pub enum DbEntityParseFail {
     FieldRowKeyCanNotBeNull,
     JsonParseError(JsonParseError),
     PartitionKeyIsTooLong,
+    RowKeyIsTooLong,
+    PartitionKeyHasForbiddenChar { value: char, position: usize },
+    RowKeyHasForbiddenChar { value: char, position: usize },
+    EntityIsTooBig { size: usize, max_size: usize },
+    JsonIsTooDeep { max_depth: usize },
 }
 
 impl From<JsonParseError> for DbEntityParseFail {
@@ -15,3 +23,22 @@ impl From<JsonParseError> for DbEntityParseFail {
         Self::JsonParseError(src)
     }
 }
+
+impl From<EntityValidationError> for DbEntityParseFail {
+    fn from(src: EntityValidationError) -> Self {
+        match src {
+            EntityValidationError::PartitionKeyIsTooLong { .. } => Self::PartitionKeyIsTooLong,
+            EntityValidationError::RowKeyIsTooLong { .. } => Self::RowKeyIsTooLong,
+            EntityValidationError::PartitionKeyHasForbiddenChar { value, position } => {
+                Self::PartitionKeyHasForbiddenChar { value, position }
+            }
+            EntityValidationError::RowKeyHasForbiddenChar { value, position } => {
+                Self::RowKeyHasForbiddenChar { value, position }
+            }
+            EntityValidationError::EntityIsTooBig { size, max_size } => {
+                Self::EntityIsTooBig { size, max_size }
+            }
+            EntityValidationError::JsonIsTooDeep { max_depth } => Self::JsonIsTooDeep { max_depth },
+        }
+    }
+}
@@ -13,3 +13,5 @@ mod db_row_content_compiler;
 pub use db_row_content_compiler::*;
 mod db_json_entity_with_content;
 pub use db_json_entity_with_content::*;
+mod raw_json_reader;
+pub use raw_json_reader::*;
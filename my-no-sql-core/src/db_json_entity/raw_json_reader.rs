@@ -0,0 +1,378 @@
+use std::borrow::Cow;
+
+/// Value of the json field. Content is not copied and not unescaped until it is requested
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RawJsonValue<'s> {
+    Null,
+    Bool(bool),
+    Number(&'s str),
+    /// Content between the quotes. Escape sequences are kept as is
+    String(&'s [u8]),
+    /// Object with the braces
+    Object(&'s [u8]),
+    /// Array with the brackets
+    Array(&'s [u8]),
+}
+
+impl<'s> RawJsonValue<'s> {
+    pub fn from_slice(src: &'s [u8]) -> Option<Self> {
+        match *src.first()? {
+            b'"' => {
+                if src.len() < 2 || src[src.len() - 1] != b'"' {
+                    return None;
+                }
+                Some(Self::String(&src[1..src.len() - 1]))
+            }
+            b'{' => Some(Self::Object(src)),
+            b'[' => Some(Self::Array(src)),
+            _ => match src {
+                b"null" => Some(Self::Null),
+                b"true" => Some(Self::Bool(true)),
+                b"false" => Some(Self::Bool(false)),
+                _ => Some(Self::Number(std::str::from_utf8(src).ok()?)),
+            },
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Unescaped string value. None if value is not a string
+    pub fn as_str(&self) -> Option<Cow<'s, str>> {
+        match self {
+            Self::String(src) => unescape_json_string(src),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(src) => src.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Field of the json object. Value positions are the positions in the source slice
+#[derive(Debug, Clone, Copy)]
+pub struct RawJsonField<'s> {
+    /// Name between the quotes. Escape sequences are kept as is
+    pub name: &'s [u8],
+    /// Position of the name without the quotes
+    pub name_start: usize,
+    pub value_start: usize,
+    pub value_end: usize,
+}
+
+impl<'s> RawJsonField<'s> {
+    pub fn name_is(&self, name: &str) -> bool {
+        if !self.name.contains(&b'\\') {
+            return self.name == name.as_bytes();
+        }
+
+        match unescape_json_string(self.name) {
+            Some(unescaped) => unescaped == name,
+            None => false,
+        }
+    }
+}
+
+/// Iterates top level fields of the json object. Iteration stops at the first malformed place
+pub struct RawJsonObjectIterator<'s> {
+    raw: &'s [u8],
+    pos: usize,
+    first: bool,
+}
+
+impl<'s> RawJsonObjectIterator<'s> {
+    pub fn new(raw: &'s [u8]) -> Self {
+        let pos = skip_whitespaces(raw, 0);
+
+        if raw.get(pos) != Some(&b'{') {
+            return Self {
+                raw,
+                pos: raw.len(),
+                first: true,
+            };
+        }
+
+        Self {
+            raw,
+            pos: pos + 1,
+            first: true,
+        }
+    }
+
+    pub fn get_value(&self, field: &RawJsonField<'s>) -> Option<RawJsonValue<'s>> {
+        RawJsonValue::from_slice(&self.raw[field.value_start..field.value_end])
+    }
+
+    fn read_next(&mut self) -> Option<RawJsonField<'s>> {
+        let mut pos = skip_whitespaces(self.raw, self.pos);
+
+        match *self.raw.get(pos)? {
+            b'}' => return None,
+            b',' if !self.first => {
+                pos = skip_whitespaces(self.raw, pos + 1);
+            }
+            _ if self.first => {}
+            _ => return None,
+        }
+
+        if *self.raw.get(pos)? != b'"' {
+            return None;
+        }
+
+        let name_end = find_string_end(self.raw, pos)?;
+        let name_start = pos + 1;
+        let name = &self.raw[name_start..name_end - 1];
+
+        pos = skip_whitespaces(self.raw, name_end);
+
+        if *self.raw.get(pos)? != b':' {
+            return None;
+        }
+
+        let value_start = skip_whitespaces(self.raw, pos + 1);
+        let value_end = find_value_end(self.raw, value_start)?;
+
+        self.pos = value_end;
+        self.first = false;
+
+        Some(RawJsonField {
+            name,
+            name_start,
+            value_start,
+            value_end,
+        })
+    }
+}
+
+impl<'s> Iterator for RawJsonObjectIterator<'s> {
+    type Item = RawJsonField<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.read_next();
+
+        if result.is_none() {
+            self.pos = self.raw.len();
+        }
+
+        result
+    }
+}
+
+/// Finds the value by the path of the field names. Every name except the last one must point to an object
+pub fn get_json_field<'s>(raw: &'s [u8], path: &[impl AsRef<str>]) -> Option<RawJsonValue<'s>> {
+    RawJsonValue::from_slice(get_json_field_slice(raw, path)?)
+}
+
+/// Same as get_json_field, but the value is returned as the json slice
+pub fn get_json_field_slice<'s>(raw: &'s [u8], path: &[impl AsRef<str>]) -> Option<&'s [u8]> {
+    let (name, rest) = path.split_first()?;
+
+    let field = RawJsonObjectIterator::new(raw).find(|itm| itm.name_is(name.as_ref()))?;
+    let value = &raw[field.value_start..field.value_end];
+
+    if rest.is_empty() {
+        return Some(value);
+    }
+
+    if value.first() != Some(&b'{') {
+        return None;
+    }
+
+    get_json_field_slice(value, rest)
+}
+
+fn skip_whitespaces(raw: &[u8], mut pos: usize) -> usize {
+    while pos < raw.len() && raw[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+
+    pos
+}
+
+/// Position must point to the opening quote. Result is the position right after the closing quote
+fn find_string_end(raw: &[u8], pos: usize) -> Option<usize> {
+    let mut pos = pos + 1;
+
+    while pos < raw.len() {
+        match raw[pos] {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+
+    None
+}
+
+/// Result is the position right after the value
+fn find_value_end(raw: &[u8], pos: usize) -> Option<usize> {
+    match *raw.get(pos)? {
+        b'"' => find_string_end(raw, pos),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut pos = pos;
+
+            while pos < raw.len() {
+                match raw[pos] {
+                    b'"' => {
+                        pos = find_string_end(raw, pos)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(pos + 1);
+                        }
+                    }
+                    _ => {}
+                }
+
+                pos += 1;
+            }
+
+            None
+        }
+        _ => {
+            let mut end = pos;
+
+            while end < raw.len() {
+                match raw[end] {
+                    b',' | b'}' | b']' => break,
+                    b if b.is_ascii_whitespace() => break,
+                    _ => end += 1,
+                }
+            }
+
+            if end == pos {
+                return None;
+            }
+
+            Some(end)
+        }
+    }
+}
+
+/// Source is the content between the quotes
+pub fn unescape_json_string(src: &[u8]) -> Option<Cow<str>> {
+    if !src.contains(&b'\\') {
+        return std::str::from_utf8(src).ok().map(Cow::Borrowed);
+    }
+
+    let src = std::str::from_utf8(src).ok()?;
+    let mut result = String::with_capacity(src.len());
+    let mut chars = src.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '"' => result.push('"'),
+            '\\' => result.push('\\'),
+            '/' => result.push('/'),
+            'b' => result.push('\u{0008}'),
+            'f' => result.push('\u{000c}'),
+            'n' => result.push('\n'),
+            'r' => result.push('\r'),
+            't' => result.push('\t'),
+            'u' => {
+                let high = read_hex_code(&mut chars)?;
+
+                if (0xD800..0xDC00).contains(&high) {
+                    if chars.next()? != '\\' || chars.next()? != 'u' {
+                        return None;
+                    }
+
+                    let low = read_hex_code(&mut chars)?;
+                    let code = 0x10000 + ((high - 0xD800) << 10) + low.checked_sub(0xDC00)?;
+                    result.push(char::from_u32(code)?);
+                } else {
+                    result.push(char::from_u32(high)?);
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    Some(Cow::Owned(result))
+}
+
+fn read_hex_code(chars: &mut std::str::Chars) -> Option<u32> {
+    let mut result = 0;
+
+    for _ in 0..4 {
+        result = result * 16 + chars.next()?.to_digit(16)?;
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_json_field, RawJsonObjectIterator, RawJsonValue};
+
+    const JSON: &str = r#" {"PartitionKey":"pk", "RowKey" : "r\"k", "Amount":-12.5e1,
+        "Active":true, "Nothing":null, "List":[1,{"a":"]"}], "Nested":{"Inner":{"Value":"AB"}}} "#;
+
+    #[test]
+    fn test_iterate_fields() {
+        let names: Vec<&str> = RawJsonObjectIterator::new(JSON.as_bytes())
+            .map(|itm| std::str::from_utf8(itm.name).unwrap())
+            .collect();
+
+        assert_eq!(
+            vec![
+                "PartitionKey",
+                "RowKey",
+                "Amount",
+                "Active",
+                "Nothing",
+                "List",
+                "Nested"
+            ],
+            names
+        );
+    }
+
+    #[test]
+    fn test_get_fields() {
+        let raw = JSON.as_bytes();
+
+        let value = get_json_field(raw, &["RowKey"]).unwrap();
+        assert_eq!("r\"k", value.as_str().unwrap());
+
+        let value = get_json_field(raw, &["Amount"]).unwrap();
+        assert_eq!(-125.0, value.as_f64().unwrap());
+
+        assert_eq!(
+            Some(RawJsonValue::Bool(true)),
+            get_json_field(raw, &["Active"])
+        );
+        assert!(get_json_field(raw, &["Nothing"]).unwrap().is_null());
+        assert_eq!(
+            Some(RawJsonValue::Array(br#"[1,{"a":"]"}]"#)),
+            get_json_field(raw, &["List"])
+        );
+
+        let value = get_json_field(raw, &["Nested", "Inner", "Value"]).unwrap();
+        assert_eq!("AB", value.as_str().unwrap());
+
+        assert!(get_json_field(raw, &["Nested", "Missing"]).is_none());
+        assert!(get_json_field(raw, &["RowKey", "Inner"]).is_none());
+    }
+}
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use crate::{
+    db::{DbPartition, DbRow, DbTable},
+    db_json_entity::{DbEntityParseFail, DbJsonEntity},
+};
+
+use super::{SortedDiffItem, SortedDiffIterator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbRowsDiffMode {
+    /// Row is updated if TimeStamp is changed. Content is compared if one of the rows has no TimeStamp
+    TimeStamp,
+    /// Row is updated if json content is changed
+    Content,
+}
+
+impl DbRowsDiffMode {
+    pub fn is_updated(&self, before: &DbRow, now: &DbRow) -> bool {
+        if let DbRowsDiffMode::TimeStamp = self {
+            if let (Some(before_time_stamp), Some(now_time_stamp)) =
+                (before.get_time_stamp_opt(), now.get_time_stamp_opt())
+            {
+                return before_time_stamp != now_time_stamp;
+            }
+        }
+
+        before.get_src_as_slice() != now.get_src_as_slice()
+    }
+}
+
+/// Difference between two versions of the rows. Rows go in the order of partition key and row key.
+/// Updated rows are the new versions of the rows.
+#[derive(Default)]
+pub struct DbRowsDiff {
+    pub inserted: Vec<Arc<DbRow>>,
+    pub updated: Vec<Arc<DbRow>>,
+    pub deleted: Vec<Arc<DbRow>>,
+}
+
+impl DbRowsDiff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+
+    /// Both slices must be sorted by row key
+    pub fn add_rows_difference(
+        &mut self,
+        before: &[Arc<DbRow>],
+        now: &[Arc<DbRow>],
+        mode: DbRowsDiffMode,
+    ) {
+        let before = before.iter().map(|itm| (itm.get_row_key(), itm));
+        let now = now.iter().map(|itm| (itm.get_row_key(), itm));
+
+        for (_, item) in SortedDiffIterator::new(before, now) {
+            match item {
+                SortedDiffItem::Inserted(now) => self.inserted.push(now.clone()),
+                SortedDiffItem::Both { before, now } => {
+                    if mode.is_updated(before, now) {
+                        self.updated.push(now.clone());
+                    }
+                }
+                SortedDiffItem::Deleted(before) => self.deleted.push(before.clone()),
+            }
+        }
+    }
+
+    /// Both iterators must be sorted by partition key. Rows of every partition must be sorted by row key
+    fn add_partitions_difference<'s>(
+        &mut self,
+        before: impl Iterator<Item = (&'s str, &'s [Arc<DbRow>])>,
+        now: impl Iterator<Item = (&'s str, &'s [Arc<DbRow>])>,
+        mode: DbRowsDiffMode,
+    ) {
+        for (_, item) in SortedDiffIterator::new(before, now) {
+            match item {
+                SortedDiffItem::Inserted(now) => self.add_rows_difference(&[], now, mode),
+                SortedDiffItem::Both { before, now } => self.add_rows_difference(before, now, mode),
+                SortedDiffItem::Deleted(before) => self.add_rows_difference(before, &[], mode),
+            }
+        }
+    }
+}
+
+pub fn diff_partitions(
+    before: Option<&DbPartition>,
+    now: Option<&DbPartition>,
+    mode: DbRowsDiffMode,
+) -> DbRowsDiff {
+    let mut result = DbRowsDiff::new();
+    result.add_rows_difference(get_rows(before), get_rows(now), mode);
+    result
+}
+
+pub fn diff_tables(before: &DbTable, now: &DbTable, mode: DbRowsDiffMode) -> DbRowsDiff {
+    let mut result = DbRowsDiff::new();
+    result.add_partitions_difference(
+        before.get_partitions().map(get_partition_rows),
+        now.get_partitions().map(get_partition_rows),
+        mode,
+    );
+    result
+}
+
+/// Compares partition with the json array of the rows. Json rows must have TimeStamp field
+pub fn diff_partition_with_json_array(
+    before: Option<&DbPartition>,
+    now: &[u8],
+    mode: DbRowsDiffMode,
+) -> Result<DbRowsDiff, DbEntityParseFail> {
+    let mut now = DbJsonEntity::restore_as_vec(now)?;
+    now.sort_by(|a, b| a.get_row_key().cmp(b.get_row_key()));
+
+    let mut result = DbRowsDiff::new();
+    result.add_rows_difference(get_rows(before), now.as_slice(), mode);
+    Ok(result)
+}
+
+/// Compares table with the json array of the rows. Json rows must have TimeStamp field
+pub fn diff_table_with_json_array(
+    before: &DbTable,
+    now: &[u8],
+    mode: DbRowsDiffMode,
+) -> Result<DbRowsDiff, DbEntityParseFail> {
+    let mut now = DbJsonEntity::restore_grouped_by_partition_key(now)?;
+
+    for (_, db_rows) in now.iter_mut() {
+        db_rows.sort_by(|a, b| a.get_row_key().cmp(b.get_row_key()));
+    }
+
+    let mut result = DbRowsDiff::new();
+    result.add_partitions_difference(
+        before.get_partitions().map(get_partition_rows),
+        now.iter()
+            .map(|(partition_key, db_rows)| (partition_key.as_str(), db_rows.as_slice())),
+        mode,
+    );
+    Ok(result)
+}
+
+fn get_rows(db_partition: Option<&DbPartition>) -> &[Arc<DbRow>] {
+    match db_partition {
+        Some(db_partition) => db_partition.get_all_rows().as_slice(),
+        None => &[],
+    }
+}
+
+fn get_partition_rows(db_partition: &DbPartition) -> (&str, &[Arc<DbRow>]) {
+    (
+        db_partition.partition_key.as_str(),
+        db_partition.get_all_rows().as_slice(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::{DbPartition, DbRow, DbTable},
+        db_json_entity::DbJsonEntity,
+    };
+
+    use super::{diff_partitions, diff_table_with_json_array, DbRowsDiffMode};
+
+    fn create_row(partition_key: &str, row_key: &str, time_stamp: &str, value: i32) -> Arc<DbRow> {
+        let json = format!(
+            r#"{{"PartitionKey":"{}","RowKey":"{}","TimeStamp":"{}","Value":{}}}"#,
+            partition_key, row_key, time_stamp, value
+        );
+        Arc::new(DbJsonEntity::restore_into_db_row(json.into_bytes()).unwrap())
+    }
+
+    fn create_partition(partition_key: &str, rows: &[(&str, &str, i32)]) -> DbPartition {
+        let mut db_partition = DbPartition::new(partition_key.to_string());
+
+        for (row_key, time_stamp, value) in rows {
+            db_partition.insert_or_replace_row(create_row(
+                partition_key,
+                row_key,
+                time_stamp,
+                *value,
+            ));
+        }
+
+        db_partition
+    }
+
+    fn get_keys(db_rows: &[Arc<DbRow>]) -> Vec<String> {
+        db_rows
+            .iter()
+            .map(|itm| format!("{}/{}", itm.get_partition_key(), itm.get_row_key()))
+            .collect()
+    }
+
+    #[test]
+    fn test_partitions_diff() {
+        let before = create_partition(
+            "pk",
+            &[
+                ("a", "2024-01-01T00:00:00", 1),
+                ("b", "2024-01-01T00:00:00", 1),
+                ("c", "2024-01-01T00:00:00", 1),
+            ],
+        );
+
+        let now = create_partition(
+            "pk",
+            &[
+                ("b", "2024-01-01T00:00:00", 2),
+                ("c", "2024-01-02T00:00:00", 1),
+                ("d", "2024-01-01T00:00:00", 1),
+            ],
+        );
+
+        let diff = diff_partitions(Some(&before), Some(&now), DbRowsDiffMode::TimeStamp);
+        assert_eq!(vec!["pk/d"], get_keys(&diff.inserted));
+        assert_eq!(vec!["pk/c"], get_keys(&diff.updated));
+        assert_eq!(vec!["pk/a"], get_keys(&diff.deleted));
+
+        let diff = diff_partitions(Some(&before), Some(&now), DbRowsDiffMode::Content);
+        assert_eq!(vec!["pk/b", "pk/c"], get_keys(&diff.updated));
+
+        let diff = diff_partitions(Some(&before), None, DbRowsDiffMode::Content);
+        assert_eq!(3, diff.deleted.len());
+
+        let diff = diff_partitions(Some(&before), Some(&before), DbRowsDiffMode::Content);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_table_with_json_array_diff() {
+        let mut before = DbTable::new_with_default_attributes("test".into());
+        before.init_partition(create_partition("pk1", &[("a", "2024-01-01T00:00:00", 1)]));
+        before.init_partition(create_partition("pk2", &[("a", "2024-01-01T00:00:00", 1)]));
+
+        let json = r#"[
+            {"PartitionKey":"pk3","RowKey":"a","TimeStamp":"2024-01-01T00:00:00"},
+            {"PartitionKey":"pk2","RowKey":"b","TimeStamp":"2024-01-01T00:00:00"},
+            {"PartitionKey":"pk2","RowKey":"a","TimeStamp":"2024-01-03T00:00:00"}
+        ]"#;
+
+        let diff = diff_table_with_json_array(&before, json.as_bytes(), DbRowsDiffMode::TimeStamp)
+            .unwrap();
+
+        assert_eq!(vec!["pk2/b", "pk3/a"], get_keys(&diff.inserted));
+        assert_eq!(vec!["pk2/a"], get_keys(&diff.updated));
+        assert_eq!(vec!["pk1/a"], get_keys(&diff.deleted));
+    }
+}
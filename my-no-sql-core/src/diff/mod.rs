@@ -0,0 +1,4 @@
+mod sorted_diff_iterator;
+pub use sorted_diff_iterator::*;
+mod db_rows_diff;
+pub use db_rows_diff::*;
@@ -0,0 +1,92 @@
+use std::iter::Peekable;
+
+pub enum SortedDiffItem<TBefore, TNow> {
+    /// Key exists only in the new version
+    Inserted(TNow),
+    /// Key exists in both versions. It is up to the consumer to decide if the value is changed
+    Both { before: TBefore, now: TNow },
+    /// Key exists only in the old version
+    Deleted(TBefore),
+}
+
+/// Walks two iterators sorted by the key at the same time. Every key is visited once.
+pub struct SortedDiffIterator<TKey, TBefore, TNow, TBeforeIterator, TNowIterator>
+where
+    TKey: Ord,
+    TBeforeIterator: Iterator<Item = (TKey, TBefore)>,
+    TNowIterator: Iterator<Item = (TKey, TNow)>,
+{
+    before: Peekable<TBeforeIterator>,
+    now: Peekable<TNowIterator>,
+}
+
+impl<TKey, TBefore, TNow, TBeforeIterator, TNowIterator>
+    SortedDiffIterator<TKey, TBefore, TNow, TBeforeIterator, TNowIterator>
+where
+    TKey: Ord,
+    TBeforeIterator: Iterator<Item = (TKey, TBefore)>,
+    TNowIterator: Iterator<Item = (TKey, TNow)>,
+{
+    pub fn new(before: TBeforeIterator, now: TNowIterator) -> Self {
+        Self {
+            before: before.peekable(),
+            now: now.peekable(),
+        }
+    }
+}
+
+impl<TKey, TBefore, TNow, TBeforeIterator, TNowIterator> Iterator
+    for SortedDiffIterator<TKey, TBefore, TNow, TBeforeIterator, TNowIterator>
+where
+    TKey: Ord,
+    TBeforeIterator: Iterator<Item = (TKey, TBefore)>,
+    TNowIterator: Iterator<Item = (TKey, TNow)>,
+{
+    type Item = (TKey, SortedDiffItem<TBefore, TNow>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.before.peek(), self.now.peek()) {
+            (Some((before_key, _)), Some((now_key, _))) => before_key.cmp(now_key),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => return None,
+        };
+
+        match ordering {
+            std::cmp::Ordering::Less => {
+                let (key, before) = self.before.next()?;
+                Some((key, SortedDiffItem::Deleted(before)))
+            }
+            std::cmp::Ordering::Greater => {
+                let (key, now) = self.now.next()?;
+                Some((key, SortedDiffItem::Inserted(now)))
+            }
+            std::cmp::Ordering::Equal => {
+                let (_, before) = self.before.next()?;
+                let (key, now) = self.now.next()?;
+                Some((key, SortedDiffItem::Both { before, now }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SortedDiffItem, SortedDiffIterator};
+
+    #[test]
+    fn test_merge_walk() {
+        let before = vec![("a", 1), ("b", 2), ("d", 4)];
+        let now = vec![("b", 3), ("c", 3), ("d", 4)];
+
+        let result: Vec<String> = SortedDiffIterator::new(before.into_iter(), now.into_iter())
+            .map(|(key, item)| match item {
+                SortedDiffItem::Inserted(now) => format!("+{}{}", key, now),
+                SortedDiffItem::Both { before, now } => format!("{}{}{}", key, before, now),
+                SortedDiffItem::Deleted(before) => format!("-{}{}", key, before),
+            })
+            .collect();
+
+        assert_eq!(vec!["-a1", "b23", "+c3", "d44"], result);
+    }
+}
@@ -20,6 +20,11 @@ where
     match parse_result {
         Ok(el) => return Ok(el),
         Err(err) => {
+            // Entities written with MessagePack encoding can be read by the json entities during the migration.
+            // Envelope is looked for only if the plain json does not fit, so plain json is parsed once
+            if let Some(envelope) = crate::message_pack_entity::MessagePackEnvelope::read(data) {
+                return envelope.deserialize_entity();
+            }
    
             let json_first_line_iterator = JsonFirstLineIterator::new(data);
             let db_entity = DbJsonEntity::new(json_first_line_iterator);
@@ -1,9 +1,12 @@
 pub mod db;
+pub mod diff;
+pub mod query;
 pub mod db_json_entity;
 mod expiration_index;
 
 pub mod validations;
 pub use expiration_index::*;
 pub mod entity_serializer;
+pub mod message_pack_entity;
 pub extern crate my_json;
 pub extern crate rust_extensions;
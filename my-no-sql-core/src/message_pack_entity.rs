@@ -0,0 +1,170 @@
+use my_json::json_string_value::escape_json_string_value;
+use my_no_sql_abstractions::{MyNoSqlEntity, Timestamp};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::db_json_entity::{consts, RawJsonObjectIterator, RawJsonValue};
+
+/// Field of the envelope with the fields of the entity. Marks the entity as MessagePack encoded
+pub const MESSAGE_PACK_FIELD: &str = "$mp";
+
+/// Json envelope of the MessagePack encoded entity:
+/// `{"PartitionKey":"..","RowKey":"..","Expires":"..","$mp":[<fields by position>]}`.
+/// Node stores json rows, so the MessagePack layout (struct as the array of its fields) is written as json array
+/// instead of the binary: base64 of the binary would add a third to the size.
+/// Keys and Expires stay readable for the node and DbJsonEntity, TimeStamp is injected by the node as usual
+pub struct MessagePackEnvelope<'s> {
+    body: &'s [u8],
+    time_stamp: Option<Timestamp>,
+    expires: Option<Timestamp>,
+}
+
+impl<'s> MessagePackEnvelope<'s> {
+    /// None - if data is not an envelope. Only the top level fields are visited
+    pub fn read(data: &'s [u8]) -> Option<Self> {
+        let mut body = None;
+        let mut time_stamp = None;
+        let mut expires = None;
+
+        let fields = RawJsonObjectIterator::new(data);
+
+        for field in fields {
+            if field.name_is(MESSAGE_PACK_FIELD) {
+                body = Some(&data[field.value_start..field.value_end]);
+            } else if field.name_is(consts::TIME_STAMP) {
+                time_stamp = read_timestamp(&data[field.value_start..field.value_end]);
+            } else if field.name_is(consts::EXPIRES) {
+                expires = read_timestamp(&data[field.value_start..field.value_end]);
+            }
+        }
+
+        Some(Self {
+            body: body?,
+            time_stamp,
+            expires,
+        })
+    }
+
+    /// TimeStamp injected by the node
+    pub fn get_time_stamp(&self) -> Option<Timestamp> {
+        self.time_stamp
+    }
+
+    /// Expires can be changed by the node without touching the encoded entity
+    pub fn get_expires(&self) -> Option<Timestamp> {
+        self.expires
+    }
+
+    pub fn deserialize_entity<TMyNoSqlEntity>(&self) -> Result<TMyNoSqlEntity, String>
+    where
+        TMyNoSqlEntity: MyNoSqlEntity + DeserializeOwned,
+    {
+        // Derived Deserialize reads the struct from the array of its fields as well
+        serde_json::from_slice(self.body).map_err(|err| {
+            format!(
+                "Table: {}. Can not parse MessagePack entity. Err: {:?}",
+                TMyNoSqlEntity::TABLE_NAME,
+                err
+            )
+        })
+    }
+}
+
+/// Fields are encoded as the array in the order of declaration, so field names are not stored.
+/// New fields can only be added to the end of the struct with #[serde(default)];
+/// skip_serializing_if, flatten and byte fields are not supported
+pub fn serialize<TMyNoSqlEntity>(
+    entity: &TMyNoSqlEntity,
+    expires: Option<Timestamp>,
+) -> Result<Vec<u8>, String>
+where
+    TMyNoSqlEntity: MyNoSqlEntity + Serialize,
+{
+    let body = encode_fields(entity).map_err(|err| {
+        format!(
+            "Table: {}. Can not serialize entity with PartitionKey: [{}] and RowKey: [{}]. Err: {}",
+            TMyNoSqlEntity::TABLE_NAME,
+            entity.get_partition_key(),
+            entity.get_row_key(),
+            err
+        )
+    })?;
+
+    let mut result = Vec::with_capacity(body.len() + 128);
+
+    write_string_field(
+        &mut result,
+        consts::PARTITION_KEY,
+        entity.get_partition_key(),
+    );
+    write_string_field(&mut result, consts::ROW_KEY, entity.get_row_key());
+
+    if let Some(expires) = expires {
+        if !expires.is_default() {
+            // Same format as the json serialization of the entity has
+            let expires = serde_json::to_string(&expires).map_err(|err| format!("{:?}", err))?;
+            write_string_field(&mut result, consts::EXPIRES, expires.trim_matches('"'));
+        }
+    }
+
+    write_field_name(&mut result, MESSAGE_PACK_FIELD);
+    result.extend_from_slice(body.as_slice());
+    result.push(b'}');
+
+    Ok(result)
+}
+
+/// MessagePack encodes struct as the array of its fields. Array is written as json, so the node can store it
+fn encode_fields<TMyNoSqlEntity: Serialize>(entity: &TMyNoSqlEntity) -> Result<Vec<u8>, String> {
+    let message_pack = rmp_serde::to_vec(entity).map_err(|err| format!("{:?}", err))?;
+
+    let fields: serde_json::Value =
+        rmp_serde::from_slice(message_pack.as_slice()).map_err(|err| format!("{:?}", err))?;
+
+    serde_json::to_vec(&fields).map_err(|err| format!("{:?}", err))
+}
+
+/// Plain json is accepted as well, so rows written before the migration can be read.
+/// Envelope is returned since TimeStamp and Expires of the encoded entity are the ones the writer had
+pub fn deserialize<'s, TMyNoSqlEntity>(
+    data: &'s [u8],
+) -> Result<(TMyNoSqlEntity, Option<MessagePackEnvelope<'s>>), String>
+where
+    TMyNoSqlEntity: MyNoSqlEntity + DeserializeOwned,
+{
+    match MessagePackEnvelope::read(data) {
+        Some(envelope) => {
+            let entity = envelope.deserialize_entity()?;
+            Ok((entity, Some(envelope)))
+        }
+        None => {
+            let entity = crate::entity_serializer::deserialize(data)?;
+            Ok((entity, None))
+        }
+    }
+}
+
+fn write_field_name(out: &mut Vec<u8>, name: &str) {
+    if out.is_empty() {
+        out.push(b'{');
+    } else {
+        out.push(b',');
+    }
+
+    out.push(b'"');
+    out.extend_from_slice(name.as_bytes());
+    out.extend_from_slice(b"\":");
+}
+
+fn write_string_field(out: &mut Vec<u8>, name: &str, value: &str) {
+    write_field_name(out, name);
+    out.push(b'"');
+    out.extend_from_slice(escape_json_string_value(value).as_str().as_bytes());
+    out.push(b'"');
+}
+
+fn read_timestamp(value: &[u8]) -> Option<Timestamp> {
+    let value = RawJsonValue::from_slice(value)?.as_str()?;
+    let value = DateTimeAsMicroseconds::from_str(value.as_ref())?;
+    Some(value.into())
+}
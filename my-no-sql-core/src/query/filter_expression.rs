@@ -0,0 +1,312 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use crate::{
+    db::DbRow,
+    db_json_entity::{get_json_field, RawJsonValue},
+};
+
+use super::FilterParseError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOperator {
+    Eq,
+    NotEq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CompareOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompareOperator::Eq => "=",
+            CompareOperator::NotEq => "!=",
+            CompareOperator::Gt => ">",
+            CompareOperator::Ge => ">=",
+            CompareOperator::Lt => "<",
+            CompareOperator::Le => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl FilterValue {
+    /// None - if values can not be compared. Missing field is compared as null
+    fn compare(&self, value: Option<RawJsonValue>) -> Option<Ordering> {
+        let value = value.unwrap_or(RawJsonValue::Null);
+
+        match self {
+            FilterValue::Null => {
+                if value.is_null() {
+                    Some(Ordering::Equal)
+                } else {
+                    None
+                }
+            }
+            FilterValue::Bool(expected) => {
+                let value = value.as_bool()?;
+                Some(value.cmp(expected))
+            }
+            FilterValue::Number(expected) => {
+                let value = value.as_f64()?;
+                value.partial_cmp(expected)
+            }
+            FilterValue::String(expected) => {
+                let value = value.as_str()?;
+                Some(value.as_ref().cmp(expected.as_str()))
+            }
+        }
+    }
+}
+
+impl Display for FilterValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterValue::Null => write!(f, "null"),
+            FilterValue::Bool(value) => write!(f, "{}", value),
+            FilterValue::Number(value) => write!(f, "{}", value),
+            FilterValue::String(value) => {
+                write!(f, "'")?;
+                for c in value.chars() {
+                    match c {
+                        '\'' => write!(f, "\\'")?,
+                        '\\' => write!(f, "\\\\")?,
+                        _ => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "'")
+            }
+        }
+    }
+}
+
+/// Path of the field. Nested fields are separated by dots: Address.City
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPath(Vec<String>);
+
+impl FieldPath {
+    pub fn new(path: &str) -> Self {
+        Self(path.split('.').map(|itm| itm.to_string()).collect())
+    }
+
+    pub fn get_names(&self) -> &[String] {
+        self.0.as_slice()
+    }
+
+    pub fn get_value<'s>(&self, raw: &'s [u8]) -> Option<RawJsonValue<'s>> {
+        get_json_field(raw, self.0.as_slice())
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join("."))
+    }
+}
+
+/// Parsed filter. Expression is evaluated against the raw json of the row.
+///
+/// Example: `Status = 'Active' AND (Amount >= 10 OR NOT Address.City IN ('Kyiv', 'Lviv')) AND RowKey STARTSWITH 'user-'`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpression {
+    Compare {
+        field: FieldPath,
+        operator: CompareOperator,
+        value: FilterValue,
+    },
+    In {
+        field: FieldPath,
+        values: Vec<FilterValue>,
+    },
+    StartsWith {
+        field: FieldPath,
+        prefix: String,
+    },
+    And(Box<FilterExpression>, Box<FilterExpression>),
+    Or(Box<FilterExpression>, Box<FilterExpression>),
+    Not(Box<FilterExpression>),
+}
+
+impl FilterExpression {
+    pub fn parse(src: &str) -> Result<Self, FilterParseError> {
+        super::parse_filter_expression(src)
+    }
+
+    pub fn is_match(&self, raw: &[u8]) -> bool {
+        match self {
+            FilterExpression::Compare {
+                field,
+                operator,
+                value,
+            } => {
+                let ordering = value.compare(field.get_value(raw));
+
+                match operator {
+                    CompareOperator::Eq => ordering == Some(Ordering::Equal),
+                    CompareOperator::NotEq => ordering != Some(Ordering::Equal),
+                    CompareOperator::Gt => ordering == Some(Ordering::Greater),
+                    CompareOperator::Ge => {
+                        matches!(ordering, Some(Ordering::Greater) | Some(Ordering::Equal))
+                    }
+                    CompareOperator::Lt => ordering == Some(Ordering::Less),
+                    CompareOperator::Le => {
+                        matches!(ordering, Some(Ordering::Less) | Some(Ordering::Equal))
+                    }
+                }
+            }
+            FilterExpression::In { field, values } => {
+                let value = field.get_value(raw);
+                values
+                    .iter()
+                    .any(|itm| itm.compare(value) == Some(Ordering::Equal))
+            }
+            FilterExpression::StartsWith { field, prefix } => {
+                match field.get_value(raw).and_then(|itm| itm.as_str()) {
+                    Some(value) => value.starts_with(prefix.as_str()),
+                    None => false,
+                }
+            }
+            FilterExpression::And(left, right) => left.is_match(raw) && right.is_match(raw),
+            FilterExpression::Or(left, right) => left.is_match(raw) || right.is_match(raw),
+            FilterExpression::Not(expression) => !expression.is_match(raw),
+        }
+    }
+
+    pub fn is_match_db_row(&self, db_row: &DbRow) -> bool {
+        self.is_match(db_row.get_src_as_slice())
+    }
+}
+
+/// Result can be parsed back into the same expression
+impl Display for FilterExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterExpression::Compare {
+                field,
+                operator,
+                value,
+            } => write!(f, "{} {} {}", field, operator.as_str(), value),
+            FilterExpression::In { field, values } => {
+                write!(f, "{} IN (", field)?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, ")")
+            }
+            FilterExpression::StartsWith { field, prefix } => write!(
+                f,
+                "{} STARTSWITH {}",
+                field,
+                FilterValue::String(prefix.to_string())
+            ),
+            FilterExpression::And(left, right) => write!(f, "({} AND {})", left, right),
+            FilterExpression::Or(left, right) => write!(f, "({} OR {})", left, right),
+            FilterExpression::Not(expression) => write!(f, "NOT ({})", expression),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        db::DbTable,
+        db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    };
+
+    use super::FilterExpression;
+
+    const JSON: &str = r#"{"PartitionKey":"pk","RowKey":"user-1","Status":"Active","Amount":15,
+        "Address":{"City":"Kyiv"},"Deleted":false,"Comment":null}"#;
+
+    fn is_match(expression: &str) -> bool {
+        FilterExpression::parse(expression)
+            .unwrap()
+            .is_match(JSON.as_bytes())
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(is_match("Status = 'Active'"));
+        assert!(is_match("Status != 'Blocked'"));
+        assert!(is_match("Amount > 10"));
+        assert!(is_match("Amount <= 15"));
+        assert!(!is_match("Amount < 15"));
+        assert!(is_match("Deleted = false"));
+        assert!(is_match("Comment = null"));
+        assert!(is_match("Missing = null"));
+        assert!(!is_match("Status > 10"));
+        assert!(is_match("Address.City = \"Kyiv\""));
+    }
+
+    #[test]
+    fn test_in_and_prefix() {
+        assert!(is_match("Address.City IN ('Lviv', 'Kyiv')"));
+        assert!(!is_match("Amount in (1, 2, 3)"));
+        assert!(is_match("RowKey STARTSWITH 'user-'"));
+        assert!(!is_match("Amount STARTSWITH '1'"));
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        assert!(is_match("Status = 'Active' AND Amount > 10"));
+        assert!(is_match("Status = 'Blocked' OR Amount > 10"));
+        assert!(!is_match("NOT Status = 'Active'"));
+        assert!(is_match(
+            "NOT (Status = 'Blocked' OR Amount > 100) AND RowKey STARTSWITH 'user'"
+        ));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let expression = FilterExpression::parse(
+            "NOT a.b = 'it\\'s' or c in (1.5, true, null) and d startswith 'x'",
+        )
+        .unwrap();
+
+        let restored = FilterExpression::parse(expression.to_string().as_str()).unwrap();
+        assert_eq!(expression, restored);
+    }
+
+    #[test]
+    fn test_table_query() {
+        let mut db_table = DbTable::new_with_default_attributes("test-table".into());
+        let time_stamp = JsonTimeStamp::now();
+
+        for (row_key, amount) in [("a", 1), ("b", 20), ("c", 30), ("d", 40)] {
+            let json = format!(
+                r#"{{"PartitionKey":"pk","RowKey":"{}","Amount":{}}}"#,
+                row_key, amount
+            );
+            let db_row = Arc::new(
+                DbJsonEntity::parse_into_db_row(json.as_bytes().into(), &time_stamp).unwrap(),
+            );
+
+            #[cfg(not(feature = "master-node"))]
+            db_table.insert_or_replace_row(&db_row);
+            #[cfg(feature = "master-node")]
+            db_table.insert_or_replace_row(&db_row, None);
+        }
+
+        let filter = FilterExpression::parse("Amount >= 20").unwrap();
+
+        let row_keys: Vec<&str> = db_table
+            .query(&filter, Some(2))
+            .map(|(_, db_row)| db_row.get_row_key())
+            .collect();
+
+        assert_eq!(vec!["b", "c"], row_keys);
+    }
+}
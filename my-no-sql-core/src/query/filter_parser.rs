@@ -0,0 +1,427 @@
+use super::{CompareOperator, FieldPath, FilterExpression, FilterValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterParseError {
+    UnexpectedEnd,
+    UnexpectedChar { position: usize, value: char },
+    UnexpectedToken { position: usize, token: String },
+    UnterminatedString { position: usize },
+    InvalidNumber { position: usize, value: String },
+    NestingIsTooDeep { position: usize, max_depth: usize },
+    TooManyOperators { position: usize, max_amount: usize },
+}
+
+/// NOT and brackets are parsed recursively, so their nesting is limited to keep the stack bounded
+pub const MAX_FILTER_NESTING_DEPTH: usize = 64;
+
+/// Expression is evaluated recursively as well. Every AND, OR and NOT is a level of the tree
+pub const MAX_FILTER_OPERATORS_AMOUNT: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Number(f64),
+    Operator(CompareOperator),
+    OpenBracket,
+    CloseBracket,
+    Comma,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match self {
+            Token::Identifier(value) => value.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+}
+
+/// Grammar:
+/// ```text
+/// expression := and ( OR and )*
+/// and        := not ( AND not )*
+/// not        := NOT not | primary
+/// primary    := '(' expression ')'
+///             | field operator value
+///             | field IN '(' value ( ',' value )* ')'
+///             | field STARTSWITH string
+/// operator   := = | == | != | <> | > | >= | < | <=
+/// value      := 'string' | "string" | number | true | false | null
+/// ```
+/// Keywords are case insensitive
+pub fn parse_filter_expression(src: &str) -> Result<FilterExpression, FilterParseError> {
+    let tokens = tokenize(src)?;
+
+    let mut parser = FilterParser {
+        tokens,
+        pos: 0,
+        depth: 0,
+        operators_amount: 0,
+    };
+
+    let result = parser.parse_or()?;
+
+    if let Some((position, token)) = parser.tokens.get(parser.pos) {
+        return Err(FilterParseError::UnexpectedToken {
+            position: *position,
+            token: format!("{:?}", token),
+        });
+    }
+
+    Ok(result)
+}
+
+struct FilterParser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    depth: usize,
+    operators_amount: usize,
+}
+
+impl FilterParser {
+    /// Position of the last consumed token
+    fn get_last_position(&self) -> usize {
+        self.tokens[self.pos - 1].0
+    }
+
+    fn add_operator(&mut self) -> Result<(), FilterParseError> {
+        self.operators_amount += 1;
+
+        if self.operators_amount > MAX_FILTER_OPERATORS_AMOUNT {
+            return Err(FilterParseError::TooManyOperators {
+                position: self.get_last_position(),
+                max_amount: MAX_FILTER_OPERATORS_AMOUNT,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn enter_nested(&mut self) -> Result<(), FilterParseError> {
+        self.depth += 1;
+
+        if self.depth > MAX_FILTER_NESTING_DEPTH {
+            return Err(FilterParseError::NestingIsTooDeep {
+                position: self.get_last_position(),
+                max_depth: MAX_FILTER_NESTING_DEPTH,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn peek_token(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn next_token(&mut self) -> Result<(usize, Token), FilterParseError> {
+        let result = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(FilterParseError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(result)
+    }
+
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        if let Some(token) = self.peek_token() {
+            if token.is_keyword(keyword) {
+                self.pos += 1;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), FilterParseError> {
+        let (position, token) = self.next_token()?;
+
+        if token != expected {
+            return Err(unexpected_token(position, &token));
+        }
+
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let mut result = self.parse_and()?;
+
+        while self.next_if_keyword("or") {
+            self.add_operator()?;
+            let right = self.parse_and()?;
+            result = FilterExpression::Or(Box::new(result), Box::new(right));
+        }
+
+        Ok(result)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let mut result = self.parse_not()?;
+
+        while self.next_if_keyword("and") {
+            self.add_operator()?;
+            let right = self.parse_not()?;
+            result = FilterExpression::And(Box::new(result), Box::new(right));
+        }
+
+        Ok(result)
+    }
+
+    fn parse_not(&mut self) -> Result<FilterExpression, FilterParseError> {
+        if self.next_if_keyword("not") {
+            self.add_operator()?;
+            self.enter_nested()?;
+            let expression = self.parse_not()?;
+            self.depth -= 1;
+            return Ok(FilterExpression::Not(Box::new(expression)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterExpression, FilterParseError> {
+        let (position, token) = self.next_token()?;
+
+        let field = match token {
+            Token::OpenBracket => {
+                self.enter_nested()?;
+                let result = self.parse_or()?;
+                self.expect(Token::CloseBracket)?;
+                self.depth -= 1;
+                return Ok(result);
+            }
+            Token::Identifier(field) => FieldPath::new(field.as_str()),
+            _ => return Err(unexpected_token(position, &token)),
+        };
+
+        if self.next_if_keyword("in") {
+            self.expect(Token::OpenBracket)?;
+
+            let mut values = vec![self.parse_value()?];
+
+            loop {
+                let (position, token) = self.next_token()?;
+                match token {
+                    Token::Comma => values.push(self.parse_value()?),
+                    Token::CloseBracket => break,
+                    _ => return Err(unexpected_token(position, &token)),
+                }
+            }
+
+            return Ok(FilterExpression::In { field, values });
+        }
+
+        if self.next_if_keyword("startswith") {
+            let (position, token) = self.next_token()?;
+            return match token {
+                Token::String(prefix) => Ok(FilterExpression::StartsWith { field, prefix }),
+                _ => Err(unexpected_token(position, &token)),
+            };
+        }
+
+        let operator = match self.next_token() {
+            Ok((_, Token::Operator(operator))) => operator,
+            Ok((position, token)) => return Err(unexpected_token(position, &token)),
+            Err(err) => return Err(err),
+        };
+
+        let value = self.parse_value()?;
+
+        Ok(FilterExpression::Compare {
+            field,
+            operator,
+            value,
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<FilterValue, FilterParseError> {
+        let (position, token) = self.next_token()?;
+
+        match token {
+            Token::String(value) => Ok(FilterValue::String(value)),
+            Token::Number(value) => Ok(FilterValue::Number(value)),
+            Token::Identifier(value) => {
+                if value.eq_ignore_ascii_case("null") {
+                    Ok(FilterValue::Null)
+                } else if value.eq_ignore_ascii_case("true") {
+                    Ok(FilterValue::Bool(true))
+                } else if value.eq_ignore_ascii_case("false") {
+                    Ok(FilterValue::Bool(false))
+                } else {
+                    Err(unexpected_token(position, &Token::Identifier(value)))
+                }
+            }
+            _ => Err(unexpected_token(position, &token)),
+        }
+    }
+}
+
+fn unexpected_token(position: usize, token: &Token) -> FilterParseError {
+    FilterParseError::UnexpectedToken {
+        position,
+        token: format!("{:?}", token),
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '$'
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, FilterParseError> {
+    let mut result = Vec::new();
+    let mut chars = src.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::OpenBracket,
+            ')' => Token::CloseBracket,
+            ',' => Token::Comma,
+            '=' => {
+                chars.next_if(|(_, c)| *c == '=');
+                Token::Operator(CompareOperator::Eq)
+            }
+            '!' => match chars.next() {
+                Some((_, '=')) => Token::Operator(CompareOperator::NotEq),
+                Some((position, value)) => {
+                    return Err(FilterParseError::UnexpectedChar { position, value })
+                }
+                None => return Err(FilterParseError::UnexpectedEnd),
+            },
+            '>' => match chars.next_if(|(_, c)| *c == '=') {
+                Some(_) => Token::Operator(CompareOperator::Ge),
+                None => Token::Operator(CompareOperator::Gt),
+            },
+            '<' => match chars.next_if(|(_, c)| *c == '=' || *c == '>') {
+                Some((_, '=')) => Token::Operator(CompareOperator::Le),
+                Some(_) => Token::Operator(CompareOperator::NotEq),
+                None => Token::Operator(CompareOperator::Lt),
+            },
+            '\'' | '"' => {
+                let mut value = String::new();
+                let mut closed = false;
+
+                while let Some((_, next)) = chars.next() {
+                    match next {
+                        '\\' => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((_, escaped)) => value.push(escaped),
+                            None => break,
+                        },
+                        next if next == c => {
+                            closed = true;
+                            break;
+                        }
+                        next => value.push(next),
+                    }
+                }
+
+                if !closed {
+                    return Err(FilterParseError::UnterminatedString { position });
+                }
+
+                Token::String(value)
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let mut value = c.to_string();
+
+                while let Some((_, next)) = chars.next_if(|(_, next)| {
+                    next.is_ascii_alphanumeric() || *next == '.' || *next == '-' || *next == '+'
+                }) {
+                    value.push(next);
+                }
+
+                match value.parse::<f64>() {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => return Err(FilterParseError::InvalidNumber { position, value }),
+                }
+            }
+            c if is_identifier_char(c) => {
+                let mut value = c.to_string();
+
+                while let Some((_, next)) = chars.next_if(|(_, next)| is_identifier_char(*next)) {
+                    value.push(next);
+                }
+
+                Token::Identifier(value)
+            }
+            value => return Err(FilterParseError::UnexpectedChar { position, value }),
+        };
+
+        result.push((position, token));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::{CompareOperator, FieldPath, FilterExpression, FilterValue};
+
+    use super::{parse_filter_expression, FilterParseError};
+
+    #[test]
+    fn test_precedence() {
+        let expression = parse_filter_expression("a = 1 or b = 2 and not c = 3").unwrap();
+
+        let compare = |field: &str, value: f64| FilterExpression::Compare {
+            field: FieldPath::new(field),
+            operator: CompareOperator::Eq,
+            value: FilterValue::Number(value),
+        };
+
+        assert_eq!(
+            FilterExpression::Or(
+                Box::new(compare("a", 1.0)),
+                Box::new(FilterExpression::And(
+                    Box::new(compare("b", 2.0)),
+                    Box::new(FilterExpression::Not(Box::new(compare("c", 3.0))))
+                ))
+            ),
+            expression
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Err(FilterParseError::UnexpectedEnd),
+            parse_filter_expression("a =")
+        );
+        assert_eq!(
+            Err(FilterParseError::UnterminatedString { position: 4 }),
+            parse_filter_expression("a = 'abc")
+        );
+        assert!(parse_filter_expression("a = 1 b").is_err());
+        assert!(parse_filter_expression("a in ()").is_err());
+        assert!(parse_filter_expression("(a = 1").is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let src = format!("{}a = 1", "NOT ".repeat(100_000));
+        assert!(matches!(
+            parse_filter_expression(src.as_str()),
+            Err(FilterParseError::NestingIsTooDeep { .. })
+        ));
+
+        let src = format!("{}a = 1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(matches!(
+            parse_filter_expression(src.as_str()),
+            Err(FilterParseError::NestingIsTooDeep { .. })
+        ));
+
+        let src = vec!["a = 1"; 100_000].join(" AND ");
+        assert!(matches!(
+            parse_filter_expression(src.as_str()),
+            Err(FilterParseError::TooManyOperators { .. })
+        ));
+
+        let src = format!("{}a = 1{}", "(".repeat(64), ")".repeat(64));
+        assert!(parse_filter_expression(src.as_str()).is_ok());
+    }
+}
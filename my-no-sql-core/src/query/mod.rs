@@ -0,0 +1,4 @@
+mod filter_expression;
+pub use filter_expression::*;
+mod filter_parser;
+pub use filter_parser::*;
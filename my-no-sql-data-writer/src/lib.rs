@@ -1,9 +1,23 @@
 mod my_no_sql_data_writer;
 pub use my_no_sql_data_writer::*;
 
+mod outbox;
+pub use outbox::*;
+
+mod table_admin;
+pub use table_admin::*;
+
 mod ping_pool;
 pub use ping_pool::*;
 
+mod migration;
+pub use migration::*;
+
 lazy_static::lazy_static! {
      static ref PING_POOL: crate::PingPool =  crate::PingPool::new();
 }
+
+/// Pool which pings MyNoSql server on behalf of all the writers. Use it to configure ping interval, status callback and shutdown
+pub fn get_ping_pool() -> &'static PingPool {
+    &PING_POOL
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::DataWriterError;
+
+/// Append-only file with the partition keys which are already migrated. One json string per line,
+/// so keys with line breaks are escaped. Lines of the plain text format are read as they are
+pub struct MigrationCheckpoint {
+    file_path: String,
+    migrated: HashSet<String>,
+}
+
+impl MigrationCheckpoint {
+    pub async fn load(file_path: impl Into<String>) -> Result<Self, DataWriterError> {
+        let file_path = file_path.into();
+
+        let content = match tokio::fs::read(file_path.as_str()).await {
+            Ok(content) => content,
+            Err(err) => {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    return Err(err.into());
+                }
+
+                vec![]
+            }
+        };
+
+        let content = String::from_utf8(content)?;
+
+        let migrated = content.lines().filter_map(parse_line).collect();
+
+        Ok(Self {
+            file_path,
+            migrated,
+        })
+    }
+
+    pub fn get_file_path(&self) -> &str {
+        self.file_path.as_str()
+    }
+
+    pub fn is_migrated(&self, partition_key: &str) -> bool {
+        self.migrated.contains(partition_key)
+    }
+
+    pub fn get_migrated_amount(&self) -> usize {
+        self.migrated.len()
+    }
+
+    pub async fn mark_as_migrated(&mut self, partition_key: &str) -> Result<(), DataWriterError> {
+        let mut line = match serde_json::to_vec(partition_key) {
+            Ok(line) => line,
+            Err(err) => return Err(DataWriterError::Error(err.to_string())),
+        };
+        line.push(b'\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_path.as_str())
+            .await?;
+
+        file.write_all(line.as_slice()).await?;
+        file.sync_data().await?;
+
+        self.migrated.insert(partition_key.to_string());
+
+        Ok(())
+    }
+}
+
+/// Last line may be written partially if the process is stopped. Such partition is migrated once again
+fn parse_line(line: &str) -> Option<String> {
+    if line.is_empty() {
+        return None;
+    }
+
+    if line.starts_with('"') {
+        return serde_json::from_str(line).ok();
+    }
+
+    Some(line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MigrationCheckpoint;
+
+    #[tokio::test]
+    async fn test_keys_with_line_breaks() {
+        let file_path = std::env::temp_dir().join(format!(
+            "my-no-sql-checkpoint-{}.checkpoint",
+            rust_extensions::date_time::DateTimeAsMicroseconds::now().unix_microseconds
+        ));
+        let file_path = file_path.to_str().unwrap().to_string();
+
+        let mut checkpoint = MigrationCheckpoint::load(file_path.as_str()).await.unwrap();
+        checkpoint.mark_as_migrated("pk\n1").await.unwrap();
+        checkpoint.mark_as_migrated("pk\"2").await.unwrap();
+
+        let checkpoint = MigrationCheckpoint::load(file_path.as_str()).await.unwrap();
+        assert_eq!(2, checkpoint.get_migrated_amount());
+        assert!(checkpoint.is_migrated("pk\n1"));
+        assert!(checkpoint.is_migrated("pk\"2"));
+        assert!(!checkpoint.is_migrated("pk"));
+
+        tokio::fs::remove_file(file_path.as_str()).await.unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+/// Difference between the rows which are in the target partition now and the rows migration is going to write
+#[derive(Debug, Clone)]
+pub struct PartitionMigrationDiff {
+    pub partition_key: String,
+    pub to_insert: Vec<String>,
+    pub to_update: Vec<String>,
+    pub to_delete: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl PartitionMigrationDiff {
+    pub fn new<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+        partition_key: &str,
+        existing: &[TEntity],
+        migrated: &[TEntity],
+    ) -> Self {
+        let mut existing: BTreeMap<&str, &TEntity> = existing
+            .iter()
+            .map(|entity| (entity.get_row_key(), entity))
+            .collect();
+
+        let mut result = Self {
+            partition_key: partition_key.to_string(),
+            to_insert: Vec::new(),
+            to_update: Vec::new(),
+            to_delete: Vec::new(),
+            unchanged: 0,
+        };
+
+        for entity in migrated {
+            match existing.remove(entity.get_row_key()) {
+                Some(existing_entity) => {
+                    if crate::is_the_same_content(
+                        existing_entity.serialize_entity().as_slice(),
+                        entity.serialize_entity().as_slice(),
+                    ) {
+                        result.unchanged += 1;
+                    } else {
+                        result.to_update.push(entity.get_row_key().to_string());
+                    }
+                }
+                None => result.to_insert.push(entity.get_row_key().to_string()),
+            }
+        }
+
+        result.to_delete = existing.into_keys().map(|itm| itm.to_string()).collect();
+
+        result
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.to_insert.is_empty() || !self.to_update.is_empty() || !self.to_delete.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PartitionVerificationFail {
+    pub partition_key: String,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    pub partitions_migrated: usize,
+    /// Partitions which are marked as migrated by the checkpoint
+    pub partitions_skipped: usize,
+    pub rows_read: usize,
+    pub rows_written: usize,
+    /// Rows the transform returned None for
+    pub rows_dropped: usize,
+    /// Filled in dry run mode only
+    pub diffs: Vec<PartitionMigrationDiff>,
+    pub verification_fails: Vec<PartitionVerificationFail>,
+}
+
+impl MigrationReport {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            partitions_migrated: 0,
+            partitions_skipped: 0,
+            rows_read: 0,
+            rows_written: 0,
+            rows_dropped: 0,
+            diffs: Vec::new(),
+            verification_fails: Vec::new(),
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verification_fails.is_empty()
+    }
+}
@@ -0,0 +1,6 @@
+mod migration_checkpoint;
+mod migration_report;
+mod my_no_sql_migration;
+pub use migration_checkpoint::*;
+pub use migration_report::*;
+pub use my_no_sql_migration::*;
@@ -0,0 +1,360 @@
+use std::marker::PhantomData;
+
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use crate::{DataWriterError, MyNoSqlDataWriterApi};
+
+use super::{
+    MigrationCheckpoint, MigrationReport, PartitionMigrationDiff, PartitionVerificationFail,
+};
+
+pub const DEFAULT_MIGRATION_PAGE_SIZE: i32 = 1000;
+
+/// Moves the entities of the source table to the target table partition by partition.
+/// Each target partition is replaced with the transformed entities of the same source partition,
+/// so transform must keep the partition key. If transform returns None - entity is dropped.
+pub struct MyNoSqlMigration<'s, TFrom, TTo, TSource, TTarget, TTransform>
+where
+    TFrom: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+    TTo: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+    TSource: MyNoSqlDataWriterApi<TFrom> + Sync + Send,
+    TTarget: MyNoSqlDataWriterApi<TTo> + Sync + Send,
+    TTransform: Fn(TFrom) -> Option<TTo>,
+{
+    source: &'s TSource,
+    target: &'s TTarget,
+    transform: TTransform,
+    page_size: i32,
+    checkpoint: Option<MigrationCheckpoint>,
+    dry_run: bool,
+    verify: bool,
+    phantom: PhantomData<(TFrom, TTo)>,
+}
+
+impl<'s, TFrom, TTo, TSource, TTarget, TTransform>
+    MyNoSqlMigration<'s, TFrom, TTo, TSource, TTarget, TTransform>
+where
+    TFrom: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+    TTo: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+    TSource: MyNoSqlDataWriterApi<TFrom> + Sync + Send,
+    TTarget: MyNoSqlDataWriterApi<TTo> + Sync + Send,
+    TTransform: Fn(TFrom) -> Option<TTo>,
+{
+    pub fn new(source: &'s TSource, target: &'s TTarget, transform: TTransform) -> Self {
+        Self {
+            source,
+            target,
+            transform,
+            page_size: DEFAULT_MIGRATION_PAGE_SIZE,
+            checkpoint: None,
+            dry_run: false,
+            verify: true,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Amount of partition keys requested from the source table at once
+    pub fn with_page_size(mut self, page_size: i32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Partitions marked in the checkpoint are skipped. Migrated partitions are appended to it
+    pub fn with_checkpoint(mut self, checkpoint: MigrationCheckpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Nothing is written. Report contains the diff of every partition
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Rows amount of every written partition is read back and compared with the amount of migrated rows
+    pub fn with_verification(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn get_checkpoint(&self) -> Option<&MigrationCheckpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    pub async fn run(&mut self) -> Result<MigrationReport, DataWriterError> {
+        let mut report = MigrationReport::new(self.dry_run);
+
+        // Keys are read before migration starts, since migration may remove partitions and shift the pages
+        for partition_key in self.get_partition_keys().await? {
+            self.migrate_partition(partition_key.as_str(), &mut report)
+                .await?;
+        }
+
+        Ok(report)
+    }
+
+    async fn get_partition_keys(&self) -> Result<Vec<String>, DataWriterError> {
+        let mut result = Vec::new();
+
+        loop {
+            let page = self
+                .source
+                .get_partition_keys(Some(result.len() as i32), Some(self.page_size))
+                .await?;
+
+            let page_len = page.len();
+            result.extend(page);
+
+            if page_len < self.page_size as usize {
+                return Ok(result);
+            }
+        }
+    }
+
+    async fn migrate_partition(
+        &mut self,
+        partition_key: &str,
+        report: &mut MigrationReport,
+    ) -> Result<(), DataWriterError> {
+        if let Some(checkpoint) = self.checkpoint.as_ref() {
+            if checkpoint.is_migrated(partition_key) {
+                report.partitions_skipped += 1;
+                return Ok(());
+            }
+        }
+
+        let source_entities = self
+            .source
+            .get_by_partition_key(partition_key, None)
+            .await?
+            .unwrap_or_default();
+
+        report.rows_read += source_entities.len();
+
+        let mut migrated = Vec::with_capacity(source_entities.len());
+
+        for entity in source_entities {
+            let entity = match (self.transform)(entity) {
+                Some(entity) => entity,
+                None => {
+                    report.rows_dropped += 1;
+                    continue;
+                }
+            };
+
+            if entity.get_partition_key() != partition_key {
+                return Err(DataWriterError::Error(format!(
+                    "Migrated entity {}/{} does not belong to the source partition {}",
+                    entity.get_partition_key(),
+                    entity.get_row_key(),
+                    partition_key
+                )));
+            }
+
+            migrated.push(entity);
+        }
+
+        if self.dry_run {
+            let existing = self
+                .target
+                .get_by_partition_key(partition_key, None)
+                .await?
+                .unwrap_or_default();
+
+            report.diffs.push(PartitionMigrationDiff::new(
+                partition_key,
+                existing.as_slice(),
+                migrated.as_slice(),
+            ));
+
+            return Ok(());
+        }
+
+        self.target
+            .clean_partition_and_bulk_insert(partition_key, migrated.as_slice())
+            .await?;
+
+        report.rows_written += migrated.len();
+        report.partitions_migrated += 1;
+
+        if self.verify {
+            let actual = self
+                .target
+                .get_by_partition_key(partition_key, None)
+                .await?
+                .map(|entities| entities.len())
+                .unwrap_or(0);
+
+            if actual != migrated.len() {
+                // Partition is not checkpointed, so the next run migrates it once again
+                report.verification_fails.push(PartitionVerificationFail {
+                    partition_key: partition_key.to_string(),
+                    expected: migrated.len(),
+                    actual,
+                });
+                return Ok(());
+            }
+        }
+
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            checkpoint.mark_as_migrated(partition_key).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "mocks"))]
+mod tests {
+    use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{MigrationCheckpoint, MyNoSqlDataWriterApi, MyNoSqlDataWriterMock};
+
+    use super::MyNoSqlMigration;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct EntityV1 {
+        partition_key: String,
+        row_key: String,
+        value: i32,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct EntityV2 {
+        partition_key: String,
+        row_key: String,
+        value: String,
+    }
+
+    macro_rules! impl_entity {
+        ($entity:ty, $table_name:expr) => {
+            impl MyNoSqlEntity for $entity {
+                const TABLE_NAME: &'static str = $table_name;
+                const LAZY_DESERIALIZATION: bool = false;
+
+                fn get_partition_key(&self) -> &str {
+                    &self.partition_key
+                }
+
+                fn get_row_key(&self) -> &str {
+                    &self.row_key
+                }
+
+                fn get_time_stamp(&self) -> Timestamp {
+                    Timestamp::default()
+                }
+            }
+
+            impl MyNoSqlEntitySerializer for $entity {
+                fn serialize_entity(&self) -> Vec<u8> {
+                    my_no_sql_core::entity_serializer::serialize(self)
+                }
+
+                fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+                    my_no_sql_core::entity_serializer::deserialize(src)
+                }
+            }
+        };
+    }
+
+    impl_entity!(EntityV1, "test-v1");
+    impl_entity!(EntityV2, "test-v2");
+
+    fn transform(src: EntityV1) -> Option<EntityV2> {
+        if src.value < 0 {
+            return None;
+        }
+
+        Some(EntityV2 {
+            partition_key: src.partition_key,
+            row_key: src.row_key,
+            value: src.value.to_string(),
+        })
+    }
+
+    async fn create_source() -> MyNoSqlDataWriterMock<EntityV1> {
+        let source = MyNoSqlDataWriterMock::new();
+
+        let mut entities = Vec::new();
+        for i in 0..10 {
+            entities.push(EntityV1 {
+                partition_key: format!("pk{}", i % 3),
+                row_key: format!("rk{}", i),
+                value: if i == 9 { -1 } else { i },
+            });
+        }
+
+        source.bulk_insert_or_replace(&entities).await.unwrap();
+        source
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let source = create_source().await;
+        let target = MyNoSqlDataWriterMock::<EntityV2>::new();
+
+        let report = MyNoSqlMigration::new(&source, &target, transform)
+            .with_page_size(2)
+            .with_dry_run(true)
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(3, report.diffs.len());
+        assert_eq!(
+            9,
+            report
+                .diffs
+                .iter()
+                .map(|diff| diff.to_insert.len())
+                .sum::<usize>()
+        );
+        assert_eq!(1, report.rows_dropped);
+        assert_eq!(0, target.get_rows_amount().await);
+    }
+
+    #[tokio::test]
+    async fn test_migration_is_resumed_from_checkpoint() {
+        let source = create_source().await;
+        let target = MyNoSqlDataWriterMock::<EntityV2>::new();
+
+        let file_path = std::env::temp_dir().join(format!(
+            "my-no-sql-migration-{}.checkpoint",
+            rust_extensions::date_time::DateTimeAsMicroseconds::now().unix_microseconds
+        ));
+        let file_path = file_path.to_str().unwrap().to_string();
+
+        let report = MyNoSqlMigration::new(&source, &target, transform)
+            .with_page_size(2)
+            .with_checkpoint(MigrationCheckpoint::load(file_path.as_str()).await.unwrap())
+            .run()
+            .await
+            .unwrap();
+
+        assert!(report.is_verified());
+        assert_eq!(3, report.partitions_migrated);
+        assert_eq!(9, report.rows_written);
+        assert_eq!(9, target.get_rows_amount().await);
+
+        let entity = target
+            .get_entity("pk1", "rk4", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("4", entity.value);
+
+        let report = MyNoSqlMigration::new(&source, &target, transform)
+            .with_checkpoint(MigrationCheckpoint::load(file_path.as_str()).await.unwrap())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(3, report.partitions_skipped);
+        assert_eq!(0, report.partitions_migrated);
+
+        tokio::fs::remove_file(file_path).await.unwrap();
+    }
+}
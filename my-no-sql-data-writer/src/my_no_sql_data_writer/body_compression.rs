@@ -0,0 +1,269 @@
+use std::io::Write;
+
+use super::{DataWriterError, WriterHttpResponse, WriterRequest};
+
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 64 * 1024;
+
+/// Bodies bigger than it are compressed and decompressed on the blocking pool, so the runtime is not stalled
+pub const BLOCKING_COMPRESSION_THRESHOLD: usize = 1024 * 1024;
+
+pub(crate) const CONTENT_ENCODING_HEADER: &str = "Content-Encoding";
+const ACCEPT_ENCODING_HEADER: &str = "Accept-Encoding";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Gzip,
+    Zstd,
+}
+
+impl CompressionAlgorithm {
+    pub fn as_content_encoding(&self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Zstd => "zstd",
+        }
+    }
+
+    /// None - if body is not compressed
+    pub fn from_content_encoding(src: &str) -> Result<Option<Self>, DataWriterError> {
+        let src = src.trim();
+
+        if src.is_empty() || src.eq_ignore_ascii_case("identity") {
+            return Ok(None);
+        }
+
+        if src.eq_ignore_ascii_case("gzip") {
+            return Ok(Some(CompressionAlgorithm::Gzip));
+        }
+
+        if src.eq_ignore_ascii_case("zstd") {
+            return Ok(Some(CompressionAlgorithm::Zstd));
+        }
+
+        Err(DataWriterError::Error(format!(
+            "Unsupported Content-Encoding: {}",
+            src
+        )))
+    }
+
+    pub fn compress(&self, body: &[u8]) -> Result<Vec<u8>, DataWriterError> {
+        let result = match self {
+            CompressionAlgorithm::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()?
+            }
+            CompressionAlgorithm::Zstd => zstd::encode_all(body, 0)?,
+        };
+
+        Ok(result)
+    }
+
+    pub fn decompress(&self, body: &[u8]) -> Result<Vec<u8>, DataWriterError> {
+        let result = match self {
+            CompressionAlgorithm::Gzip => {
+                let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+                decoder.write_all(body)?;
+                decoder.finish()?
+            }
+            CompressionAlgorithm::Zstd => zstd::decode_all(body)?,
+        };
+
+        Ok(result)
+    }
+}
+
+/// Compression of the bodies of bulk operations. Bodies smaller than threshold are sent as is.
+#[derive(Debug, Clone)]
+pub struct BodyCompression {
+    pub algorithm: CompressionAlgorithm,
+    pub threshold: usize,
+}
+
+impl BodyCompression {
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Returns None if body is smaller than threshold
+    pub fn compress(&self, body: &[u8]) -> Result<Option<Vec<u8>>, DataWriterError> {
+        if body.len() < self.threshold {
+            return Ok(None);
+        }
+
+        Ok(Some(self.algorithm.compress(body)?))
+    }
+}
+
+/// Small bodies are handled inline, big ones on the blocking pool
+async fn run_blocking_if_big<TResult: Send + 'static>(
+    size: usize,
+    action: impl FnOnce() -> Result<TResult, DataWriterError> + Send + 'static,
+) -> Result<TResult, DataWriterError> {
+    if size < BLOCKING_COMPRESSION_THRESHOLD {
+        return action();
+    }
+
+    match tokio::task::spawn_blocking(action).await {
+        Ok(result) => result,
+        Err(err) => Err(DataWriterError::Error(format!(
+            "Compression task failed: {:?}",
+            err
+        ))),
+    }
+}
+
+pub(crate) async fn compress_body(
+    fl_url: WriterRequest,
+    body: Option<Vec<u8>>,
+    compression: Option<&BodyCompression>,
+) -> Result<(WriterRequest, Option<Vec<u8>>), DataWriterError> {
+    let compression = match compression {
+        Some(compression) => compression,
+        None => return Ok((fl_url, body)),
+    };
+
+    let body = match body {
+        Some(body) => body,
+        None => return Ok((fl_url, None)),
+    };
+
+    let compression = compression.clone();
+    let (body, compressed) = run_blocking_if_big(body.len(), move || {
+        let compressed = compression.compress(body.as_slice())?;
+        Ok((body, compressed.map(|itm| (itm, compression.algorithm))))
+    })
+    .await?;
+
+    match compressed {
+        Some((compressed, algorithm)) => Ok((
+            fl_url.with_header(CONTENT_ENCODING_HEADER, algorithm.as_content_encoding()),
+            Some(compressed),
+        )),
+        None => Ok((fl_url, Some(body))),
+    }
+}
+
+pub(crate) fn accept_compressed(
+    fl_url: WriterRequest,
+    compression: Option<&BodyCompression>,
+) -> WriterRequest {
+    if compression.is_none() {
+        return fl_url;
+    }
+
+    fl_url.with_header(ACCEPT_ENCODING_HEADER, "gzip, zstd")
+}
+
+/// Body is decompressed by the Content-Encoding of the response. Body without it is returned as is
+pub(crate) async fn receive_decompressed_body(
+    response: WriterHttpResponse,
+) -> Result<Vec<u8>, DataWriterError> {
+    let algorithm = match response.get_header(CONTENT_ENCODING_HEADER) {
+        Some(content_encoding) => CompressionAlgorithm::from_content_encoding(content_encoding)?,
+        None => None,
+    };
+
+    let body = response.receive_body();
+
+    match algorithm {
+        Some(algorithm) => {
+            run_blocking_if_big(body.len(), move || algorithm.decompress(body.as_slice())).await
+        }
+        None => Ok(body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        receive_decompressed_body, BodyCompression, CompressionAlgorithm, CONTENT_ENCODING_HEADER,
+    };
+    use crate::WriterHttpResponse;
+
+    fn create_body() -> Vec<u8> {
+        let mut result = Vec::new();
+        result.push(b'[');
+        for i in 0..1000 {
+            if i > 0 {
+                result.push(b',');
+            }
+            result.extend_from_slice(
+                format!(r#"{{"PartitionKey":"pk","RowKey":"{}"}}"#, i).as_bytes(),
+            );
+        }
+        result.push(b']');
+        result
+    }
+
+    fn create_response(content_encoding: Option<&str>, body: Vec<u8>) -> WriterHttpResponse {
+        WriterHttpResponse {
+            url: "http://localhost:5123/Row".to_string(),
+            status_code: 200,
+            headers: content_encoding
+                .map(|itm| vec![(CONTENT_ENCODING_HEADER.to_string(), itm.to_string())])
+                .unwrap_or_default(),
+            body,
+        }
+    }
+
+    #[test]
+    fn test_small_body_is_not_compressed() {
+        let compression = BodyCompression::new(CompressionAlgorithm::Gzip).with_threshold(1024);
+        assert!(compression.compress(b"[]").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_gzip_round_trip() {
+        let body = create_body();
+        let compression = BodyCompression::new(CompressionAlgorithm::Gzip).with_threshold(0);
+
+        let compressed = compression.compress(body.as_slice()).unwrap().unwrap();
+        assert!(compressed.len() < body.len());
+
+        let decompressed = receive_decompressed_body(create_response(Some("gzip"), compressed))
+            .await
+            .unwrap();
+        assert_eq!(body, decompressed);
+    }
+
+    #[tokio::test]
+    async fn test_zstd_round_trip() {
+        let body = create_body();
+        let compression = BodyCompression::new(CompressionAlgorithm::Zstd).with_threshold(0);
+
+        let compressed = compression.compress(body.as_slice()).unwrap().unwrap();
+        assert!(compressed.len() < body.len());
+
+        let decompressed = receive_decompressed_body(create_response(Some("zstd"), compressed))
+            .await
+            .unwrap();
+        assert_eq!(body, decompressed);
+    }
+
+    #[tokio::test]
+    async fn test_body_without_content_encoding_is_not_decompressed() {
+        let body = vec![0x1f, 0x8b, b'[', b']'];
+
+        let result = receive_decompressed_body(create_response(None, body.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(body, result);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_content_encoding() {
+        let result = receive_decompressed_body(create_response(Some("br"), create_body())).await;
+        assert!(result.is_err());
+    }
+}
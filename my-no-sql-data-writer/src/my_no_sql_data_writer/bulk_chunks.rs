@@ -0,0 +1,327 @@
+use futures::StreamExt;
+use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use super::{fl_url_factory::FlUrlFactory, DataWriterError, RequestOptions};
+
+pub const DEFAULT_BULK_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+pub const DEFAULT_BULK_CHUNK_ROWS_AMOUNT: usize = 5000;
+pub const DEFAULT_BULK_CHUNKS_CONCURRENCY: usize = 4;
+
+/// Limits of one request of the bulk operation
+#[derive(Debug, Clone)]
+pub struct BulkChunksSettings {
+    pub max_chunk_size: usize,
+    pub max_rows_per_chunk: usize,
+    pub max_concurrency: usize,
+}
+
+impl BulkChunksSettings {
+    pub fn new() -> Self {
+        Self {
+            max_chunk_size: DEFAULT_BULK_CHUNK_SIZE,
+            max_rows_per_chunk: DEFAULT_BULK_CHUNK_ROWS_AMOUNT,
+            max_concurrency: DEFAULT_BULK_CHUNKS_CONCURRENCY,
+        }
+    }
+
+    pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+        self.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    pub fn with_max_rows_per_chunk(mut self, max_rows_per_chunk: usize) -> Self {
+        self.max_rows_per_chunk = max_rows_per_chunk;
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct BulkChunkFail {
+    /// Indexes of the entities in the slice passed to the bulk operation
+    pub entities: Vec<usize>,
+    pub error: DataWriterError,
+}
+
+#[derive(Debug)]
+pub struct BulkWriteResult {
+    /// Indexes of the entities in the slice passed to the bulk operation
+    pub written: Vec<usize>,
+    pub failed: Vec<BulkChunkFail>,
+}
+
+impl BulkWriteResult {
+    pub fn has_failed(&self) -> bool {
+        !self.failed.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), DataWriterError> {
+        if self.has_failed() {
+            return Err(DataWriterError::BulkWritePartiallyFailed(self));
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct BulkChunk {
+    pub entities: Vec<usize>,
+    pub body: Vec<u8>,
+    rows_amount: usize,
+}
+
+impl BulkChunk {
+    fn new() -> Self {
+        Self {
+            entities: Vec::new(),
+            body: vec![b'['],
+            rows_amount: 0,
+        }
+    }
+
+    /// Entities with the same PartitionKey and RowKey are written as one row - the last of them
+    fn add(&mut self, indexes: &[usize], payload: &[u8]) {
+        if self.rows_amount > 0 {
+            self.body.push(b',');
+        }
+
+        self.body.extend_from_slice(payload);
+        self.entities.extend_from_slice(indexes);
+        self.rows_amount += 1;
+    }
+
+    fn size_with(&self, payload: &[u8]) -> usize {
+        // comma and closing bracket
+        self.body.len() + payload.len() + 2
+    }
+
+    fn build(mut self) -> Self {
+        self.body.push(b']');
+        self
+    }
+}
+
+/// Entities are ordered by partition key first, so rows of the same partition go to the same chunk where possible.
+/// Chunks are sent concurrently, so the row is never split between chunks: only the last entity
+/// with the same PartitionKey and RowKey is sent, which keeps last write wins
+pub(crate) fn split_into_chunks<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    entities: &[TEntity],
+    settings: &BulkChunksSettings,
+) -> Result<Vec<BulkChunk>, DataWriterError> {
+    let mut indexes: Vec<usize> = (0..entities.len()).collect();
+
+    // Sort is stable, so the entities of the same row stay in the order they were passed
+    indexes.sort_by(|a, b| {
+        let a = &entities[*a];
+        let b = &entities[*b];
+        a.get_partition_key()
+            .cmp(b.get_partition_key())
+            .then_with(|| a.get_row_key().cmp(b.get_row_key()))
+    });
+
+    let mut result = Vec::new();
+    let mut chunk = BulkChunk::new();
+
+    for row_indexes in indexes.chunk_by(|a, b| {
+        entities[*a].get_partition_key() == entities[*b].get_partition_key()
+            && entities[*a].get_row_key() == entities[*b].get_row_key()
+    }) {
+        let last_index = row_indexes[row_indexes.len() - 1];
+        let payload = super::execution::serialize_entity(&entities[last_index])?;
+
+        if chunk.rows_amount > 0
+            && (chunk.rows_amount >= settings.max_rows_per_chunk
+                || chunk.size_with(payload.as_slice()) > settings.max_chunk_size)
+        {
+            result.push(chunk.build());
+            chunk = BulkChunk::new();
+        }
+
+        chunk.add(row_indexes, payload.as_slice());
+    }
+
+    if !chunk.entities.is_empty() {
+        result.push(chunk.build());
+    }
+
+    Ok(result)
+}
+
+pub(crate) async fn bulk_insert_or_replace_chunked<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    fl_url_factory: &FlUrlFactory,
+    entities: &[TEntity],
+    sync_period: &DataSynchronizationPeriod,
+    settings: &BulkChunksSettings,
+    options: &RequestOptions,
+) -> BulkWriteResult {
+    let chunks = match fl_url_factory
+        .validate_entities(entities)
+        .and_then(|_| split_into_chunks(entities, settings))
+    {
+        Ok(chunks) => chunks,
+        Err(error) => {
+            return BulkWriteResult {
+                written: Vec::new(),
+                failed: vec![BulkChunkFail {
+                    entities: (0..entities.len()).collect(),
+                    error,
+                }],
+            };
+        }
+    };
+
+    let results: Vec<(Vec<usize>, Result<(), DataWriterError>)> = futures::stream::iter(chunks)
+        .map(|chunk| async move {
+            let result = options
+                .execute(async {
+                    let (fl_url, _) = fl_url_factory.get_writer_request().await?;
+                    super::execution::bulk_insert_or_replace_body(
+                        options.fill_fields(fl_url),
+                        TEntity::TABLE_NAME,
+                        Some(chunk.body),
+                        sync_period,
+                        fl_url_factory.get_compression(),
+                    )
+                    .await
+                })
+                .await;
+
+            (chunk.entities, result)
+        })
+        .buffer_unordered(settings.max_concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut written = Vec::new();
+    let mut failed = Vec::new();
+
+    for (chunk_entities, result) in results {
+        match result {
+            Ok(()) => written.extend(chunk_entities),
+            Err(error) => failed.push(BulkChunkFail {
+                entities: chunk_entities,
+                error,
+            }),
+        }
+    }
+
+    written.sort();
+
+    BulkWriteResult { written, failed }
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp};
+    use serde::Serialize;
+    use serde_derive::Deserialize;
+
+    use super::{split_into_chunks, BulkChunksSettings};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TestEntity {
+        partition_key: String,
+        row_key: String,
+    }
+
+    impl MyNoSqlEntity for TestEntity {
+        const TABLE_NAME: &'static str = "test";
+        const LAZY_DESERIALIZATION: bool = false;
+
+        fn get_partition_key(&self) -> &str {
+            &self.partition_key
+        }
+
+        fn get_row_key(&self) -> &str {
+            &self.row_key
+        }
+
+        fn get_time_stamp(&self) -> Timestamp {
+            Timestamp::default()
+        }
+    }
+
+    impl MyNoSqlEntitySerializer for TestEntity {
+        fn serialize_entity(&self) -> Vec<u8> {
+            my_no_sql_core::entity_serializer::serialize(self)
+        }
+
+        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+            my_no_sql_core::entity_serializer::deserialize(src)
+        }
+    }
+
+    fn create_entities() -> Vec<TestEntity> {
+        let mut result = Vec::new();
+
+        for i in 0..10 {
+            result.push(TestEntity {
+                partition_key: format!("pk{}", i % 2),
+                row_key: format!("rk{}", i),
+            });
+        }
+
+        result
+    }
+
+    #[test]
+    fn test_split_by_rows_amount() {
+        let entities = create_entities();
+        let settings = BulkChunksSettings::new().with_max_rows_per_chunk(5);
+
+        let chunks = split_into_chunks(&entities, &settings).unwrap();
+
+        assert_eq!(2, chunks.len());
+        assert_eq!(vec![0, 2, 4, 6, 8], chunks[0].entities);
+        assert_eq!(vec![1, 3, 5, 7, 9], chunks[1].entities);
+
+        for chunk in chunks {
+            let parsed: Vec<TestEntity> = serde_json::from_slice(chunk.body.as_slice()).unwrap();
+            assert_eq!(5, parsed.len());
+        }
+    }
+
+    #[test]
+    fn test_split_by_size() {
+        let entities = create_entities();
+        let settings = BulkChunksSettings::new().with_max_chunk_size(100);
+
+        let chunks = split_into_chunks(&entities, &settings).unwrap();
+
+        let mut amount = 0;
+        for chunk in chunks {
+            assert!(chunk.body.len() <= 100);
+            let parsed: Vec<TestEntity> = serde_json::from_slice(chunk.body.as_slice()).unwrap();
+            amount += parsed.len();
+        }
+
+        assert_eq!(entities.len(), amount);
+    }
+
+    #[test]
+    fn test_same_row_is_in_one_chunk() {
+        let mut entities = create_entities();
+        entities.push(TestEntity {
+            partition_key: "pk0".to_string(),
+            row_key: "rk0".to_string(),
+        });
+
+        let settings = BulkChunksSettings::new().with_max_rows_per_chunk(1);
+
+        let chunks = split_into_chunks(&entities, &settings).unwrap();
+
+        assert_eq!(10, chunks.len());
+        assert_eq!(vec![0, 10], chunks[0].entities);
+
+        let parsed: Vec<TestEntity> = serde_json::from_slice(chunks[0].body.as_slice()).unwrap();
+        assert_eq!(1, parsed.len());
+        assert_eq!("rk0", parsed[0].row_key);
+    }
+}
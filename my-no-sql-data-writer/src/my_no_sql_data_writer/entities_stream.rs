@@ -0,0 +1,541 @@
+use std::marker::PhantomData;
+
+use futures::{Stream, StreamExt};
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use super::{DataWriterError, FlUrlFactory, RequestOptions, WriterBodyStream};
+
+pub const DEFAULT_STREAM_PAGE_SIZE: usize = 1000;
+
+struct EntitiesStreamState<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer> {
+    fl_url_factory: FlUrlFactory,
+    options: RequestOptions,
+    partition_key: Option<String>,
+    page_size: usize,
+    skip: usize,
+    body: Option<WriterBodyStream>,
+    items: JsonArrayItems,
+    page_rows_amount: usize,
+    first_row_of_page: Option<Vec<u8>>,
+    first_row_of_previous_page: Option<Vec<u8>>,
+    last_page_is_loaded: bool,
+    phantom: PhantomData<TEntity>,
+}
+
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer> EntitiesStreamState<TEntity> {
+    async fn request_next_page(&mut self) -> Result<(), DataWriterError> {
+        let body = self
+            .options
+            .execute(async {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+
+                super::execution::get_rows_page(
+                    self.options.fill_fields(fl_url),
+                    TEntity::TABLE_NAME,
+                    self.partition_key.as_deref(),
+                    Some(self.skip as i32),
+                    Some(self.page_size as i32),
+                )
+                .await
+            })
+            .await?;
+
+        match body {
+            Some(body) => self.body = Some(body),
+            None => self.last_page_is_loaded = true,
+        }
+
+        Ok(())
+    }
+
+    fn complete_page(&mut self) {
+        // Page which is smaller than requested is the last one.
+        // Page which is bigger than requested means that the server ignores skip/limit and returned everything
+        self.last_page_is_loaded = self.page_rows_amount != self.page_size;
+
+        self.skip += self.page_rows_amount;
+        self.page_rows_amount = 0;
+        self.first_row_of_previous_page = self.first_row_of_page.take();
+        self.items = JsonArrayItems::default();
+    }
+
+    async fn get_next(&mut self) -> Result<Option<TEntity>, DataWriterError> {
+        if self.page_size == 0 {
+            return Err(DataWriterError::Error(
+                "Page size of the stream must be greater than 0".to_string(),
+            ));
+        }
+
+        loop {
+            if let Some((from, to)) = self.items.get_next()? {
+                let item = &self.items.buffer[from..to];
+
+                if self.page_rows_amount == 0 {
+                    // Server which ignores skip returns the same page again
+                    if self.first_row_of_previous_page.as_deref() == Some(item) {
+                        self.last_page_is_loaded = true;
+                        self.body = None;
+                        return Ok(None);
+                    }
+
+                    self.first_row_of_page = Some(item.to_vec());
+                }
+
+                self.page_rows_amount += 1;
+                return super::execution::deserialize_entity(item).map(Some);
+            }
+
+            if let Some(body) = self.body.as_mut() {
+                match body.next().await {
+                    Some(chunk) => self.items.push(chunk?.as_slice()),
+                    None => {
+                        self.body = None;
+                        self.items.check_is_completed()?;
+                        self.complete_page();
+                    }
+                }
+
+                continue;
+            }
+
+            if self.last_page_is_loaded {
+                return Ok(None);
+            }
+
+            self.request_next_page().await?;
+        }
+    }
+}
+
+/// Rows are requested from the server page by page using skip/limit.
+/// Entities are deserialized one by one while the body of the page is received,
+/// so memory is bounded by the page even if the transport delivers the body in one chunk.
+pub(crate) fn stream_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    fl_url_factory: FlUrlFactory,
+    options: RequestOptions,
+    partition_key: Option<String>,
+    page_size: usize,
+) -> impl Stream<Item = Result<TEntity, DataWriterError>> {
+    let state = EntitiesStreamState {
+        fl_url_factory,
+        options,
+        partition_key,
+        page_size,
+        skip: 0,
+        body: None,
+        items: JsonArrayItems::default(),
+        page_rows_amount: 0,
+        first_row_of_page: None,
+        first_row_of_previous_page: None,
+        last_page_is_loaded: false,
+        phantom: PhantomData,
+    };
+
+    futures::stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+
+        match state.get_next().await {
+            Ok(Some(entity)) => Some((Ok(entity), Some(state))),
+            Ok(None) => None,
+            Err(err) => Some((Err(err), None)),
+        }
+    })
+}
+
+/// Splits the json array into the items while the array is received chunk by chunk
+#[derive(Default)]
+struct JsonArrayItems {
+    buffer: Vec<u8>,
+    pos: usize,
+    item_start: Option<usize>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    array_is_started: bool,
+    array_is_finished: bool,
+}
+
+impl JsonArrayItems {
+    fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Range of the next item inside of the buffer. Range is valid until the next call
+    fn get_next(&mut self) -> Result<Option<(usize, usize)>, DataWriterError> {
+        while self.pos < self.buffer.len() {
+            let pos = self.pos;
+            let b = self.buffer[pos];
+            self.pos += 1;
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if b == b'\\' {
+                    self.escaped = true;
+                } else if b == b'"' {
+                    self.in_string = false;
+                }
+
+                continue;
+            }
+
+            if b.is_ascii_whitespace() {
+                continue;
+            }
+
+            if !self.array_is_started {
+                if b != b'[' {
+                    return Err(invalid_json_array(pos));
+                }
+
+                self.array_is_started = true;
+                continue;
+            }
+
+            if self.array_is_finished {
+                return Err(invalid_json_array(pos));
+            }
+
+            match b {
+                b',' | b']' if self.depth == 0 => {
+                    if b == b']' {
+                        self.array_is_finished = true;
+                    }
+
+                    if let Some(item_start) = self.item_start.take() {
+                        return Ok(Some((item_start, self.get_item_end(pos))));
+                    }
+
+                    if b == b',' {
+                        return Err(invalid_json_array(pos));
+                    }
+                }
+                b'{' | b'[' => {
+                    self.start_item(pos);
+                    self.depth += 1;
+                }
+                b'}' | b']' => {
+                    if self.depth == 0 {
+                        return Err(invalid_json_array(pos));
+                    }
+
+                    self.depth -= 1;
+                }
+                b'"' => {
+                    self.start_item(pos);
+                    self.in_string = true;
+                }
+                _ => {
+                    self.start_item(pos);
+                }
+            }
+        }
+
+        self.compact();
+        Ok(None)
+    }
+
+    fn check_is_completed(&self) -> Result<(), DataWriterError> {
+        // Empty body is the same as the empty array
+        if self.array_is_finished || (!self.array_is_started && self.buffer.is_empty()) {
+            return Ok(());
+        }
+
+        Err(DataWriterError::Error(
+            "Json array of the rows is not completed".to_string(),
+        ))
+    }
+
+    fn start_item(&mut self, pos: usize) {
+        if self.item_start.is_none() {
+            self.item_start = Some(pos);
+        }
+    }
+
+    fn get_item_end(&self, separator_pos: usize) -> usize {
+        let mut end = separator_pos;
+
+        while end > 0 && self.buffer[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+
+        end
+    }
+
+    /// Bytes of the items which are already returned are not kept
+    fn compact(&mut self) {
+        let keep_from = self.item_start.unwrap_or(self.pos);
+
+        if keep_from == 0 {
+            return;
+        }
+
+        self.buffer.drain(..keep_from);
+        self.pos -= keep_from;
+
+        if let Some(item_start) = self.item_start.as_mut() {
+            *item_start -= keep_from;
+        }
+    }
+}
+
+fn invalid_json_array(pos: usize) -> DataWriterError {
+    DataWriterError::Error(format!(
+        "Invalid json array of the rows at position {}",
+        pos
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures::StreamExt;
+    use my_no_sql_abstractions::{
+        DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp,
+    };
+    use serde::Serialize;
+    use serde_derive::Deserialize;
+
+    use crate::{
+        DataWriterError, FlUrlFactory, MyNoSqlWriterSettings, RequestOptions, WriterHttpRequest,
+        WriterHttpResponse, WriterTransport,
+    };
+
+    use super::JsonArrayItems;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TestEntity {
+        partition_key: String,
+        row_key: String,
+    }
+
+    impl MyNoSqlEntity for TestEntity {
+        const TABLE_NAME: &'static str = "test";
+        const LAZY_DESERIALIZATION: bool = false;
+
+        fn get_partition_key(&self) -> &str {
+            &self.partition_key
+        }
+
+        fn get_row_key(&self) -> &str {
+            &self.row_key
+        }
+
+        fn get_time_stamp(&self) -> Timestamp {
+            Timestamp::default()
+        }
+    }
+
+    impl MyNoSqlEntitySerializer for TestEntity {
+        fn serialize_entity(&self) -> Vec<u8> {
+            my_no_sql_core::entity_serializer::serialize(self)
+        }
+
+        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+            my_no_sql_core::entity_serializer::deserialize(src)
+        }
+    }
+
+    struct TestSettings;
+
+    #[async_trait::async_trait]
+    impl MyNoSqlWriterSettings for TestSettings {
+        async fn get_url(&self) -> String {
+            "http://localhost:5123".to_string()
+        }
+
+        fn get_app_name(&self) -> &'static str {
+            "test"
+        }
+
+        fn get_app_version(&self) -> &'static str {
+            "0.1.0"
+        }
+    }
+
+    /// Returns the page of the rows. Server may ignore skip and limit
+    struct PagesTransport {
+        rows_amount: usize,
+        supports_skip: bool,
+        supports_limit: bool,
+        requests_amount: Mutex<usize>,
+    }
+
+    impl PagesTransport {
+        fn get_param(request: &WriterHttpRequest, name: &str) -> Option<usize> {
+            request
+                .query_params
+                .iter()
+                .find(|(param_name, _)| param_name == name)
+                .and_then(|(_, value)| value.as_ref())
+                .map(|value| value.parse().unwrap())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl WriterTransport for PagesTransport {
+        async fn execute(
+            &self,
+            request: WriterHttpRequest,
+        ) -> Result<WriterHttpResponse, DataWriterError> {
+            *self.requests_amount.lock().unwrap() += 1;
+
+            let mut from = 0;
+            let mut to = self.rows_amount;
+
+            if self.supports_skip {
+                from = Self::get_param(&request, "skip").unwrap_or(0).min(to);
+            }
+
+            if self.supports_limit {
+                if let Some(limit) = Self::get_param(&request, "limit") {
+                    to = (from + limit).min(to);
+                }
+            }
+
+            let rows: Vec<String> = (from..to)
+                .map(|row_no| format!(r#"{{"PartitionKey":"pk","RowKey":"{}"}}"#, row_no))
+                .collect();
+
+            Ok(WriterHttpResponse {
+                url: request.get_url(),
+                status_code: 200,
+                headers: Vec::new(),
+                body: format!("[{}]", rows.join(",")).into_bytes(),
+            })
+        }
+    }
+
+    async fn read_stream(
+        supports_skip: bool,
+        supports_limit: bool,
+        page_size: usize,
+    ) -> (Vec<Result<TestEntity, DataWriterError>>, usize) {
+        let transport = Arc::new(PagesTransport {
+            rows_amount: 5,
+            supports_skip,
+            supports_limit,
+            requests_amount: Mutex::new(0),
+        });
+
+        let mut fl_url_factory = FlUrlFactory::new(
+            Arc::new(TestSettings),
+            None,
+            TestEntity::TABLE_NAME,
+            DataSynchronizationPeriod::Sec1,
+        );
+        fl_url_factory.transport = Some(transport.clone());
+
+        let result = super::stream_entities::<TestEntity>(
+            fl_url_factory,
+            RequestOptions::new(),
+            None,
+            page_size,
+        )
+        .collect::<Vec<_>>()
+        .await;
+
+        let requests_amount = *transport.requests_amount.lock().unwrap();
+        (result, requests_amount)
+    }
+
+    #[tokio::test]
+    async fn test_stream_by_pages() {
+        let (result, requests_amount) = read_stream(true, true, 2).await;
+
+        let row_keys: Vec<String> = result.into_iter().map(|itm| itm.unwrap().row_key).collect();
+        assert_eq!(vec!["0", "1", "2", "3", "4"], row_keys);
+        assert_eq!(3, requests_amount);
+    }
+
+    #[tokio::test]
+    async fn test_server_ignores_skip_and_limit() {
+        let (result, requests_amount) = read_stream(false, false, 2).await;
+
+        assert_eq!(5, result.len());
+        assert_eq!(1, requests_amount);
+    }
+
+    #[tokio::test]
+    async fn test_server_ignores_skip() {
+        let (result, requests_amount) = read_stream(false, true, 2).await;
+
+        assert_eq!(2, result.len());
+        assert_eq!(2, requests_amount);
+    }
+
+    #[tokio::test]
+    async fn test_zero_page_size_is_rejected() {
+        let (result, requests_amount) = read_stream(true, true, 0).await;
+
+        assert_eq!(1, result.len());
+        assert!(result[0].is_err());
+        assert_eq!(0, requests_amount);
+    }
+
+    fn read_items(chunks: &[&str]) -> Vec<String> {
+        let mut items = JsonArrayItems::default();
+        let mut result = Vec::new();
+
+        for chunk in chunks {
+            items.push(chunk.as_bytes());
+
+            while let Some((from, to)) = items.get_next().unwrap() {
+                result.push(String::from_utf8(items.buffer[from..to].to_vec()).unwrap());
+            }
+        }
+
+        items.check_is_completed().unwrap();
+
+        result
+    }
+
+    #[test]
+    fn test_json_array_items() {
+        let items = read_items(&[
+            r#"[{"PartitionKey":"1","RowKey":"1"}, {"PartitionKey":"1","RowKey":"2"}]"#,
+        ]);
+
+        assert_eq!(
+            vec![
+                r#"{"PartitionKey":"1","RowKey":"1"}"#,
+                r#"{"PartitionKey":"1","RowKey":"2"}"#
+            ],
+            items
+        );
+    }
+
+    #[test]
+    fn test_json_array_items_received_by_chunks() {
+        let src = r#" [ {"PartitionKey":"1","RowKey":"]\"}"} ,{"PartitionKey":"1","Items":[1,{"a":2}]} ] "#;
+
+        let chunks: Vec<String> = src.chars().map(|itm| itm.to_string()).collect();
+        let chunks: Vec<&str> = chunks.iter().map(|itm| itm.as_str()).collect();
+
+        assert_eq!(
+            vec![
+                r#"{"PartitionKey":"1","RowKey":"]\"}"}"#,
+                r#"{"PartitionKey":"1","Items":[1,{"a":2}]}"#
+            ],
+            read_items(&chunks)
+        );
+    }
+
+    #[test]
+    fn test_empty_json_array_items() {
+        assert_eq!(0, read_items(&["[]"]).len());
+        assert_eq!(0, read_items(&[]).len());
+    }
+
+    #[test]
+    fn test_not_completed_json_array() {
+        let mut items = JsonArrayItems::default();
+        items.push(r#"[{"PartitionKey":"1"}, {"Parti"#.as_bytes());
+
+        assert!(items.get_next().unwrap().is_some());
+        assert!(items.get_next().unwrap().is_none());
+        assert!(items.check_is_completed().is_err());
+    }
+}
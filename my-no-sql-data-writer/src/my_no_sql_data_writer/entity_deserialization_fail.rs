@@ -0,0 +1,72 @@
+use serde::Deserialize;
+
+use super::DataWriterError;
+
+#[derive(Deserialize)]
+struct EntityKeys {
+    #[serde(rename = "PartitionKey")]
+    partition_key: Option<String>,
+    #[serde(rename = "RowKey")]
+    row_key: Option<String>,
+}
+
+/// Row which came from the server but could not be deserialized into the entity
+#[derive(Debug, Clone)]
+pub struct EntityDeserializationFail {
+    pub partition_key: Option<String>,
+    pub row_key: Option<String>,
+    pub reason: String,
+    pub raw: Vec<u8>,
+}
+
+impl EntityDeserializationFail {
+    pub fn new(raw: &[u8], reason: String) -> Self {
+        let (partition_key, row_key) = match serde_json::from_slice::<EntityKeys>(raw) {
+            Ok(keys) => (keys.partition_key, keys.row_key),
+            Err(_) => (None, None),
+        };
+
+        Self {
+            partition_key,
+            row_key,
+            reason,
+            raw: raw.to_vec(),
+        }
+    }
+
+    pub fn into_error(self, table: &str) -> DataWriterError {
+        DataWriterError::EntityDeserializationError {
+            table: table.to_string(),
+            partition_key: self.partition_key,
+            row_key: self.row_key,
+            reason: self.reason,
+            raw: self.raw,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EntityDeserializationFail;
+
+    #[test]
+    fn test_keys_are_extracted() {
+        let raw = r#"{"PartitionKey":"pk","RowKey":"rk","Value":"not-a-number"}"#;
+
+        let fail = EntityDeserializationFail::new(raw.as_bytes(), "invalid type".to_string());
+
+        assert_eq!(Some("pk"), fail.partition_key.as_deref());
+        assert_eq!(Some("rk"), fail.row_key.as_deref());
+    }
+
+    #[test]
+    fn test_keys_of_broken_json() {
+        let raw = r#"{"PartitionKey":"pk","RowKe"#;
+
+        let fail = EntityDeserializationFail::new(raw.as_bytes(), "eof".to_string());
+
+        assert_eq!(None, fail.partition_key);
+        assert_eq!(None, fail.row_key);
+        assert_eq!(raw.as_bytes(), fail.raw.as_slice());
+    }
+}
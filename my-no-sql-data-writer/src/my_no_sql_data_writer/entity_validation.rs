@@ -0,0 +1,116 @@
+use my_no_sql_abstractions::{EntityValidationRules, MyNoSqlEntity, MyNoSqlEntitySerializer};
+
+use super::DataWriterError;
+
+/// Same checks as the node does when the entity is parsed. Entity is serialized only if size or depth is limited
+pub fn validate_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    rules: &EntityValidationRules,
+    entity: &TEntity,
+) -> Result<(), DataWriterError> {
+    rules.validate_partition_key(entity.get_partition_key())?;
+    rules.validate_row_key(entity.get_row_key())?;
+
+    if rules.requires_serialized_entity() {
+        let raw = super::execution::serialize_entity(entity)?;
+        rules.validate_size(raw.len())?;
+        rules.validate_json_depth(raw.as_slice())?;
+    }
+
+    Ok(())
+}
+
+pub fn validate_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    rules: &EntityValidationRules,
+    entities: &[TEntity],
+) -> Result<(), DataWriterError> {
+    for entity in entities {
+        validate_entity(rules, entity)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_abstractions::{
+        EntityKeyCharset, EntityValidationError, EntityValidationRules, MyNoSqlEntity,
+        MyNoSqlEntitySerializer, Timestamp,
+    };
+    use serde::Serialize;
+
+    use crate::DataWriterError;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TestEntity {
+        partition_key: String,
+        row_key: String,
+        value: String,
+    }
+
+    impl MyNoSqlEntity for TestEntity {
+        const TABLE_NAME: &'static str = "test";
+        const LAZY_DESERIALIZATION: bool = false;
+
+        fn get_partition_key(&self) -> &str {
+            &self.partition_key
+        }
+
+        fn get_row_key(&self) -> &str {
+            &self.row_key
+        }
+
+        fn get_time_stamp(&self) -> Timestamp {
+            Timestamp::default()
+        }
+    }
+
+    impl MyNoSqlEntitySerializer for TestEntity {
+        fn serialize_entity(&self) -> Vec<u8> {
+            serde_json::to_vec(self).unwrap()
+        }
+
+        fn deserialize_entity(_: &[u8]) -> Result<Self, String> {
+            Err("Not supported".to_string())
+        }
+    }
+
+    fn create_entity(row_key: &str, value: &str) -> TestEntity {
+        TestEntity {
+            partition_key: "pk".to_string(),
+            row_key: row_key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entities_are_rejected_before_sending() {
+        let rules = EntityValidationRules {
+            max_partition_key_len: Some(16),
+            max_row_key_len: Some(16),
+            key_charset: EntityKeyCharset::PrintableWithoutReserved,
+            max_entity_size: Some(64),
+            max_json_depth: None,
+        };
+
+        let entities = vec![create_entity("rk1", "a"), create_entity("rk2", "b")];
+        assert!(super::validate_entities(&rules, &entities).is_ok());
+
+        let entities = vec![create_entity("rk1", "a"), create_entity("rk/2", "b")];
+        let err = super::validate_entities(&rules, &entities).unwrap_err();
+        assert!(matches!(
+            err,
+            DataWriterError::EntityValidationError(EntityValidationError::RowKeyHasForbiddenChar {
+                value: '/',
+                ..
+            })
+        ));
+
+        let entity = create_entity("rk", "a".repeat(64).as_str());
+        let err = super::validate_entity(&rules, &entity).unwrap_err();
+        assert!(matches!(
+            err,
+            DataWriterError::EntityValidationError(EntityValidationError::EntityIsTooBig { .. })
+        ));
+    }
+}
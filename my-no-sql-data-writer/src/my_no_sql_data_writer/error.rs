@@ -1,6 +1,10 @@
 use std::{str::Utf8Error, string::FromUtf8Error};
 
 use flurl::FlUrlError;
+use my_no_sql_abstractions::EntityValidationError;
+use my_no_sql_core::query::FilterParseError;
+
+use crate::{BulkWriteResult, TableAttributesDrift};
 
 #[derive(Debug)]
 pub enum DataWriterError {
@@ -14,8 +18,63 @@ pub enum DataWriterError {
     Utf8Error(Utf8Error),
     Error(String),
     FlUrlError(FlUrlError),
+    /// Connection level error of the custom transport
+    TransportError(String),
     HyperError(flurl::hyper::Error),
     JsonParseError(my_json::json_reader::JsonParseError),
+    IoError(std::io::Error),
+    OutboxIsFull(String),
+    ServerError(String),
+    EntityDeserializationError {
+        table: String,
+        partition_key: Option<String>,
+        row_key: Option<String>,
+        reason: String,
+        raw: Vec<u8>,
+    },
+    TableAttributesMismatch(TableAttributesDrift),
+    Timeout(std::time::Duration),
+    BulkWritePartiallyFailed(BulkWriteResult),
+    /// Entity is rejected by the validation rules before it is sent to the server
+    EntityValidationError(EntityValidationError),
+    /// Filter of the query is parsed before it is sent to the server
+    InvalidFilter(FilterParseError),
+    /// Server responded 404 to the endpoint. Older servers do not have some of the endpoints
+    EndpointIsNotSupported(String),
+}
+
+impl DataWriterError {
+    pub fn is_transport_error(&self) -> bool {
+        match self {
+            DataWriterError::FlUrlError(_) => true,
+            DataWriterError::HyperError(_) => true,
+            DataWriterError::TransportError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Errors which may be gone if the same request is sent once again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DataWriterError::ServerError(_) => true,
+            DataWriterError::Timeout(_) => true,
+            _ => self.is_transport_error(),
+        }
+    }
+
+    /// Server answered that the request can not be applied. The same request gets the same answer
+    pub fn is_rejected_by_server(&self) -> bool {
+        match self {
+            DataWriterError::TableAlreadyExists(_) => true,
+            DataWriterError::TableNotFound(_) => true,
+            DataWriterError::RecordAlreadyExists(_) => true,
+            DataWriterError::RecordIsChanged(_) => true,
+            DataWriterError::RequiredEntityFieldIsMissing(_) => true,
+            DataWriterError::ServerCouldNotParseJson(_) => true,
+            DataWriterError::EndpointIsNotSupported(_) => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<flurl::hyper::Error> for DataWriterError {
@@ -47,3 +106,21 @@ impl From<FlUrlError> for DataWriterError {
         Self::FlUrlError(src)
     }
 }
+
+impl From<std::io::Error> for DataWriterError {
+    fn from(src: std::io::Error) -> Self {
+        Self::IoError(src)
+    }
+}
+
+impl From<FilterParseError> for DataWriterError {
+    fn from(src: FilterParseError) -> Self {
+        Self::InvalidFilter(src)
+    }
+}
+
+impl From<EntityValidationError> for DataWriterError {
+    fn from(src: EntityValidationError) -> Self {
+        Self::EntityValidationError(src)
+    }
+}
@@ -1,15 +1,27 @@
-use flurl::{FlUrl, FlUrlResponse};
+use std::collections::BTreeMap;
+
 use my_json::{
     json_reader::JsonArrayIterator,
     json_writer::{JsonArrayWriter, RawJsonObject},
 };
 use my_logger::LogEventCtx;
 use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_core::query::FilterExpression;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 use serde::{Deserialize, Serialize};
 
-use crate::{CreateTableParams, DataWriterError, OperationFailHttpContract, UpdateReadStatistics};
+use crate::{
+    CreateTableParams, DataWriterError, EntityDeserializationFail, LenientEntities,
+    OperationFailHttpContract, OutboxOperation, OutboxOperationType, ServerStatusContract,
+    TableDescriptionContract, UpdateReadStatistics, IDEMPOTENCY_KEY_HEADER,
+};
 
-use super::fl_url_ext::FlUrlExt;
+use super::{
+    body_compression::{accept_compressed, compress_body, receive_decompressed_body},
+    request_tracing::{record_body, record_bytes, record_status, writer_operation},
+    writer_request_ext::WriterRequestExt,
+    BodyCompression, WriterBodyStream, WriterHttpResponse, WriterRequest,
+};
 
 const API_SEGMENT: &str = "api";
 
@@ -17,186 +29,452 @@ const ROW_CONTROLLER: &str = "Row";
 const ROWS_CONTROLLER: &str = "Rows";
 const BULK_CONTROLLER: &str = "Bulk";
 const PARTITIONS_CONTROLLER: &str = "Partitions";
+const TABLES_CONTROLLER: &str = "Tables";
+const STATUS_CONTROLLER: &str = "Status";
+
+const QUERY_ACTION: &str = "Query";
+const EXPIRATION_IS_REMOVED: &str = "Null";
+
+/// Api key is sent as the header, so it never gets into the urls which are logged
+const API_KEY_HEADER: &str = "X-Api-Key";
 
 pub async fn create_table_if_not_exists(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     url: &str,
-    table_name: &'static str,
+    table_name: &str,
     params: &CreateTableParams,
     sync_period: DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
-    let fl_url = flurl
-        .append_path_segment("Tables")
-        .append_path_segment("CreateIfNotExists")
-        .append_data_sync_period(&sync_period)
-        .with_table_name_as_query_param(table_name);
+    writer_operation!(
+        "create_table_if_not_exists",
+        [table = table_name],
+        async move {
+            let fl_url = flurl
+                .append_path_segment(TABLES_CONTROLLER)
+                .append_path_segment("CreateIfNotExists")
+                .append_data_sync_period(&sync_period)
+                .with_table_name_as_query_param(table_name);
 
-    let fl_url = params.populate_params(fl_url);
+            let fl_url = params.populate_params(fl_url);
 
-    let mut response = fl_url.post(None).await?;
+            let response = fl_url.post(None).await?;
 
-    create_table_errors_handler(&mut response, "create_table_if_not_exists", url).await
+            create_table_errors_handler(&response, "create_table_if_not_exists", url).await
+        }
+    )
 }
 
 pub async fn create_table(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     url: &str,
     table_name: &str,
     params: CreateTableParams,
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
-    let fl_url = flurl
-        .append_path_segment("Tables")
-        .append_path_segment("Create")
-        .with_table_name_as_query_param(table_name)
-        .append_data_sync_period(sync_period);
+    writer_operation!("create_table", [table = table_name], async move {
+        let fl_url = flurl
+            .append_path_segment(TABLES_CONTROLLER)
+            .append_path_segment("Create")
+            .with_table_name_as_query_param(table_name)
+            .append_data_sync_period(sync_period);
+
+        let fl_url = params.populate_params(fl_url);
+
+        let response = fl_url.post(None).await?;
+
+        create_table_errors_handler(&response, "create_table", url).await
+    })
+}
+
+pub async fn get_tables(
+    flurl: WriterRequest,
+) -> Result<Vec<TableDescriptionContract>, DataWriterError> {
+    writer_operation!("get_tables", [], async move {
+        let response = flurl
+            .append_path_segment(TABLES_CONTROLLER)
+            .append_path_segment("List")
+            .get()
+            .await?;
+
+        check_error(&response).await?;
+
+        if is_ok_result(&response) {
+            return deserialize_json(response.get_body_as_slice());
+        }
+
+        return Ok(vec![]);
+    })
+}
+
+pub async fn get_server_status(
+    flurl: WriterRequest,
+) -> Result<ServerStatusContract, DataWriterError> {
+    writer_operation!("get_server_status", [], async move {
+        let response = flurl
+            .append_path_segment(API_SEGMENT)
+            .append_path_segment(STATUS_CONTROLLER)
+            .get()
+            .await?;
+
+        check_error(&response).await?;
+
+        if !is_ok_result(&response) {
+            let body = response.receive_body();
+            return Err(DataWriterError::Error(String::from_utf8(body)?));
+        }
+
+        deserialize_json(response.get_body_as_slice())
+    })
+}
+
+pub async fn update_table_attributes(
+    flurl: WriterRequest,
+    table_name: &str,
+    attributes: &CreateTableParams,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    writer_operation!(
+        "update_table_attributes",
+        [table = table_name],
+        async move {
+            // Every attribute is sent, so the limit which is not set anymore is cleared on the server
+            let fl_url = flurl
+                .append_path_segment(TABLES_CONTROLLER)
+                .append_path_segment("Update")
+                .with_table_name_as_query_param(table_name)
+                .append_data_sync_period(sync_period)
+                .append_query_param("persist", Some(attributes.persist.to_string()))
+                .append_query_param(
+                    "maxPartitionsAmount",
+                    attributes.max_partitions_amount.map(|itm| itm.to_string()),
+                )
+                .append_query_param(
+                    "maxRowsPerPartitionAmount",
+                    attributes
+                        .max_rows_per_partition_amount
+                        .map(|itm| itm.to_string()),
+                )
+                .append_query_param(
+                    "maxTableSizeBytes",
+                    attributes.max_table_size_bytes.map(|itm| itm.to_string()),
+                );
+
+            let response = fl_url.post(None).await?;
+
+            check_error(&response).await?;
+
+            if is_ok_result(&response) {
+                return Ok(());
+            }
+
+            let reason = response.receive_body();
+            let reason = String::from_utf8(reason)?;
+            return Err(DataWriterError::Error(reason));
+        }
+    )
+}
+
+pub async fn clean_table(
+    flurl: WriterRequest,
+    table_name: &str,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    writer_operation!("clean_table", [table = table_name], async move {
+        let response = flurl
+            .append_path_segment(TABLES_CONTROLLER)
+            .append_path_segment("Clean")
+            .with_table_name_as_query_param(table_name)
+            .append_data_sync_period(sync_period)
+            .put(None)
+            .await?;
+
+        check_error(&response).await?;
+
+        if is_ok_result(&response) {
+            return Ok(());
+        }
+
+        let reason = response.receive_body();
+        let reason = String::from_utf8(reason)?;
+        return Err(DataWriterError::Error(reason));
+    })
+}
+
+pub async fn delete_table(
+    flurl: WriterRequest,
+    table_name: &str,
+    api_key: &str,
+) -> Result<(), DataWriterError> {
+    writer_operation!("delete_table", [table = table_name], async move {
+        let response = flurl
+            .append_path_segment(TABLES_CONTROLLER)
+            .append_path_segment("Delete")
+            .with_table_name_as_query_param(table_name)
+            .with_header(API_KEY_HEADER, api_key)
+            .delete()
+            .await?;
+
+        if response.get_status_code() == 404 {
+            return Err(DataWriterError::TableNotFound(table_name.to_string()));
+        }
 
-    let fl_url = params.populate_params(fl_url);
+        check_error(&response).await?;
 
-    let mut response = fl_url.post(None).await?;
+        if is_ok_result(&response) {
+            return Ok(());
+        }
 
-    create_table_errors_handler(&mut response, "create_table", url).await
+        let reason = response.receive_body();
+        let reason = String::from_utf8(reason)?;
+        return Err(DataWriterError::Error(reason));
+    })
 }
 
 pub async fn insert_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     entity: &TEntity,
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
-    let response = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .append_path_segment("Insert")
-        .append_data_sync_period(sync_period)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .post(entity.serialize_entity().into())
-        .await?;
-
-    if is_ok_result(&response) {
-        return Ok(());
-    }
+    writer_operation!(
+        "insert_entity",
+        [
+            table = TEntity::TABLE_NAME,
+            partition_key = entity.get_partition_key()
+        ],
+        async move {
+            let entity = serialize_entity(entity)?;
+            record_bytes(entity.len());
+
+            let response = flurl
+                .append_path_segment(ROW_CONTROLLER)
+                .append_path_segment("Insert")
+                .append_data_sync_period(sync_period)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME)
+                .post(entity.into())
+                .await?;
+
+            check_error(&response).await?;
+
+            if is_ok_result(&response) {
+                return Ok(());
+            }
 
-    let reason = response.receive_body().await?;
-    let reason = String::from_utf8(reason)?;
-    return Err(DataWriterError::Error(reason));
+            let reason = response.receive_body();
+            let reason = String::from_utf8(reason)?;
+            return Err(DataWriterError::Error(reason));
+        }
+    )
 }
 
 pub async fn insert_or_replace_entity<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     entity: &TEntity,
     sync_period: &DataSynchronizationPeriod,
 ) -> Result<(), DataWriterError> {
-    let entity = entity.serialize_entity();
-
-    let response = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .append_path_segment("InsertOrReplace")
-        .append_data_sync_period(sync_period)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .post(entity.into())
-        .await?;
-
-    if is_ok_result(&response) {
-        return Ok(());
-    }
+    writer_operation!(
+        "insert_or_replace_entity",
+        [
+            table = TEntity::TABLE_NAME,
+            partition_key = entity.get_partition_key()
+        ],
+        async move {
+            let entity = serialize_entity(entity)?;
+            record_bytes(entity.len());
+
+            let response = flurl
+                .append_path_segment(ROW_CONTROLLER)
+                .append_path_segment("InsertOrReplace")
+                .append_data_sync_period(sync_period)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME)
+                .post(entity.into())
+                .await?;
+
+            check_error(&response).await?;
+
+            if is_ok_result(&response) {
+                return Ok(());
+            }
 
-    let body = response.receive_body().await?;
-    let body = String::from_utf8(body)?;
+            let body = response.receive_body();
+            let body = String::from_utf8(body)?;
 
-    return Err(DataWriterError::Error(body));
+            return Err(DataWriterError::Error(body));
+        }
+    )
 }
 
 pub async fn bulk_insert_or_replace<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     entities: &[TEntity],
     sync_period: &DataSynchronizationPeriod,
+    compression: Option<&BodyCompression>,
 ) -> Result<(), DataWriterError> {
     if entities.is_empty() {
         return Ok(());
     }
 
-    let response = flurl
-        .append_path_segment(BULK_CONTROLLER)
-        .append_path_segment("InsertOrReplace")
-        .append_data_sync_period(sync_period)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .post(serialize_entities_to_body(entities))
-        .await?;
+    bulk_insert_or_replace_body(
+        flurl,
+        TEntity::TABLE_NAME,
+        serialize_entities_to_body(entities)?,
+        sync_period,
+        compression,
+    )
+    .await
+}
 
-    if is_ok_result(&response) {
-        return Ok(());
-    }
+/// Body is the json array of already serialized entities
+pub async fn bulk_insert_or_replace_body(
+    flurl: WriterRequest,
+    table_name: &str,
+    body: Option<Vec<u8>>,
+    sync_period: &DataSynchronizationPeriod,
+    compression: Option<&BodyCompression>,
+) -> Result<(), DataWriterError> {
+    writer_operation!(
+        "bulk_insert_or_replace_body",
+        [table = table_name],
+        async move {
+            let (flurl, body) = compress_body(flurl, body, compression).await?;
+            record_body(&body);
+
+            let response = flurl
+                .append_path_segment(BULK_CONTROLLER)
+                .append_path_segment("InsertOrReplace")
+                .append_data_sync_period(sync_period)
+                .with_table_name_as_query_param(table_name)
+                .post(body)
+                .await?;
+
+            check_error(&response).await?;
+
+            if is_ok_result(&response) {
+                return Ok(());
+            }
 
-    let reason = response.receive_body().await?;
-    let reason = String::from_utf8(reason)?;
-    return Err(DataWriterError::Error(reason));
+            let reason = response.receive_body();
+            let reason = String::from_utf8(reason)?;
+            return Err(DataWriterError::Error(reason));
+        }
+    )
 }
 
 pub async fn get_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     partition_key: &str,
     row_key: &str,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<TEntity>, DataWriterError> {
+    writer_operation!(
+        "get_entity",
+        [table = TEntity::TABLE_NAME, partition_key = partition_key],
+        async move {
+            let body = get_row(
+                flurl,
+                TEntity::TABLE_NAME,
+                partition_key,
+                row_key,
+                update_read_statistics,
+            )
+            .await?;
+
+            match body {
+                Some(body) => Ok(Some(deserialize_entity(body.as_slice())?)),
+                None => Ok(None),
+            }
+        }
+    )
+}
+
+/// Row the way the node stores it: TimeStamp and Expires are written by the node
+pub async fn get_row(
+    flurl: WriterRequest,
+    table_name: &str,
+    partition_key: &str,
+    row_key: &str,
+    update_read_statistics: Option<&UpdateReadStatistics>,
+) -> Result<Option<Vec<u8>>, DataWriterError> {
     let mut request = flurl
         .append_path_segment(ROW_CONTROLLER)
         .with_partition_key_as_query_param(partition_key)
         .with_row_key_as_query_param(row_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME);
+        .with_table_name_as_query_param(table_name);
 
     if let Some(update_read_statistics) = update_read_statistics {
         request = update_read_statistics.fill_fields(request);
     }
 
-    let mut response = request.get().await?;
+    let response = request.get().await?;
 
     if response.get_status_code() == 404 {
         return Ok(None);
     }
 
-    check_error(&mut response).await?;
+    check_error(&response).await?;
 
     if is_ok_result(&response) {
-        let entity = TEntity::deserialize_entity(response.get_body_as_slice().await?).unwrap();
-        return Ok(Some(entity));
+        let body = response.get_body_as_slice();
+        record_bytes(body.len());
+        return Ok(Some(body.to_vec()));
     }
 
-    return Ok(None);
+    Ok(None)
 }
 
 pub async fn get_by_partition_key<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     partition_key: &str,
     update_read_statistics: Option<&UpdateReadStatistics>,
+    compression: Option<&BodyCompression>,
 ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-    let mut request = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .with_partition_key_as_query_param(partition_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME);
-
-    if let Some(update_read_statistics) = update_read_statistics {
-        request = update_read_statistics.fill_fields(request);
-    }
-
-    let mut response = request.get().await?;
-
-    if response.get_status_code() == 404 {
-        return Ok(None);
-    }
+    writer_operation!(
+        "get_by_partition_key",
+        [table = TEntity::TABLE_NAME, partition_key = partition_key],
+        async move {
+            let mut request = accept_compressed(flurl, compression)
+                .append_path_segment(ROW_CONTROLLER)
+                .with_partition_key_as_query_param(partition_key)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME);
+
+            if let Some(update_read_statistics) = update_read_statistics {
+                request = update_read_statistics.fill_fields(request);
+            }
 
-    check_error(&mut response).await?;
+            read_entities(request, deserialize_entities::<TEntity>).await
+        }
+    )
+}
 
-    if is_ok_result(&response) {
-        let entities = deserialize_entities(response.get_body_as_slice().await?)?;
-        return Ok(Some(entities));
-    }
+pub async fn get_by_partition_key_lenient<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: WriterRequest,
+    partition_key: &str,
+    update_read_statistics: Option<&UpdateReadStatistics>,
+    compression: Option<&BodyCompression>,
+) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+    writer_operation!(
+        "get_by_partition_key_lenient",
+        [table = TEntity::TABLE_NAME, partition_key = partition_key],
+        async move {
+            let mut request = accept_compressed(flurl, compression)
+                .append_path_segment(ROW_CONTROLLER)
+                .with_partition_key_as_query_param(partition_key)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME);
+
+            if let Some(update_read_statistics) = update_read_statistics {
+                request = update_read_statistics.fill_fields(request);
+            }
 
-    return Ok(None);
+            read_entities(request, deserialize_entities_lenient::<TEntity>).await
+        }
+    )
 }
 
 pub async fn get_enum_case_models_by_partition_key<
@@ -208,11 +486,11 @@ pub async fn get_enum_case_models_by_partition_key<
         + Send
         + 'static,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<Vec<TResult>>, DataWriterError> {
     let result: Option<Vec<TEntity>> =
-        get_by_partition_key(flurl, TResult::PARTITION_KEY, update_read_statistics).await?;
+        get_by_partition_key(flurl, TResult::PARTITION_KEY, update_read_statistics, None).await?;
 
     match result {
         Some(entities) => {
@@ -237,7 +515,7 @@ pub async fn get_enum_case_model<
         + Send
         + 'static,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     update_read_statistics: Option<&UpdateReadStatistics>,
 ) -> Result<Option<TResult>, DataWriterError> {
     let entity: Option<TEntity> = get_entity(
@@ -255,72 +533,88 @@ pub async fn get_enum_case_model<
 }
 
 pub async fn get_by_row_key<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     row_key: &str,
 ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-    let mut response = flurl
-        .append_path_segment(API_SEGMENT)
-        .append_path_segment(ROW_CONTROLLER)
-        .with_row_key_as_query_param(row_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .get()
-        .await?;
-
-    if response.get_status_code() == 404 {
-        return Ok(None);
-    }
-
-    check_error(&mut response).await?;
-
-    if is_ok_result(&response) {
-        let entities = deserialize_entities(response.get_body_as_slice().await?)?;
-        return Ok(Some(entities));
-    }
+    writer_operation!(
+        "get_by_row_key",
+        [table = TEntity::TABLE_NAME],
+        async move {
+            let request = flurl
+                .append_path_segment(API_SEGMENT)
+                .append_path_segment(ROW_CONTROLLER)
+                .with_row_key_as_query_param(row_key)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME);
+
+            read_entities(request, deserialize_entities::<TEntity>).await
+        }
+    )
+}
 
-    return Ok(None);
+pub async fn get_by_row_key_lenient<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
+>(
+    flurl: WriterRequest,
+    row_key: &str,
+) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+    writer_operation!(
+        "get_by_row_key_lenient",
+        [table = TEntity::TABLE_NAME],
+        async move {
+            let request = flurl
+                .append_path_segment(API_SEGMENT)
+                .append_path_segment(ROW_CONTROLLER)
+                .with_row_key_as_query_param(row_key)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME);
+
+            read_entities(request, deserialize_entities_lenient::<TEntity>).await
+        }
+    )
 }
 
 pub async fn get_partition_keys(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     table_name: &str,
     skip: Option<i32>,
     limit: Option<i32>,
 ) -> Result<Vec<String>, DataWriterError> {
-    #[derive(Serialize, Deserialize)]
-    pub struct GetPartitionsJsonResult {
-        pub amount: usize,
-        pub data: Vec<String>,
-    }
-    let mut response = flurl
-        .append_path_segment(API_SEGMENT)
-        .append_path_segment(PARTITIONS_CONTROLLER)
-        .with_table_name_as_query_param(table_name)
-        .with_skip_as_query_param(skip)
-        .with_limit_as_query_param(limit)
-        .get()
-        .await?;
-
-    if response.get_status_code() == 404 {
-        return Err(DataWriterError::TableNotFound(table_name.to_string()));
-    }
-
-    check_error(&mut response).await?;
+    writer_operation!("get_partition_keys", [table = table_name], async move {
+        #[derive(Serialize, Deserialize)]
+        pub struct GetPartitionsJsonResult {
+            pub amount: usize,
+            pub data: Vec<String>,
+        }
+        let response = flurl
+            .append_path_segment(API_SEGMENT)
+            .append_path_segment(PARTITIONS_CONTROLLER)
+            .with_table_name_as_query_param(table_name)
+            .with_skip_as_query_param(skip)
+            .with_limit_as_query_param(limit)
+            .get()
+            .await?;
+
+        if response.get_status_code() == 404 {
+            return Err(DataWriterError::TableNotFound(table_name.to_string()));
+        }
 
-    if is_ok_result(&response) {
-        let result: Result<GetPartitionsJsonResult, _> =
-            serde_json::from_slice(response.get_body_as_slice().await?);
-        match result {
-            Ok(result) => return Ok(result.data),
-            Err(err) => {
-                return Err(DataWriterError::Error(format!(
-                    "Failed to deserialize: {:?}",
-                    err
-                )))
+        check_error(&response).await?;
+
+        if is_ok_result(&response) {
+            let result: Result<GetPartitionsJsonResult, _> =
+                serde_json::from_slice(response.get_body_as_slice());
+            match result {
+                Ok(result) => return Ok(result.data),
+                Err(err) => {
+                    return Err(DataWriterError::Error(format!(
+                        "Failed to deserialize: {:?}",
+                        err
+                    )))
+                }
             }
         }
-    }
 
-    return Ok(vec![]);
+        return Ok(vec![]);
+    })
 }
 
 pub async fn delete_enum_case<
@@ -332,7 +626,7 @@ pub async fn delete_enum_case<
         + Send
         + 'static,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
 ) -> Result<Option<TResult>, DataWriterError> {
     let entity: Option<TEntity> =
         delete_row(flurl, TResult::PARTITION_KEY, TResult::ROW_KEY).await?;
@@ -352,7 +646,7 @@ pub async fn delete_enum_case_with_row_key<
         + Send
         + 'static,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     row_key: &str,
 ) -> Result<Option<TResult>, DataWriterError> {
     let entity: Option<TEntity> = delete_row(flurl, TResult::PARTITION_KEY, row_key).await?;
@@ -364,152 +658,546 @@ pub async fn delete_enum_case_with_row_key<
 }
 
 pub async fn delete_row<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     partition_key: &str,
     row_key: &str,
 ) -> Result<Option<TEntity>, DataWriterError> {
-    let mut response = flurl
-        .append_path_segment(API_SEGMENT)
-        .append_path_segment(ROW_CONTROLLER)
-        .with_partition_key_as_query_param(partition_key)
-        .with_row_key_as_query_param(row_key)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .delete()
-        .await?;
-
-    if response.get_status_code() == 404 {
-        return Ok(None);
-    }
+    writer_operation!(
+        "delete_row",
+        [table = TEntity::TABLE_NAME, partition_key = partition_key],
+        async move {
+            let response = flurl
+                .append_path_segment(API_SEGMENT)
+                .append_path_segment(ROW_CONTROLLER)
+                .with_partition_key_as_query_param(partition_key)
+                .with_row_key_as_query_param(row_key)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME)
+                .delete()
+                .await?;
+
+            if response.get_status_code() == 404 {
+                return Ok(None);
+            }
 
-    check_error(&mut response).await?;
+            check_error(&response).await?;
 
-    if response.get_status_code() == 200 {
-        let entity = TEntity::deserialize_entity(response.get_body_as_slice().await?).unwrap();
-        return Ok(Some(entity));
-    }
+            if response.get_status_code() == 200 {
+                let body = response.get_body_as_slice();
+                record_bytes(body.len());
+                let entity = deserialize_entity(body)?;
+                return Ok(Some(entity));
+            }
 
-    return Ok(None);
+            return Ok(None);
+        }
+    )
 }
 
 pub async fn delete_partitions(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     table_name: &str,
     partition_keys: &[&str],
 ) -> Result<(), DataWriterError> {
-    let mut response = flurl
-        .append_path_segment(ROWS_CONTROLLER)
-        .with_table_name_as_query_param(table_name)
-        .with_partition_keys_as_query_param(partition_keys)
-        .delete()
-        .await?;
+    writer_operation!("delete_partitions", [table = table_name], async move {
+        let response = flurl
+            .append_path_segment(ROWS_CONTROLLER)
+            .with_table_name_as_query_param(table_name)
+            .with_partition_keys_as_query_param(partition_keys)
+            .delete()
+            .await?;
+
+        if response.get_status_code() == 404 {
+            return Ok(());
+        }
+
+        check_error(&response).await?;
 
-    if response.get_status_code() == 404 {
         return Ok(());
-    }
+    })
+}
+
+pub async fn bulk_delete_rows(
+    flurl: WriterRequest,
+    table_name: &str,
+    rows: &[(&str, &str)],
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    writer_operation!("bulk_delete_rows", [table = table_name], async move {
+        if rows.is_empty() {
+            return Ok(());
+        }
 
-    check_error(&mut response).await?;
+        let body = serialize_rows_to_delete(rows)?;
+        record_bytes(body.len());
+
+        let response = flurl
+            .append_path_segment(BULK_CONTROLLER)
+            .append_path_segment("Delete")
+            .append_data_sync_period(sync_period)
+            .with_table_name_as_query_param(table_name)
+            .post(body.into())
+            .await?;
+
+        // Rows must not be reported as deleted if the server does not have the endpoint
+        if response.get_status_code() == 404 {
+            return Err(DataWriterError::EndpointIsNotSupported(format!(
+                "{}/Delete",
+                BULK_CONTROLLER
+            )));
+        }
+
+        check_error(&response).await?;
+
+        if is_ok_result(&response) {
+            return Ok(());
+        }
 
-    return Ok(());
+        let reason = response.receive_body();
+        let reason = String::from_utf8(reason)?;
+        return Err(DataWriterError::Error(reason));
+    })
+}
+
+pub async fn set_rows_expiration(
+    flurl: WriterRequest,
+    table_name: &str,
+    partition_key: &str,
+    row_keys: &[&str],
+    expires: Option<DateTimeAsMicroseconds>,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    writer_operation!(
+        "set_rows_expiration",
+        [table = table_name, partition_key = partition_key],
+        async move {
+            if row_keys.is_empty() {
+                return Ok(());
+            }
+
+            let body = serialize_json(row_keys)?;
+
+            let response = flurl
+                .append_path_segment(ROWS_CONTROLLER)
+                .append_path_segment("SetExpiration")
+                .append_data_sync_period(sync_period)
+                .with_table_name_as_query_param(table_name)
+                .with_partition_key_as_query_param(partition_key)
+                .append_query_param("expires", Some(get_expires_as_str(expires)))
+                .post(body.into())
+                .await?;
+
+            check_error(&response).await?;
+
+            if is_ok_result(&response) {
+                return Ok(());
+            }
+
+            let reason = response.receive_body();
+            let reason = String::from_utf8(reason)?;
+            return Err(DataWriterError::Error(reason));
+        }
+    )
+}
+
+pub async fn set_partitions_expiration(
+    flurl: WriterRequest,
+    table_name: &str,
+    partition_keys: &[&str],
+    expires: Option<DateTimeAsMicroseconds>,
+    sync_period: &DataSynchronizationPeriod,
+) -> Result<(), DataWriterError> {
+    writer_operation!(
+        "set_partitions_expiration",
+        [table = table_name],
+        async move {
+            if partition_keys.is_empty() {
+                return Ok(());
+            }
+
+            let body = serialize_json(partition_keys)?;
+
+            let response = flurl
+                .append_path_segment(PARTITIONS_CONTROLLER)
+                .append_path_segment("SetExpiration")
+                .append_data_sync_period(sync_period)
+                .with_table_name_as_query_param(table_name)
+                .append_query_param("expires", Some(get_expires_as_str(expires)))
+                .post(body.into())
+                .await?;
+
+            check_error(&response).await?;
+
+            if is_ok_result(&response) {
+                return Ok(());
+            }
+
+            let reason = response.receive_body();
+            let reason = String::from_utf8(reason)?;
+            return Err(DataWriterError::Error(reason));
+        }
+    )
 }
 
 pub async fn get_all<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
-    flurl: FlUrl,
+    flurl: WriterRequest,
+    compression: Option<&BodyCompression>,
 ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-    let mut response = flurl
-        .append_path_segment(ROW_CONTROLLER)
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .get()
-        .await?;
+    writer_operation!("get_all", [table = TEntity::TABLE_NAME], async move {
+        let request = accept_compressed(flurl, compression)
+            .append_path_segment(ROW_CONTROLLER)
+            .with_table_name_as_query_param(TEntity::TABLE_NAME);
 
-    if response.get_status_code() == 404 {
-        return Ok(None);
-    }
+        read_entities(request, deserialize_entities::<TEntity>).await
+    })
+}
 
-    check_error(&mut response).await?;
+pub async fn query<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: WriterRequest,
+    partition_key: Option<&str>,
+    filter: &FilterExpression,
+    limit: Option<i32>,
+    compression: Option<&BodyCompression>,
+) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+    writer_operation!(
+        "query",
+        [
+            table = TEntity::TABLE_NAME,
+            partition_key = partition_key,
+            filter = tracing::field::display(filter)
+        ],
+        async move {
+            let mut request = accept_compressed(flurl, compression)
+                .append_path_segment(ROW_CONTROLLER)
+                .append_path_segment(QUERY_ACTION)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME);
+
+            if let Some(partition_key) = partition_key {
+                request = request.with_partition_key_as_query_param(partition_key);
+            }
 
-    if is_ok_result(&response) {
-        let entities = deserialize_entities(response.get_body_as_slice().await?)?;
-        return Ok(Some(entities));
-    }
+            let response = request
+                .with_filter_as_query_param(filter)
+                .with_limit_as_query_param(limit)
+                .get()
+                .await?;
+
+            // Unfiltered rows must never be returned as the result of the query
+            if response.get_status_code() == 404 {
+                return Err(DataWriterError::EndpointIsNotSupported(format!(
+                    "{}/{}",
+                    ROW_CONTROLLER, QUERY_ACTION
+                )));
+            }
+
+            check_error(&response).await?;
 
-    return Ok(None);
+            if is_ok_result(&response) {
+                record_bytes(response.get_body_as_slice().len());
+                let body = receive_decompressed_body(response).await?;
+                let entities = deserialize_entities(body.as_slice())?;
+                return Ok(Some(entities));
+            }
+
+            return Ok(None);
+        }
+    )
+}
+
+pub async fn get_all_lenient<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>(
+    flurl: WriterRequest,
+    compression: Option<&BodyCompression>,
+) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+    writer_operation!(
+        "get_all_lenient",
+        [table = TEntity::TABLE_NAME],
+        async move {
+            let request = accept_compressed(flurl, compression)
+                .append_path_segment(ROW_CONTROLLER)
+                .with_table_name_as_query_param(TEntity::TABLE_NAME);
+
+            read_entities(request, deserialize_entities_lenient::<TEntity>).await
+        }
+    )
+}
+
+/// Returns the JSON array of the rows as is. Rows are not deserialized here
+pub async fn get_rows_page(
+    flurl: WriterRequest,
+    table_name: &str,
+    partition_key: Option<&str>,
+    skip: Option<i32>,
+    limit: Option<i32>,
+) -> Result<Option<WriterBodyStream>, DataWriterError> {
+    writer_operation!(
+        "get_rows_page",
+        [
+            table = table_name,
+            partition_key = partition_key.unwrap_or_default()
+        ],
+        async move {
+            let mut request = flurl
+                .append_path_segment(ROW_CONTROLLER)
+                .with_table_name_as_query_param(table_name);
+
+            if let Some(partition_key) = partition_key {
+                request = request.with_partition_key_as_query_param(partition_key);
+            }
+
+            let response = request
+                .with_skip_as_query_param(skip)
+                .with_limit_as_query_param(limit)
+                .get_streamed()
+                .await?;
+
+            let status_code = response.get_status_code();
+
+            if status_code == 404 {
+                return Ok(None);
+            }
+
+            if (200..300).contains(&status_code) {
+                record_status(status_code);
+                return Ok(Some(response.body));
+            }
+
+            let response = response.receive_body().await?;
+            check_error(&response).await?;
+
+            return Ok(None);
+        }
+    )
 }
 
 pub async fn clean_table_and_bulk_insert<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     entities: &[TEntity],
     sync_period: &DataSynchronizationPeriod,
+    compression: Option<&BodyCompression>,
 ) -> Result<(), DataWriterError> {
-    let mut response = flurl
-        .append_path_segment(BULK_CONTROLLER)
-        .append_path_segment("CleanAndBulkInsert")
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .append_data_sync_period(sync_period)
-        .post(serialize_entities_to_body(entities))
-        .await?;
-
-    check_error(&mut response).await?;
-
-    return Ok(());
+    writer_operation!(
+        "clean_table_and_bulk_insert",
+        [table = TEntity::TABLE_NAME],
+        async move {
+            let (flurl, body) =
+                compress_body(flurl, serialize_entities_to_body(entities)?, compression).await?;
+            record_body(&body);
+
+            let response = flurl
+                .append_path_segment(BULK_CONTROLLER)
+                .append_path_segment("CleanAndBulkInsert")
+                .with_table_name_as_query_param(TEntity::TABLE_NAME)
+                .append_data_sync_period(sync_period)
+                .post(body)
+                .await?;
+
+            check_error(&response).await?;
+
+            return Ok(());
+        }
+    )
 }
 
 pub async fn clean_partition_and_bulk_insert<
     TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send,
 >(
-    flurl: FlUrl,
+    flurl: WriterRequest,
     partition_key: &str,
     entities: &[TEntity],
     sync_period: &DataSynchronizationPeriod,
+    compression: Option<&BodyCompression>,
 ) -> Result<(), DataWriterError> {
-    let mut response = flurl
-        .append_path_segment(BULK_CONTROLLER)
-        .append_path_segment("CleanAndBulkInsert")
-        .with_table_name_as_query_param(TEntity::TABLE_NAME)
-        .append_data_sync_period(sync_period)
-        .with_partition_key_as_query_param(partition_key)
-        .post(serialize_entities_to_body(entities))
-        .await?;
+    writer_operation!(
+        "clean_partition_and_bulk_insert",
+        [table = TEntity::TABLE_NAME, partition_key = partition_key],
+        async move {
+            let (flurl, body) =
+                compress_body(flurl, serialize_entities_to_body(entities)?, compression).await?;
+            record_body(&body);
+
+            let response = flurl
+                .append_path_segment(BULK_CONTROLLER)
+                .append_path_segment("CleanAndBulkInsert")
+                .with_table_name_as_query_param(TEntity::TABLE_NAME)
+                .append_data_sync_period(sync_period)
+                .with_partition_key_as_query_param(partition_key)
+                .post(body)
+                .await?;
+
+            check_error(&response).await?;
+
+            return Ok(());
+        }
+    )
+}
+
+pub async fn execute_outbox_operation(
+    flurl: WriterRequest,
+    operation: &OutboxOperation,
+    compression: Option<&BodyCompression>,
+) -> Result<Option<Vec<u8>>, DataWriterError> {
+    writer_operation!(
+        "execute_outbox_operation",
+        [
+            table = operation.table_name.as_str(),
+            partition_key = operation.partition_key.as_deref().unwrap_or_default()
+        ],
+        async move {
+            let flurl = flurl
+                .with_header(IDEMPOTENCY_KEY_HEADER, operation.idempotency_key.as_str())
+                .with_table_name_as_query_param(operation.table_name.as_str());
+
+            let sync_period = operation.get_sync_period();
+
+            if let Some(payload) = operation.payload.as_ref() {
+                record_bytes(payload.len());
+            }
+
+            let response = match operation.operation {
+                OutboxOperationType::Insert => {
+                    flurl
+                        .append_path_segment(ROW_CONTROLLER)
+                        .append_path_segment("Insert")
+                        .append_data_sync_period(&sync_period)
+                        .post(operation.get_payload())
+                        .await?
+                }
+                OutboxOperationType::InsertOrReplace => {
+                    flurl
+                        .append_path_segment(ROW_CONTROLLER)
+                        .append_path_segment("InsertOrReplace")
+                        .append_data_sync_period(&sync_period)
+                        .post(operation.get_payload())
+                        .await?
+                }
+                OutboxOperationType::BulkInsertOrReplace => {
+                    let (flurl, body) =
+                        compress_body(flurl, operation.get_payload(), compression).await?;
+
+                    flurl
+                        .append_path_segment(BULK_CONTROLLER)
+                        .append_path_segment("InsertOrReplace")
+                        .append_data_sync_period(&sync_period)
+                        .post(body)
+                        .await?
+                }
+                OutboxOperationType::DeleteRow => {
+                    let partition_key = operation.partition_key.as_deref().unwrap_or_default();
+                    let row_key = operation.row_key.as_deref().unwrap_or_default();
+
+                    flurl
+                        .append_path_segment(API_SEGMENT)
+                        .append_path_segment(ROW_CONTROLLER)
+                        .with_partition_key_as_query_param(partition_key)
+                        .with_row_key_as_query_param(row_key)
+                        .delete()
+                        .await?
+                }
+            };
+
+            if response.get_status_code() == 404
+                && operation.operation == OutboxOperationType::DeleteRow
+            {
+                return Ok(None);
+            }
+
+            check_error(&response).await?;
+
+            if !is_ok_result(&response) {
+                let reason = response.receive_body();
+                let reason = String::from_utf8(reason)?;
+                return Err(DataWriterError::Error(reason));
+            }
+
+            if operation.operation == OutboxOperationType::DeleteRow
+                && response.get_status_code() == 200
+            {
+                let body = response.receive_body();
+                return Ok(Some(body));
+            }
+
+            Ok(None)
+        }
+    )
+}
+
+/// Rows are grouped by partition key: {"pk":["rk1","rk2"]}
+fn serialize_rows_to_delete(rows: &[(&str, &str)]) -> Result<Vec<u8>, DataWriterError> {
+    let mut grouped: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
 
-    check_error(&mut response).await?;
+    for (partition_key, row_key) in rows {
+        grouped.entry(*partition_key).or_default().push(*row_key);
+    }
+
+    serialize_json(&grouped)
+}
 
-    return Ok(());
+fn serialize_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, DataWriterError> {
+    serde_json::to_vec(value).map_err(|err| DataWriterError::Error(format!("{:?}", err)))
+}
+
+/// Server removes the expiration if the value is "Null". It is the same contract
+/// the setPartitionExpirationTime and setRowsExpirationTime headers of UpdateReadStatistics have
+fn get_expires_as_str(expires: Option<DateTimeAsMicroseconds>) -> String {
+    match expires {
+        Some(expires) => expires.to_rfc3339(),
+        None => EXPIRATION_IS_REMOVED.to_string(),
+    }
 }
 
-fn is_ok_result(response: &FlUrlResponse) -> bool {
+fn is_ok_result(response: &WriterHttpResponse) -> bool {
     response.get_status_code() >= 200 && response.get_status_code() < 300
 }
 
-fn serialize_entities_to_body<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+pub(crate) fn serialize_entities_to_body<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
     entities: &[TEntity],
-) -> Option<Vec<u8>> {
+) -> Result<Option<Vec<u8>>, DataWriterError> {
     if entities.len() == 0 {
-        return Some(vec![b'[', b']']);
+        return Ok(Some(vec![b'[', b']']));
     }
 
     let mut json_array_writer = JsonArrayWriter::new();
 
     for entity in entities {
-        let payload = entity.serialize_entity();
+        let payload = serialize_entity(entity)?;
         let payload: RawJsonObject = payload.into();
         json_array_writer.write(payload);
     }
 
-    Some(json_array_writer.build().into_bytes())
+    Ok(Some(json_array_writer.build().into_bytes()))
 }
 
-async fn check_error(response: &mut FlUrlResponse) -> Result<(), DataWriterError> {
+/// Encodings of the entity can fail, so the error is returned instead of panic
+pub(crate) fn serialize_entity<TEntity: MyNoSqlEntitySerializer>(
+    entity: &TEntity,
+) -> Result<Vec<u8>, DataWriterError> {
+    entity
+        .try_serialize_entity()
+        .map_err(DataWriterError::Error)
+}
+
+async fn check_error(response: &WriterHttpResponse) -> Result<(), DataWriterError> {
+    record_status(response.get_status_code());
+
     let result = match response.get_status_code() {
         400 => Err(deserialize_error(response).await?),
 
         409 => Err(DataWriterError::TableNotFound("".to_string())),
+        status_code @ 500..=599 => {
+            let body = response.get_body_as_slice();
+            Err(DataWriterError::ServerError(format!(
+                "Status code: {}. {}",
+                status_code,
+                String::from_utf8_lossy(body)
+            )))
+        }
         _ => Ok(()),
     };
 
     if let Err(err) = &result {
         my_logger::LOGGER.write_error(
-            format!("FlUrlRequest to {}", response.url.to_string()),
+            format!("MyNoSql request to {}", response.url),
             format!("{:?}", err),
             None.into(),
         );
@@ -519,9 +1207,9 @@ async fn check_error(response: &mut FlUrlResponse) -> Result<(), DataWriterError
 }
 
 async fn deserialize_error(
-    response: &mut FlUrlResponse,
+    response: &WriterHttpResponse,
 ) -> Result<DataWriterError, DataWriterError> {
-    let body = response.get_body_as_slice().await?;
+    let body = response.get_body_as_slice();
 
     let body_as_str = std::str::from_utf8(body)?;
 
@@ -547,61 +1235,108 @@ async fn deserialize_error(
     Ok(result)
 }
 
-fn deserialize_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
-    src: &[u8],
-) -> Result<Vec<TEntity>, DataWriterError> {
-    let mut result = Vec::new();
+/// Shared by the strict and the lenient reads, which differ only by the way rows are deserialized
+async fn read_entities<TResult>(
+    request: WriterRequest,
+    deserialize: fn(&[u8]) -> Result<TResult, DataWriterError>,
+) -> Result<Option<TResult>, DataWriterError> {
+    let response = request.get().await?;
 
-    let json_array_iterator = JsonArrayIterator::new(src);
+    if response.get_status_code() == 404 {
+        return Ok(None);
+    }
 
-    if let Err(err) = &json_array_iterator {
-        panic!(
-            "Can not deserialize entities for table: {}. Err: {:?}",
-            TEntity::TABLE_NAME,
-            err
-        );
+    check_error(&response).await?;
+
+    if is_ok_result(&response) {
+        record_bytes(response.get_body_as_slice().len());
+        let body = receive_decompressed_body(response).await?;
+        return Ok(Some(deserialize(body.as_slice())?));
     }
 
-    let json_array_iterator = json_array_iterator.unwrap();
+    Ok(None)
+}
 
-    while let Some(item) = json_array_iterator.get_next() {
-        let itm = item.unwrap();
+fn deserialize_json<TResult: serde::de::DeserializeOwned>(
+    src: &[u8],
+) -> Result<TResult, DataWriterError> {
+    match serde_json::from_slice(src) {
+        Ok(result) => Ok(result),
+        Err(err) => Err(DataWriterError::Error(format!(
+            "Failed to deserialize: {:?}",
+            err
+        ))),
+    }
+}
 
-        match TEntity::deserialize_entity(itm.as_bytes()) {
-            Ok(entity) => {
-                result.push(entity);
-            }
-            Err(err) => {
-                println!(
-                    "Table: '{}', Entity: {:?}",
-                    TEntity::TABLE_NAME,
-                    std::str::from_utf8(itm.as_bytes())
-                );
-                panic!("Can not deserialize entity: {}", err);
-            }
+pub(crate) fn deserialize_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    src: &[u8],
+) -> Result<TEntity, DataWriterError> {
+    match TEntity::deserialize_entity(src) {
+        Ok(entity) => Ok(entity),
+        Err(reason) => {
+            Err(EntityDeserializationFail::new(src, reason).into_error(TEntity::TABLE_NAME))
         }
     }
-    Ok(result)
+}
 
-    /*
+fn deserialize_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    src: &[u8],
+) -> Result<Vec<TEntity>, DataWriterError> {
     let mut result = Vec::new();
 
+    let json_array_iterator = JsonArrayIterator::new(src)?;
+
+    while let Some(item) = json_array_iterator.get_next() {
+        let item = item?;
+        result.push(deserialize_entity(item.as_bytes())?);
+    }
+
+    Ok(result)
+}
+
+fn deserialize_entities_lenient<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    src: &[u8],
+) -> Result<LenientEntities<TEntity>, DataWriterError> {
+    let mut result = LenientEntities {
+        entities: Vec::new(),
+        failed: Vec::new(),
+    };
 
+    let json_array_iterator = JsonArrayIterator::new(src)?;
 
-    for itm in JsonArrayIterator::new(src) {
-        let itm = itm.unwrap();
+    while let Some(item) = json_array_iterator.get_next() {
+        let item = item?;
+
+        match TEntity::deserialize_entity(item.as_bytes()) {
+            Ok(entity) => result.entities.push(entity),
+            Err(reason) => {
+                let fail = EntityDeserializationFail::new(item.as_bytes(), reason);
+
+                my_logger::LOGGER.write_error(
+                    "deserialize_entities_lenient",
+                    fail.reason.clone(),
+                    LogEventCtx::new()
+                        .add("TableName", TEntity::TABLE_NAME)
+                        .add("PartitionKey", fail.partition_key.as_deref().unwrap_or(""))
+                        .add("RowKey", fail.row_key.as_deref().unwrap_or("")),
+                );
 
-        result.push(TEntity::deserialize_entity(itm).unwrap());
+                result.failed.push(fail);
+            }
+        }
     }
+
     Ok(result)
-     */
 }
 
 async fn create_table_errors_handler(
-    response: &mut FlUrlResponse,
+    response: &WriterHttpResponse,
     process_name: &'static str,
     url: &str,
 ) -> Result<(), DataWriterError> {
+    record_status(response.get_status_code());
+
     if is_ok_result(response) {
         return Ok(());
     }
@@ -619,10 +1354,58 @@ async fn create_table_errors_handler(
 
 #[cfg(test)]
 mod tests {
-    use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp};
+    use std::sync::{Arc, Mutex};
+
+    use my_no_sql_abstractions::{
+        DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp,
+    };
+    use my_no_sql_core::query::FilterExpression;
     use serde::Serialize;
     use serde_derive::Deserialize;
 
+    use crate::{
+        CreateTableParams, DataWriterError, WriterHttpRequest, WriterHttpResponse, WriterRequest,
+        WriterRequestMethod, WriterTransport,
+    };
+
+    struct RecordingTransport {
+        requests: Mutex<Vec<WriterHttpRequest>>,
+        status_code: u16,
+        body: Vec<u8>,
+    }
+
+    impl RecordingTransport {
+        fn new(status_code: u16, body: &[u8]) -> Arc<Self> {
+            Arc::new(Self {
+                requests: Mutex::new(Vec::new()),
+                status_code,
+                body: body.to_vec(),
+            })
+        }
+
+        fn get_requests(&self) -> Vec<WriterHttpRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl WriterTransport for RecordingTransport {
+        async fn execute(
+            &self,
+            request: WriterHttpRequest,
+        ) -> Result<WriterHttpResponse, DataWriterError> {
+            let url = request.get_url();
+            self.requests.lock().unwrap().push(request);
+
+            Ok(WriterHttpResponse {
+                url,
+                status_code: self.status_code,
+                headers: Vec::new(),
+                body: self.body.clone(),
+            })
+        }
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     #[serde(rename_all = "PascalCase")]
     struct TestEntity {
@@ -678,8 +1461,286 @@ mod tests {
             },
         ];
 
-        let as_json = super::serialize_entities_to_body(&entities).unwrap();
+        let as_json = super::serialize_entities_to_body(&entities)
+            .unwrap()
+            .unwrap();
 
         println!("{}", std::str::from_utf8(&as_json).unwrap());
     }
+
+    #[test]
+    fn test_rows_to_delete_are_grouped_by_partition_key() {
+        let rows = [("pk1", "rk1"), ("pk2", "rk1"), ("pk1", "rk2")];
+
+        let result = super::serialize_rows_to_delete(&rows).unwrap();
+
+        assert_eq!(
+            r#"{"pk1":["rk1","rk2"],"pk2":["rk1"]}"#,
+            std::str::from_utf8(&result).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_or_replace_is_sent_through_transport() {
+        let transport = RecordingTransport::new(200, b"");
+        let entity = TestEntity {
+            partition_key: "pk".to_string(),
+            row_key: "rk".to_string(),
+        };
+
+        super::insert_or_replace_entity(
+            WriterRequest::new("http://localhost:5123", transport.clone()),
+            &entity,
+            &DataSynchronizationPeriod::Sec1,
+        )
+        .await
+        .unwrap();
+
+        let requests = transport.get_requests();
+        assert_eq!(1, requests.len());
+        assert_eq!(WriterRequestMethod::Post, requests[0].method);
+        assert_eq!(
+            "http://localhost:5123/Row/InsertOrReplace?syncPeriod=1&tableName=test",
+            requests[0].get_url()
+        );
+        assert_eq!(entity.serialize_entity(), requests[0].body.clone().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_not_found_entity_is_none() {
+        let transport = RecordingTransport::new(404, b"");
+
+        let result: Option<TestEntity> = super::get_entity(
+            WriterRequest::new("http://localhost:5123", transport.clone()),
+            "pk",
+            "rk",
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.is_none());
+        assert_eq!(WriterRequestMethod::Get, transport.get_requests()[0].method);
+    }
+
+    #[tokio::test]
+    async fn test_strict_and_lenient_reads_of_broken_row() {
+        let body = br#"[{"PartitionKey":"pk","RowKey":"rk"},{"PartitionKey":"pk"}]"#;
+
+        let transport = RecordingTransport::new(200, body);
+        let result = super::get_all::<TestEntity>(
+            WriterRequest::new("http://localhost:5123", transport.clone()),
+            None,
+        )
+        .await;
+
+        match result {
+            Err(DataWriterError::EntityDeserializationError {
+                partition_key,
+                row_key,
+                ..
+            }) => {
+                assert_eq!(Some("pk"), partition_key.as_deref());
+                assert_eq!(None, row_key);
+            }
+            _ => panic!("Broken row must fail the strict read"),
+        }
+
+        let transport = RecordingTransport::new(200, body);
+        let result = super::get_all_lenient::<TestEntity>(WriterRequest::new(
+            "http://localhost:5123",
+            transport.clone(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(1, result.entities.len());
+        assert_eq!(1, result.failed.len());
+        assert_eq!(
+            "http://localhost:5123/Row?tableName=test",
+            transport.get_requests()[0].get_url()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_sends_filter() {
+        let transport = RecordingTransport::new(200, b"[]");
+
+        let filter = FilterExpression::parse("Amount>10 and not Status = 'Closed'").unwrap();
+
+        let result: Option<Vec<TestEntity>> = super::query(
+            WriterRequest::new("http://localhost:5123", transport.clone()),
+            Some("pk"),
+            &filter,
+            Some(5),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(0, result.unwrap().len());
+
+        let requests = transport.get_requests();
+        assert!(requests[0].get_url().contains("/Row/Query"));
+        assert_eq!(
+            vec![
+                ("tableName".to_string(), Some("test".to_string())),
+                ("partitionKey".to_string(), Some("pk".to_string())),
+                ("filter".to_string(), Some(filter.to_string())),
+                ("limit".to_string(), Some("5".to_string())),
+            ],
+            requests[0].query_params
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_is_not_supported_by_server() {
+        let transport = RecordingTransport::new(404, b"");
+
+        let result: Result<Option<Vec<TestEntity>>, DataWriterError> = super::query(
+            WriterRequest::new("http://localhost:5123", transport),
+            None,
+            &FilterExpression::parse("Amount > 10").unwrap(),
+            None,
+            None,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(DataWriterError::EndpointIsNotSupported(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_is_not_supported_by_server() {
+        let transport = RecordingTransport::new(404, b"");
+
+        let result = super::bulk_delete_rows(
+            WriterRequest::new("http://localhost:5123", transport),
+            "test",
+            &[("pk1", "rk1")],
+            &DataSynchronizationPeriod::Sec1,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(DataWriterError::EndpointIsNotSupported(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoints() {
+        let transport = RecordingTransport::new(200, b"");
+        let request = || WriterRequest::new("http://localhost:5123", transport.clone());
+
+        let params = CreateTableParams {
+            persist: true,
+            max_partitions_amount: Some(10),
+            max_rows_per_partition_amount: None,
+            max_table_size_bytes: Some(1024),
+        };
+
+        super::update_table_attributes(
+            request(),
+            "test",
+            &params,
+            &DataSynchronizationPeriod::Sec1,
+        )
+        .await
+        .unwrap();
+        super::clean_table(request(), "test", &DataSynchronizationPeriod::Sec1)
+            .await
+            .unwrap();
+        super::delete_table(request(), "test", "secret")
+            .await
+            .unwrap();
+
+        let requests = transport.get_requests();
+
+        assert_eq!(WriterRequestMethod::Post, requests[0].method);
+        assert_eq!(
+            "http://localhost:5123/Tables/Update?tableName=test&syncPeriod=1&persist=true&maxPartitionsAmount=10&maxRowsPerPartitionAmount&maxTableSizeBytes=1024",
+            requests[0].get_url()
+        );
+
+        assert_eq!(WriterRequestMethod::Put, requests[1].method);
+        assert_eq!(
+            "http://localhost:5123/Tables/Clean?tableName=test&syncPeriod=1",
+            requests[1].get_url()
+        );
+
+        assert_eq!(WriterRequestMethod::Delete, requests[2].method);
+        assert_eq!(
+            "http://localhost:5123/Tables/Delete?tableName=test",
+            requests[2].get_url()
+        );
+        assert_eq!(
+            vec![("X-Api-Key".to_string(), "secret".to_string())],
+            requests[2].headers
+        );
+    }
+
+    #[tokio::test]
+    async fn test_admin_read_endpoints() {
+        let transport = RecordingTransport::new(200, br#"[{"name":"test","persist":true}]"#);
+
+        let tables = super::get_tables(WriterRequest::new(
+            "http://localhost:5123",
+            transport.clone(),
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!("test", tables[0].name);
+        assert!(tables[0].persist);
+
+        let requests = transport.get_requests();
+        assert_eq!(WriterRequestMethod::Get, requests[0].method);
+        assert_eq!("http://localhost:5123/Tables/List", requests[0].get_url());
+
+        let transport = RecordingTransport::new(200, br#"{"tables":[],"readers":[]}"#);
+
+        let status = super::get_server_status(WriterRequest::new(
+            "http://localhost:5123",
+            transport.clone(),
+        ))
+        .await
+        .unwrap();
+
+        assert!(status.tables.is_empty());
+
+        let requests = transport.get_requests();
+        assert_eq!(WriterRequestMethod::Get, requests[0].method);
+        assert_eq!("http://localhost:5123/api/Status", requests[0].get_url());
+    }
+
+    #[tokio::test]
+    async fn test_admin_errors() {
+        let request = |status_code: u16, body: &[u8]| {
+            WriterRequest::new(
+                "http://localhost:5123",
+                RecordingTransport::new(status_code, body),
+            )
+        };
+
+        let result = super::delete_table(request(404, b""), "test", "secret").await;
+        assert!(matches!(result, Err(DataWriterError::TableNotFound(name)) if name == "test"));
+
+        let result = super::clean_table(
+            request(400, br#"{"reason":"TableNotFound","message":"test"}"#),
+            "test",
+            &DataSynchronizationPeriod::Sec1,
+        )
+        .await;
+        assert!(matches!(result, Err(DataWriterError::TableNotFound(_))));
+
+        let result = super::get_tables(request(503, b"unavailable")).await;
+        assert!(matches!(result, Err(DataWriterError::ServerError(_))));
+
+        let result = super::get_server_status(request(500, b"")).await;
+        assert!(matches!(result, Err(DataWriterError::ServerError(_))));
+    }
 }
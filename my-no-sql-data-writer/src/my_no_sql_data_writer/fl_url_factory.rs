@@ -1,22 +1,33 @@
 use std::sync::Arc;
 
-use flurl::FlUrl;
-
+use my_no_sql_abstractions::{
+    DataSynchronizationPeriod, EntityValidationRules, MyNoSqlEntity, MyNoSqlEntitySerializer,
+};
 use rust_extensions::UnsafeValue;
 
-use super::{CreateTableParams, DataWriterError, MyNoSqlWriterSettings};
+use super::{
+    BodyCompression, CreateTableParams, DataWriterError, FlUrlTransport, MyNoSqlWriterSettings,
+    WriterRequest, WriterTransport,
+};
 
 #[derive(Clone)]
 pub struct FlUrlFactory {
     settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
     auto_create_table_params: Option<Arc<CreateTableParams>>,
 
-    #[cfg(feature = "with-ssh")]
-    pub ssh_security_credentials_resolver:
-        Option<Arc<dyn flurl::my_ssh::ssh_settings::SshSecurityCredentialsResolver + Send + Sync>>,
+    pub compression: Option<BodyCompression>,
+
+    /// FlUrlTransport is used if transport is not set
+    pub transport: Option<Arc<dyn WriterTransport + Send + Sync + 'static>>,
+
+    /// Built once and shared by all the requests
+    fl_url_transport: Arc<dyn WriterTransport + Send + Sync + 'static>,
+
+    pub validation_rules: Option<Arc<EntityValidationRules>>,
 
     create_table_is_called: Arc<UnsafeValue<bool>>,
     table_name: &'static str,
+    sync_period: DataSynchronizationPeriod,
 }
 
 impl FlUrlFactory {
@@ -24,6 +35,7 @@ impl FlUrlFactory {
         settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
         auto_create_table_params: Option<Arc<CreateTableParams>>,
         table_name: &'static str,
+        sync_period: DataSynchronizationPeriod,
     ) -> Self {
         Self {
             auto_create_table_params,
@@ -31,25 +43,53 @@ impl FlUrlFactory {
             create_table_is_called: UnsafeValue::new(false).into(),
             settings,
             table_name,
+            sync_period,
+            compression: None,
+            transport: None,
+            fl_url_transport: Arc::new(FlUrlTransport::new()),
+            validation_rules: None,
+        }
+    }
 
-            #[cfg(feature = "with-ssh")]
-            ssh_security_credentials_resolver: None,
+    pub fn get_compression(&self) -> Option<&BodyCompression> {
+        self.compression.as_ref()
+    }
+
+    /// Entities are not checked if validation rules are not set
+    pub fn validate_entities<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        match self.validation_rules.as_ref() {
+            Some(rules) => super::validate_entities(rules, entities),
+            None => Ok(()),
         }
     }
 
-    async fn create_fl_url(&self, url: &str) -> FlUrl {
-        let fl_url = flurl::FlUrl::new(url);
+    /// FlUrl transport is rebuilt with the resolver
+    #[cfg(feature = "with-ssh")]
+    pub fn set_ssh_security_credentials_resolver(
+        &mut self,
+        resolver: Arc<
+            dyn flurl::my_ssh::ssh_settings::SshSecurityCredentialsResolver + Send + Sync,
+        >,
+    ) {
+        self.fl_url_transport =
+            Arc::new(FlUrlTransport::new().with_ssh_security_credentials_resolver(Some(resolver)));
+    }
 
-        #[cfg(feature = "with-ssh")]
-        if let Some(ssh_security_credentials_resolver) = &self.ssh_security_credentials_resolver {
-            return fl_url
-                .set_ssh_security_credentials_resolver(ssh_security_credentials_resolver.clone());
+    fn get_transport(&self) -> Arc<dyn WriterTransport + Send + Sync + 'static> {
+        match &self.transport {
+            Some(transport) => transport.clone(),
+            None => self.fl_url_transport.clone(),
         }
+    }
 
-        fl_url
+    async fn create_writer_request(&self, url: &str) -> WriterRequest {
+        WriterRequest::new(url, self.get_transport())
     }
 
-    pub async fn get_fl_url(&self) -> Result<(FlUrl, String), DataWriterError> {
+    pub async fn get_writer_request(&self) -> Result<(WriterRequest, String), DataWriterError> {
         let url = self.settings.get_url().await;
         if !self.create_table_is_called.get_value() {
             if let Some(crate_table_params) = &self.auto_create_table_params {
@@ -60,7 +100,7 @@ impl FlUrlFactory {
             self.create_table_is_called.set_value(true);
         }
 
-        let result = self.create_fl_url(url.as_str()).await;
+        let result = self.create_writer_request(url.as_str()).await;
 
         Ok((result, url))
     }
@@ -70,13 +110,13 @@ impl FlUrlFactory {
         url: &str,
         create_table_params: &CreateTableParams,
     ) -> Result<(), DataWriterError> {
-        let fl_url = self.create_fl_url(url).await;
+        let fl_url = self.create_writer_request(url).await;
         super::execution::create_table_if_not_exists(
             fl_url,
             url,
             self.table_name,
             create_table_params,
-            my_no_sql_abstractions::DataSynchronizationPeriod::Sec1,
+            self.sync_period,
         )
         .await
     }
@@ -0,0 +1,99 @@
+#[cfg(feature = "with-ssh")]
+use std::sync::Arc;
+
+use flurl::FlUrl;
+
+use super::{
+    body_compression::CONTENT_ENCODING_HEADER, DataWriterError, WriterHttpRequest,
+    WriterHttpResponse, WriterRequestMethod, WriterTransport,
+};
+
+/// Default transport of the writer
+pub struct FlUrlTransport {
+    #[cfg(feature = "with-ssh")]
+    ssh_security_credentials_resolver:
+        Option<Arc<dyn flurl::my_ssh::ssh_settings::SshSecurityCredentialsResolver + Send + Sync>>,
+}
+
+impl FlUrlTransport {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "with-ssh")]
+            ssh_security_credentials_resolver: None,
+        }
+    }
+
+    #[cfg(feature = "with-ssh")]
+    pub fn with_ssh_security_credentials_resolver(
+        mut self,
+        resolver: Option<
+            Arc<dyn flurl::my_ssh::ssh_settings::SshSecurityCredentialsResolver + Send + Sync>,
+        >,
+    ) -> Self {
+        self.ssh_security_credentials_resolver = resolver;
+        self
+    }
+
+    fn create_fl_url(&self, url: &str) -> FlUrl {
+        let fl_url = FlUrl::new(url);
+
+        #[cfg(feature = "with-ssh")]
+        if let Some(ssh_security_credentials_resolver) = &self.ssh_security_credentials_resolver {
+            return fl_url
+                .set_ssh_security_credentials_resolver(ssh_security_credentials_resolver.clone());
+        }
+
+        fl_url
+    }
+}
+
+#[async_trait::async_trait]
+impl WriterTransport for FlUrlTransport {
+    async fn execute(
+        &self,
+        request: WriterHttpRequest,
+    ) -> Result<WriterHttpResponse, DataWriterError> {
+        let mut fl_url = self.create_fl_url(request.base_url.as_str());
+
+        for segment in request.path_segments.iter() {
+            fl_url = fl_url.append_path_segment(segment.as_str());
+        }
+
+        for (name, value) in request.query_params.iter() {
+            fl_url = fl_url.append_query_param(name.as_str(), value.as_deref());
+        }
+
+        for (name, value) in request.headers.iter() {
+            fl_url = fl_url.with_header(name.as_str(), value.as_str());
+        }
+
+        if request.retries > 0 {
+            fl_url = fl_url.with_retries(request.retries);
+        }
+
+        let response = match request.method {
+            WriterRequestMethod::Get => fl_url.get().await?,
+            WriterRequestMethod::Post => fl_url.post(request.body).await?,
+            WriterRequestMethod::Put => fl_url.put(request.body).await?,
+            WriterRequestMethod::Delete => fl_url.delete().await?,
+        };
+
+        let url = response.url.to_string();
+        let status_code = response.get_status_code();
+
+        // Only the headers the writer reads are kept
+        let headers = match response.get_header(CONTENT_ENCODING_HEADER) {
+            Some(value) => vec![(CONTENT_ENCODING_HEADER.to_string(), value.to_string())],
+            None => Vec::new(),
+        };
+
+        let body = response.receive_body().await?;
+
+        Ok(WriterHttpResponse {
+            url,
+            status_code,
+            headers,
+            body,
+        })
+    }
+}
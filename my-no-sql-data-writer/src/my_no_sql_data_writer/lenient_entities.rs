@@ -0,0 +1,14 @@
+use super::EntityDeserializationFail;
+
+/// Result of the lenient read: entities which were deserialized and rows which were not.
+#[derive(Debug)]
+pub struct LenientEntities<TEntity> {
+    pub entities: Vec<TEntity>,
+    pub failed: Vec<EntityDeserializationFail>,
+}
+
+impl<TEntity> LenientEntities<TEntity> {
+    pub fn has_failed(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
@@ -6,9 +6,41 @@ pub use error::DataWriterError;
 pub use my_no_sql_data_writer::*;
 pub use settings::*;
 pub use update_read_statistics::*;
-mod execution;
-mod fl_url_ext;
+pub(crate) mod execution;
+mod writer_request_ext;
+mod request_tracing;
+pub use request_tracing::*;
+mod body_compression;
+pub use body_compression::*;
+mod bulk_chunks;
+pub use bulk_chunks::*;
 mod with_retries;
 pub use with_retries::*;
+mod with_options;
+pub use with_options::*;
+mod request_options;
+pub use request_options::*;
+mod retry_policy;
+pub use retry_policy::*;
+mod entity_deserialization_fail;
+pub use entity_deserialization_fail::*;
+mod lenient_entities;
+pub use lenient_entities::*;
+mod entities_stream;
+pub use entities_stream::*;
+mod my_no_sql_data_writer_api;
+pub use my_no_sql_data_writer_api::*;
+#[cfg(feature = "mocks")]
+mod my_no_sql_data_writer_mock;
+#[cfg(feature = "mocks")]
+pub use my_no_sql_data_writer_mock::*;
 mod fl_url_factory;
 pub use fl_url_factory::*;
+mod writer_transport;
+pub use writer_transport::*;
+mod writer_request;
+pub use writer_request::*;
+mod fl_url_transport;
+pub use fl_url_transport::*;
+mod entity_validation;
+pub use entity_validation::*;
@@ -1,23 +1,47 @@
 use std::{marker::PhantomData, sync::Arc};
 
-use flurl::FlUrl;
+use futures::Stream;
 
-use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_abstractions::{
+    DataSynchronizationPeriod, EntityValidationRules, MyNoSqlEntity, MyNoSqlEntitySerializer,
+    MyNoSqlTableAttributes,
+};
 
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 use serde::{Deserialize, Serialize};
 
-use crate::{MyNoSqlDataWriterWithRetries, MyNoSqlWriterSettings};
+use crate::{
+    MyNoSqlDataWriterApi, MyNoSqlDataWriterWithOptions, MyNoSqlDataWriterWithRetries,
+    MyNoSqlOutbox, MyNoSqlWriterSettings, OutboxSettings, RequestOptions, RetryPolicy,
+};
 
-use super::{fl_url_factory::FlUrlFactory, DataWriterError, UpdateReadStatistics};
+use super::{
+    fl_url_factory::FlUrlFactory, BodyCompression, BulkChunksSettings, BulkWriteResult,
+    DataWriterError, LenientEntities, UpdateReadStatistics, WriterEntitiesStream, WriterRequest,
+    WriterTransport,
+};
 
 pub struct CreateTableParams {
     pub persist: bool,
     pub max_partitions_amount: Option<usize>,
     pub max_rows_per_partition_amount: Option<usize>,
+    /// Rows of the least recently read partitions are garbage collected when the table exceeds the size
+    pub max_table_size_bytes: Option<usize>,
+}
+
+impl From<&MyNoSqlTableAttributes> for CreateTableParams {
+    fn from(src: &MyNoSqlTableAttributes) -> Self {
+        Self {
+            persist: src.persist,
+            max_partitions_amount: src.max_partitions_amount,
+            max_rows_per_partition_amount: src.max_rows_per_partition_amount,
+            max_table_size_bytes: None,
+        }
+    }
 }
 
 impl CreateTableParams {
-    pub fn populate_params(&self, mut fl_url: FlUrl) -> FlUrl {
+    pub fn populate_params(&self, mut fl_url: WriterRequest) -> WriterRequest {
         if let Some(max_partitions_amount) = self.max_partitions_amount {
             fl_url = fl_url.append_query_param(
                 "maxPartitionsAmount",
@@ -32,6 +56,11 @@ impl CreateTableParams {
             )
         };
 
+        if let Some(max_table_size_bytes) = self.max_table_size_bytes {
+            fl_url = fl_url
+                .append_query_param("maxTableSizeBytes", Some(max_table_size_bytes.to_string()))
+        };
+
         if !self.persist {
             fl_url = fl_url.append_query_param("persist", Some("false"));
         };
@@ -41,9 +70,12 @@ impl CreateTableParams {
 }
 
 pub struct MyNoSqlDataWriter<TEntity: MyNoSqlEntity + Sync + Send> {
-    sync_period: DataSynchronizationPeriod,
+    pub(crate) sync_period: DataSynchronizationPeriod,
     phantom: PhantomData<TEntity>,
-    fl_url_factory: FlUrlFactory,
+    pub(crate) fl_url_factory: FlUrlFactory,
+    pub(crate) outbox: Option<Arc<MyNoSqlOutbox>>,
+    pub(crate) bulk_chunks: Option<BulkChunksSettings>,
+    ping_id: u64,
 }
 
 impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlDataWriter<TEntity> {
@@ -52,12 +84,15 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         auto_create_table_params: Option<CreateTableParams>,
         sync_period: DataSynchronizationPeriod,
     ) -> Self {
-        let settings_cloned = settings.clone();
-        tokio::spawn(async move {
-            crate::PING_POOL
-                .register(settings_cloned, TEntity::TABLE_NAME)
-                .await;
-        });
+        // Table attributes declared by the entity are used if params are not passed explicitly
+        let auto_create_table_params = match auto_create_table_params {
+            Some(params) => Some(params),
+            None => TEntity::TABLE_ATTRIBUTES
+                .as_ref()
+                .map(CreateTableParams::from),
+        };
+
+        let ping_id = crate::PING_POOL.register(settings.clone(), TEntity::TABLE_NAME);
 
         Self {
             phantom: PhantomData,
@@ -66,23 +101,14 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
                 settings,
                 auto_create_table_params.map(|itm| itm.into()),
                 TEntity::TABLE_NAME,
+                sync_period,
             ),
+            outbox: None,
+            bulk_chunks: None,
+            ping_id,
         }
     }
 
-    pub async fn create_table(&self, params: CreateTableParams) -> Result<(), DataWriterError> {
-        let (fl_url, url) = self.fl_url_factory.get_fl_url().await?;
-
-        super::execution::create_table(
-            fl_url,
-            url.as_str(),
-            TEntity::TABLE_NAME,
-            params,
-            &self.sync_period,
-        )
-        .await
-    }
-
     #[cfg(feature = "with-ssh")]
     pub fn set_ssh_security_credentials_resolver(
         &mut self,
@@ -90,48 +116,105 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
             dyn flurl::my_ssh::ssh_settings::SshSecurityCredentialsResolver + Send + Sync,
         >,
     ) {
-        self.fl_url_factory.ssh_security_credentials_resolver = Some(resolver);
+        self.fl_url_factory
+            .set_ssh_security_credentials_resolver(resolver);
     }
 
-    pub async fn create_table_if_not_exists(
-        &self,
-        params: &CreateTableParams,
-    ) -> Result<(), DataWriterError> {
-        let (fl_url, url) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::create_table_if_not_exists(
-            fl_url,
-            url.as_str(),
-            TEntity::TABLE_NAME,
-            params,
-            self.sync_period,
-        )
-        .await
+    /// Requests are sent with the transport instead of FlUrl
+    pub fn set_transport(&mut self, transport: Arc<dyn WriterTransport + Send + Sync + 'static>) {
+        self.fl_url_factory.transport = Some(transport);
+    }
+
+    /// Bodies of bulk operations are compressed if they are bigger than threshold.
+    /// Compressed responses are accepted for get_all and get_by_partition_key
+    pub fn set_compression(&mut self, compression: BodyCompression) {
+        self.fl_url_factory.compression = Some(compression);
+    }
+
+    /// bulk_insert_or_replace splits entities into several requests limited by size and rows amount
+    pub fn set_bulk_chunks(&mut self, settings: BulkChunksSettings) {
+        self.bulk_chunks = Some(settings);
+    }
+
+    /// Entities which break the rules are rejected before they are sent or put into the outbox.
+    /// Writers created by with_options and with_retries use the same rules
+    pub fn set_validation_rules(&mut self, rules: EntityValidationRules) {
+        self.fl_url_factory.validation_rules = Some(Arc::new(rules));
+    }
+
+    /// Write operations (insert, replace, bulk, delete) which can not reach the server
+    /// are stored to the file based outbox and replayed in order by the background task.
+    pub async fn enable_outbox(
+        &mut self,
+        settings: OutboxSettings,
+    ) -> Result<Arc<MyNoSqlOutbox>, DataWriterError> {
+        let outbox = Arc::new(MyNoSqlOutbox::load(settings).await?);
+        MyNoSqlOutbox::start_replay_loop(&outbox, self.fl_url_factory.clone());
+        self.outbox = Some(outbox.clone());
+        Ok(outbox)
+    }
+
+    pub fn get_outbox(&self) -> Option<&Arc<MyNoSqlOutbox>> {
+        self.outbox.as_ref()
     }
 
     pub fn with_retries(&self, max_attempts: usize) -> MyNoSqlDataWriterWithRetries<TEntity> {
+        self.with_retry_policy(RetryPolicy::new(max_attempts))
+    }
+
+    pub fn with_retry_policy(
+        &self,
+        retry_policy: RetryPolicy,
+    ) -> MyNoSqlDataWriterWithRetries<TEntity> {
         MyNoSqlDataWriterWithRetries::new(
             self.fl_url_factory.clone(),
             self.sync_period,
-            max_attempts,
+            retry_policy,
         )
+        .set_outbox_and_bulk_chunks(self.outbox.clone(), self.bulk_chunks.clone())
+    }
+
+    /// Sync period, timeout and headers can be overridden for the calls made through the returned writer
+    pub fn with_options(&self) -> MyNoSqlDataWriterWithOptions<'_, TEntity> {
+        MyNoSqlDataWriterWithOptions::new(self, RequestOptions::new())
+    }
+
+    pub async fn create_table(&self, params: CreateTableParams) -> Result<(), DataWriterError> {
+        self.with_options().create_table(params).await
+    }
+
+    pub async fn create_table_if_not_exists(
+        &self,
+        params: &CreateTableParams,
+    ) -> Result<(), DataWriterError> {
+        self.with_options().create_table_if_not_exists(params).await
     }
 
     pub async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::insert_entity(fl_url, entity, &self.sync_period).await
+        self.with_options().insert_entity(entity).await
     }
 
     pub async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::insert_or_replace_entity(fl_url, entity, &self.sync_period).await
+        self.with_options().insert_or_replace_entity(entity).await
     }
 
     pub async fn bulk_insert_or_replace(
         &self,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::bulk_insert_or_replace(fl_url, entities, &self.sync_period).await
+        self.with_options().bulk_insert_or_replace(entities).await
+    }
+
+    /// Chunks are sent concurrently. Result contains the indexes of written entities and the failed chunks.
+    /// Entities rejected by the validation rules or failed to serialize are returned as one failed chunk
+    pub async fn bulk_insert_or_replace_chunked(
+        &self,
+        entities: &[TEntity],
+        settings: &BulkChunksSettings,
+    ) -> BulkWriteResult {
+        self.with_options()
+            .bulk_insert_or_replace_chunked(entities, settings)
+            .await
     }
 
     pub async fn get_entity(
@@ -140,14 +223,9 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         row_key: &str,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<TEntity>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_entity(
-            fl_url,
-            partition_key,
-            row_key,
-            update_read_statistics.as_ref(),
-        )
-        .await
+        self.with_options()
+            .get_entity(partition_key, row_key, update_read_statistics)
+            .await
     }
 
     pub async fn get_by_partition_key(
@@ -155,13 +233,20 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         partition_key: &str,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_by_partition_key(
-            fl_url,
-            partition_key,
-            update_read_statistics.as_ref(),
-        )
-        .await
+        self.with_options()
+            .get_by_partition_key(partition_key, update_read_statistics)
+            .await
+    }
+
+    /// Rows which can not be deserialized are returned separately instead of failing the whole read
+    pub async fn get_by_partition_key_lenient(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.with_options()
+            .get_by_partition_key_lenient(partition_key, update_read_statistics)
+            .await
     }
 
     pub async fn get_enum_case_models_by_partition_key<
@@ -175,12 +260,9 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         &self,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<Vec<TResult>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_enum_case_models_by_partition_key(
-            fl_url,
-            update_read_statistics.as_ref(),
-        )
-        .await
+        self.with_options()
+            .get_enum_case_models_by_partition_key(update_read_statistics)
+            .await
     }
 
     pub async fn get_enum_case_model<
@@ -194,16 +276,23 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         &self,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<TResult>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_enum_case_model(fl_url, update_read_statistics.as_ref()).await
+        self.with_options()
+            .get_enum_case_model(update_read_statistics)
+            .await
     }
 
     pub async fn get_by_row_key(
         &self,
         row_key: &str,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_by_row_key(fl_url, row_key).await
+        self.with_options().get_by_row_key(row_key).await
+    }
+
+    pub async fn get_by_row_key_lenient(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.with_options().get_by_row_key_lenient(row_key).await
     }
 
     pub async fn get_partition_keys(
@@ -211,8 +300,7 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         skip: Option<i32>,
         limit: Option<i32>,
     ) -> Result<Vec<String>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_partition_keys(fl_url, TEntity::TABLE_NAME, skip, limit).await
+        self.with_options().get_partition_keys(skip, limit).await
     }
 
     pub async fn delete_enum_case<
@@ -225,8 +313,7 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
     >(
         &self,
     ) -> Result<Option<TResult>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_enum_case(fl_url).await
+        self.with_options().delete_enum_case().await
     }
 
     pub async fn delete_enum_case_with_row_key<
@@ -240,8 +327,9 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         &self,
         row_key: &str,
     ) -> Result<Option<TResult>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_enum_case_with_row_key(fl_url, row_key).await
+        self.with_options()
+            .delete_enum_case_with_row_key(row_key)
+            .await
     }
 
     pub async fn delete_row(
@@ -249,26 +337,89 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         partition_key: &str,
         row_key: &str,
     ) -> Result<Option<TEntity>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_row(fl_url, partition_key, row_key).await
+        self.with_options().delete_row(partition_key, row_key).await
     }
 
     pub async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::delete_partitions(fl_url, TEntity::TABLE_NAME, partition_keys).await
+        self.with_options().delete_partitions(partition_keys).await
+    }
+
+    pub async fn bulk_delete_rows(&self, rows: &[(&str, &str)]) -> Result<(), DataWriterError> {
+        self.with_options().bulk_delete_rows(rows).await
+    }
+
+    /// None removes the expiration of the rows
+    pub async fn set_rows_expiration(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        self.with_options()
+            .set_rows_expiration(partition_key, row_keys, expires)
+            .await
+    }
+
+    /// None removes the expiration of the partitions
+    pub async fn set_partitions_expiration(
+        &self,
+        partition_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        self.with_options()
+            .set_partitions_expiration(partition_keys, expires)
+            .await
     }
 
     pub async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_all(fl_url).await
+        self.with_options().get_all().await
+    }
+
+    /// Rows which match the filter are selected on the server side.
+    /// Filter has the syntax of my_no_sql_core::query::FilterExpression. Example: "Status = 'Active' AND Amount > 10".
+    /// Filter is parsed before the request, so invalid filter is returned as DataWriterError::InvalidFilter
+    pub async fn query(
+        &self,
+        partition_key: Option<&str>,
+        filter: &str,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.with_options()
+            .query(partition_key, filter, limit)
+            .await
+    }
+
+    pub async fn get_all_lenient(
+        &self,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.with_options().get_all_lenient().await
+    }
+
+    /// Entities are deserialized one by one while the stream is polled.
+    /// Table is requested from the server page by page. Example: DEFAULT_STREAM_PAGE_SIZE
+    pub fn stream_all(
+        &self,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<TEntity, DataWriterError>> {
+        self.with_options().stream_all(page_size)
+    }
+
+    pub fn stream_partition(
+        &self,
+        partition_key: &str,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<TEntity, DataWriterError>> {
+        self.with_options()
+            .stream_partition(partition_key, page_size)
     }
 
     pub async fn clean_table_and_bulk_insert(
         &self,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::clean_table_and_bulk_insert(fl_url, entities, &self.sync_period).await
+        self.with_options()
+            .clean_table_and_bulk_insert(entities)
+            .await
     }
 
     pub async fn clean_partition_and_bulk_insert(
@@ -276,14 +427,169 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlData
         partition_key: &str,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::clean_partition_and_bulk_insert(
-            fl_url,
+        self.with_options()
+            .clean_partition_and_bulk_insert(partition_key, entities)
+            .await
+    }
+}
+
+impl<TEntity: MyNoSqlEntity + Sync + Send> Drop for MyNoSqlDataWriter<TEntity> {
+    fn drop(&mut self) {
+        crate::PING_POOL.unregister(self.ping_id);
+    }
+}
+
+#[async_trait::async_trait]
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> MyNoSqlDataWriterApi<TEntity>
+    for MyNoSqlDataWriter<TEntity>
+{
+    async fn create_table(&self, params: CreateTableParams) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::create_table(self, params).await
+    }
+
+    async fn create_table_if_not_exists(
+        &self,
+        params: &CreateTableParams,
+    ) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::create_table_if_not_exists(self, params).await
+    }
+
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::insert_entity(self, entity).await
+    }
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::insert_or_replace_entity(self, entity).await
+    }
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::bulk_insert_or_replace(self, entities).await
+    }
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        MyNoSqlDataWriter::get_entity(self, partition_key, row_key, update_read_statistics).await
+    }
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::get_by_partition_key(self, partition_key, update_read_statistics).await
+    }
+
+    async fn get_by_partition_key_lenient(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::get_by_partition_key_lenient(self, partition_key, update_read_statistics)
+            .await
+    }
+
+    async fn get_by_row_key(&self, row_key: &str) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::get_by_row_key(self, row_key).await
+    }
+
+    async fn get_by_row_key_lenient(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::get_by_row_key_lenient(self, row_key).await
+    }
+
+    async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        MyNoSqlDataWriter::get_partition_keys(self, skip, limit).await
+    }
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::get_all(self).await
+    }
+
+    async fn get_all_lenient(&self) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::get_all_lenient(self).await
+    }
+
+    async fn query(
+        &self,
+        partition_key: Option<&str>,
+        filter: &str,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        MyNoSqlDataWriter::query(self, partition_key, filter, limit).await
+    }
+
+    fn stream_all(&self, page_size: usize) -> WriterEntitiesStream<'_, TEntity> {
+        Box::pin(MyNoSqlDataWriter::stream_all(self, page_size))
+    }
+
+    fn stream_partition(
+        &self,
+        partition_key: &str,
+        page_size: usize,
+    ) -> WriterEntitiesStream<'_, TEntity> {
+        Box::pin(MyNoSqlDataWriter::stream_partition(
+            self,
             partition_key,
-            entities,
-            &self.sync_period,
-        )
-        .await
+            page_size,
+        ))
+    }
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        MyNoSqlDataWriter::delete_row(self, partition_key, row_key).await
+    }
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::delete_partitions(self, partition_keys).await
+    }
+
+    async fn bulk_delete_rows(&self, rows: &[(&str, &str)]) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::bulk_delete_rows(self, rows).await
+    }
+
+    async fn set_rows_expiration(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::set_rows_expiration(self, partition_key, row_keys, expires).await
+    }
+
+    async fn set_partitions_expiration(
+        &self,
+        partition_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::set_partitions_expiration(self, partition_keys, expires).await
+    }
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::clean_table_and_bulk_insert(self, entities).await
+    }
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        MyNoSqlDataWriter::clean_partition_and_bulk_insert(self, partition_key, entities).await
     }
 }
 
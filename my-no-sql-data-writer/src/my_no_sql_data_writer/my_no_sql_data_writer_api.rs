@@ -0,0 +1,199 @@
+use std::pin::Pin;
+
+use futures::Stream;
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use super::{CreateTableParams, DataWriterError, LenientEntities, UpdateReadStatistics};
+
+/// Entities returned by stream_all and stream_partition
+pub type WriterEntitiesStream<'s, TEntity> =
+    Pin<Box<dyn Stream<Item = Result<TEntity, DataWriterError>> + Send + 's>>;
+
+/// Operations of the writer. Implemented by MyNoSqlDataWriter and by MyNoSqlDataWriterMock (feature "mocks")
+#[async_trait::async_trait]
+pub trait MyNoSqlDataWriterApi<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send> {
+    async fn create_table(&self, params: CreateTableParams) -> Result<(), DataWriterError>;
+
+    async fn create_table_if_not_exists(
+        &self,
+        params: &CreateTableParams,
+    ) -> Result<(), DataWriterError>;
+
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError>;
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError>;
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError>;
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError>;
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    async fn get_by_partition_key_lenient(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError>;
+
+    async fn get_by_row_key(&self, row_key: &str) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    async fn get_by_row_key_lenient(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError>;
+
+    async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError>;
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    async fn get_all_lenient(&self) -> Result<Option<LenientEntities<TEntity>>, DataWriterError>;
+
+    /// Filter has the syntax of my_no_sql_core::query::FilterExpression
+    async fn query(
+        &self,
+        partition_key: Option<&str>,
+        filter: &str,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError>;
+
+    fn stream_all(&self, page_size: usize) -> WriterEntitiesStream<'_, TEntity>;
+
+    fn stream_partition(
+        &self,
+        partition_key: &str,
+        page_size: usize,
+    ) -> WriterEntitiesStream<'_, TEntity>;
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError>;
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError>;
+
+    async fn bulk_delete_rows(&self, rows: &[(&str, &str)]) -> Result<(), DataWriterError>;
+
+    async fn set_rows_expiration(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError>;
+
+    async fn set_partitions_expiration(
+        &self,
+        partition_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError>;
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError>;
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError>;
+
+    async fn get_enum_case_model<
+        TResult: MyNoSqlEntity
+            + From<TEntity>
+            + my_no_sql_abstractions::GetMyNoSqlEntity
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        let entity = self
+            .get_entity(
+                TResult::PARTITION_KEY,
+                TResult::ROW_KEY,
+                update_read_statistics,
+            )
+            .await?;
+
+        match entity {
+            Some(entity) => Ok(Some(entity.into())),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_enum_case_models_by_partition_key<
+        TResult: MyNoSqlEntity
+            + my_no_sql_abstractions::GetMyNoSqlEntitiesByPartitionKey
+            + From<TEntity>
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TResult>>, DataWriterError> {
+        let entities = self
+            .get_by_partition_key(TResult::PARTITION_KEY, update_read_statistics)
+            .await?;
+
+        match entities {
+            Some(entities) => Ok(Some(entities.into_iter().map(|itm| itm.into()).collect())),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete_enum_case<
+        TResult: MyNoSqlEntity
+            + From<TEntity>
+            + my_no_sql_abstractions::GetMyNoSqlEntity
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        let entity = self
+            .delete_row(TResult::PARTITION_KEY, TResult::ROW_KEY)
+            .await?;
+
+        match entity {
+            Some(entity) => Ok(Some(entity.into())),
+            None => Ok(None),
+        }
+    }
+
+    async fn delete_enum_case_with_row_key<
+        TResult: MyNoSqlEntity
+            + From<TEntity>
+            + my_no_sql_abstractions::GetMyNoSqlEntitiesByPartitionKey
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        let entity = self.delete_row(TResult::PARTITION_KEY, row_key).await?;
+
+        match entity {
+            Some(entity) => Ok(Some(entity.into())),
+            None => Ok(None),
+        }
+    }
+}
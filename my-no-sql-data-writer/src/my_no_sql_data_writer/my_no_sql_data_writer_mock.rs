@@ -0,0 +1,904 @@
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+use futures::StreamExt;
+use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_core::{
+    db::{DbRow, DbTable},
+    db_json_entity::{DbJsonEntity, JsonTimeStamp},
+    my_json::json_reader::JsonFirstLineIterator,
+    query::FilterExpression,
+};
+use my_no_sql_tcp_reader::{MyNoSqlDataReaderMock, MyNoSqlDataReaderMockInner};
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+use tokio::sync::{Mutex, MutexGuard};
+
+use super::{
+    CreateTableParams, DataWriterError, EntityDeserializationFail, LenientEntities,
+    MyNoSqlDataWriterApi, UpdateReadStatistics, WriterEntitiesStream,
+};
+
+/// Table of the mock with the expiration moments of the rows and the partitions.
+/// Table is changed only through the DbTable api, so the mock behaves as the table of the server
+struct MyNoSqlDataWriterMockInner {
+    table: DbTable,
+    table_is_created: bool,
+    partitions_expires: BTreeMap<String, DateTimeAsMicroseconds>,
+    rows_expires: BTreeMap<String, BTreeMap<String, DateTimeAsMicroseconds>>,
+}
+
+impl MyNoSqlDataWriterMockInner {
+    fn new(table_name: &'static str) -> Self {
+        Self {
+            table: DbTable::new_with_default_attributes(table_name.into()),
+            table_is_created: false,
+            partitions_expires: BTreeMap::new(),
+            rows_expires: BTreeMap::new(),
+        }
+    }
+
+    fn set_row_expires(
+        &mut self,
+        partition_key: &str,
+        row_key: &str,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) {
+        match expires {
+            Some(expires) => {
+                self.rows_expires
+                    .entry(partition_key.to_string())
+                    .or_default()
+                    .insert(row_key.to_string(), expires);
+            }
+            None => {
+                if let Some(rows) = self.rows_expires.get_mut(partition_key) {
+                    rows.remove(row_key);
+
+                    if rows.is_empty() {
+                        self.rows_expires.remove(partition_key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Expires of the row is taken from the entity, as the server does
+    fn insert_or_replace_row(
+        &mut self,
+        db_row: &Arc<DbRow>,
+        expires: Option<DateTimeAsMicroseconds>,
+    ) {
+        self.table.insert_or_replace_row(db_row);
+        self.set_row_expires(db_row.get_partition_key(), db_row.get_row_key(), expires);
+    }
+
+    fn insert_row(&mut self, db_row: &Arc<DbRow>, expires: Option<DateTimeAsMicroseconds>) -> bool {
+        if self.table.insert_row(db_row).is_none() {
+            return false;
+        }
+
+        self.set_row_expires(db_row.get_partition_key(), db_row.get_row_key(), expires);
+        true
+    }
+
+    fn remove_row(&mut self, partition_key: &str, row_key: &str) -> Option<Arc<DbRow>> {
+        let (_, removed_row, partition_is_empty) =
+            self.table
+                .remove_row(&partition_key.to_string(), &row_key.to_string(), true)?;
+
+        self.set_row_expires(partition_key, row_key, None);
+
+        if partition_is_empty {
+            self.partitions_expires.remove(partition_key);
+        }
+
+        Some(removed_row)
+    }
+
+    fn remove_partition(&mut self, partition_key: &str) -> Vec<Arc<DbRow>> {
+        self.partitions_expires.remove(partition_key);
+        self.rows_expires.remove(partition_key);
+
+        match self.table.remove_partition(&partition_key.to_string()) {
+            Some(db_partition) => db_partition.get_all_rows_cloned(),
+            None => vec![],
+        }
+    }
+
+    fn remove_all(&mut self) -> Vec<Arc<DbRow>> {
+        let removed_rows = self
+            .table
+            .get_all_rows(None, None)
+            .map(|(_, db_row)| db_row.clone())
+            .collect();
+
+        self.table.clear_table();
+        self.partitions_expires.clear();
+        self.rows_expires.clear();
+
+        removed_rows
+    }
+
+    fn remove_expired(&mut self, now: DateTimeAsMicroseconds) -> Vec<Arc<DbRow>> {
+        let partitions_to_expire: Vec<String> = self
+            .partitions_expires
+            .iter()
+            .filter(|(_, expires)| expires.unix_microseconds <= now.unix_microseconds)
+            .map(|(partition_key, _)| partition_key.clone())
+            .collect();
+
+        let mut removed_rows = Vec::new();
+
+        for partition_key in partitions_to_expire {
+            removed_rows.extend(self.remove_partition(partition_key.as_str()));
+        }
+
+        let mut rows_to_expire = Vec::new();
+
+        for (partition_key, rows) in self.rows_expires.iter() {
+            for (row_key, expires) in rows.iter() {
+                if expires.unix_microseconds <= now.unix_microseconds {
+                    rows_to_expire.push((partition_key.clone(), row_key.clone()));
+                }
+            }
+        }
+
+        for (partition_key, row_key) in rows_to_expire {
+            if let Some(removed_row) = self.remove_row(partition_key.as_str(), row_key.as_str()) {
+                removed_rows.push(removed_row);
+            }
+        }
+
+        removed_rows
+    }
+}
+
+/// In memory writer to be used in unit tests instead of MyNoSqlDataWriter.
+/// If reader mock is linked - every write is visible through the reader.
+/// Expired rows and partitions are removed before the next operation.
+pub struct MyNoSqlDataWriterMock<
+    TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static,
+> {
+    inner: Mutex<MyNoSqlDataWriterMockInner>,
+    reader: Option<Arc<MyNoSqlDataReaderMockInner<TEntity>>>,
+    itm: PhantomData<TEntity>,
+}
+
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
+    MyNoSqlDataWriterMock<TEntity>
+{
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(MyNoSqlDataWriterMockInner::new(TEntity::TABLE_NAME)),
+            reader: None,
+            itm: PhantomData,
+        }
+    }
+
+    pub fn new_with_reader(reader: &MyNoSqlDataReaderMock<TEntity>) -> Self {
+        Self {
+            inner: Mutex::new(MyNoSqlDataWriterMockInner::new(TEntity::TABLE_NAME)),
+            reader: Some(reader.inner.clone()),
+            itm: PhantomData,
+        }
+    }
+
+    pub async fn get_rows_amount(&self) -> usize {
+        let inner = self.lock().await;
+        inner.table.get_rows_amount()
+    }
+
+    async fn lock(&self) -> MutexGuard<'_, MyNoSqlDataWriterMockInner> {
+        let mut inner = self.inner.lock().await;
+
+        let expired_rows = inner.remove_expired(DateTimeAsMicroseconds::now());
+        self.delete_from_reader(&expired_rows).await;
+
+        inner
+    }
+
+    async fn push_to_reader(&self, db_rows: &[Arc<DbRow>]) -> Result<(), DataWriterError> {
+        let reader = match self.reader.as_ref() {
+            Some(reader) => reader,
+            None => return Ok(()),
+        };
+
+        let mut entities = Vec::with_capacity(db_rows.len());
+
+        for db_row in db_rows {
+            entities.push(Arc::new(to_entity::<TEntity>(db_row)?));
+        }
+
+        reader.update(entities.into_iter()).await;
+        Ok(())
+    }
+
+    async fn delete_from_reader(&self, db_rows: &[Arc<DbRow>]) {
+        if db_rows.is_empty() {
+            return;
+        }
+
+        if let Some(reader) = self.reader.as_ref() {
+            reader
+                .delete(db_rows.iter().map(|db_row| {
+                    (
+                        db_row.get_partition_key().to_string(),
+                        db_row.get_row_key().to_string(),
+                    )
+                }))
+                .await;
+        }
+    }
+
+    async fn bulk_insert_or_replace_rows(
+        &self,
+        inner: &mut MyNoSqlDataWriterMockInner,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        let now = JsonTimeStamp::now();
+        let mut db_rows = Vec::with_capacity(entities.len());
+
+        for entity in entities {
+            db_rows.push(to_db_row(entity, &now)?);
+        }
+
+        for (db_row, expires) in &db_rows {
+            inner.insert_or_replace_row(db_row, *expires);
+        }
+
+        let db_rows: Vec<Arc<DbRow>> = db_rows.into_iter().map(|(db_row, _)| db_row).collect();
+        self.push_to_reader(&db_rows).await
+    }
+
+    fn stream_entities(&self, partition_key: Option<String>) -> WriterEntitiesStream<'_, TEntity> {
+        let entities = futures::stream::once(async move {
+            match partition_key {
+                Some(partition_key) => {
+                    self.get_by_partition_key(partition_key.as_str(), None)
+                        .await
+                }
+                None => self.get_all().await,
+            }
+        });
+
+        Box::pin(entities.flat_map(|entities| {
+            let items: Vec<Result<TEntity, DataWriterError>> = match entities {
+                Ok(entities) => entities.unwrap_or_default().into_iter().map(Ok).collect(),
+                Err(err) => vec![Err(err)],
+            };
+
+            futures::stream::iter(items)
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send + 'static>
+    MyNoSqlDataWriterApi<TEntity> for MyNoSqlDataWriterMock<TEntity>
+{
+    /// Table of the mock exists from the start, so writes do not require it to be created
+    async fn create_table(&self, _params: CreateTableParams) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        if inner.table_is_created {
+            return Err(DataWriterError::TableAlreadyExists(
+                TEntity::TABLE_NAME.to_string(),
+            ));
+        }
+
+        inner.table_is_created = true;
+        Ok(())
+    }
+
+    async fn create_table_if_not_exists(
+        &self,
+        _params: &CreateTableParams,
+    ) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+        inner.table_is_created = true;
+        Ok(())
+    }
+
+    async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        let (db_row, expires) = to_db_row(entity, &JsonTimeStamp::now())?;
+
+        let mut inner = self.lock().await;
+
+        if !inner.insert_row(&db_row, expires) {
+            return Err(DataWriterError::RecordAlreadyExists(format!(
+                "Record with PartitionKey: {} and RowKey: {} already exists",
+                db_row.get_partition_key(),
+                db_row.get_row_key()
+            )));
+        }
+
+        self.push_to_reader(&[db_row]).await
+    }
+
+    async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+        self.bulk_insert_or_replace_rows(&mut inner, std::slice::from_ref(entity))
+            .await
+    }
+
+    async fn bulk_insert_or_replace(&self, entities: &[TEntity]) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+        self.bulk_insert_or_replace_rows(&mut inner, entities).await
+    }
+
+    async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        _update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let inner = self.lock().await;
+
+        let db_row = inner
+            .table
+            .get_partition(partition_key)
+            .and_then(|db_partition| db_partition.get_row(row_key));
+
+        match db_row {
+            Some(db_row) => Ok(Some(to_entity(db_row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        _update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let inner = self.lock().await;
+
+        let db_partition = match inner.table.get_partition(partition_key) {
+            Some(db_partition) => db_partition,
+            None => return Ok(None),
+        };
+
+        let mut result = Vec::with_capacity(db_partition.get_rows_amount());
+
+        for db_row in db_partition.get_all_rows() {
+            result.push(to_entity(db_row)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn get_by_partition_key_lenient(
+        &self,
+        partition_key: &str,
+        _update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        let inner = self.lock().await;
+
+        let db_partition = match inner.table.get_partition(partition_key) {
+            Some(db_partition) => db_partition,
+            None => return Ok(None),
+        };
+
+        Ok(Some(to_lenient_entities(db_partition.get_all_rows())))
+    }
+
+    async fn get_by_row_key(&self, row_key: &str) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let inner = self.lock().await;
+
+        let mut result = Vec::new();
+
+        for (_, db_row) in inner.table.get_by_row_key(row_key, None, None) {
+            result.push(to_entity(db_row)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn get_by_row_key_lenient(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        let inner = self.lock().await;
+
+        Ok(Some(to_lenient_entities(
+            inner
+                .table
+                .get_by_row_key(row_key, None, None)
+                .map(|(_, db_row)| db_row),
+        )))
+    }
+
+    async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        let inner = self.lock().await;
+
+        let skip = skip.unwrap_or(0).max(0) as usize;
+        let limit = match limit {
+            Some(limit) => limit.max(0) as usize,
+            None => usize::MAX,
+        };
+
+        Ok(inner
+            .table
+            .get_partitions()
+            .skip(skip)
+            .take(limit)
+            .map(|db_partition| db_partition.partition_key.to_string())
+            .collect())
+    }
+
+    async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let inner = self.lock().await;
+
+        let mut result = Vec::with_capacity(inner.table.get_rows_amount());
+
+        for (_, db_row) in inner.table.get_all_rows(None, None) {
+            result.push(to_entity(db_row)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn get_all_lenient(&self) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        let inner = self.lock().await;
+
+        Ok(Some(to_lenient_entities(
+            inner
+                .table
+                .get_all_rows(None, None)
+                .map(|(_, db_row)| db_row),
+        )))
+    }
+
+    async fn query(
+        &self,
+        partition_key: Option<&str>,
+        filter: &str,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let filter = FilterExpression::parse(filter)?;
+        let limit = limit.map(|limit| limit.max(0) as usize);
+
+        let inner = self.lock().await;
+
+        let db_rows: Vec<&Arc<DbRow>> = match partition_key {
+            Some(partition_key) => match inner.table.get_partition(partition_key) {
+                Some(db_partition) => db_partition
+                    .get_all_rows()
+                    .filter(|db_row| filter.is_match_db_row(db_row))
+                    .take(limit.unwrap_or(usize::MAX))
+                    .collect(),
+                None => vec![],
+            },
+            None => inner
+                .table
+                .query(&filter, limit)
+                .map(|(_, db_row)| db_row)
+                .collect(),
+        };
+
+        let mut result = Vec::with_capacity(db_rows.len());
+
+        for db_row in db_rows {
+            result.push(to_entity(db_row)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Rows of the mock are in memory, so page_size is not used
+    fn stream_all(&self, _page_size: usize) -> WriterEntitiesStream<'_, TEntity> {
+        self.stream_entities(None)
+    }
+
+    fn stream_partition(
+        &self,
+        partition_key: &str,
+        _page_size: usize,
+    ) -> WriterEntitiesStream<'_, TEntity> {
+        self.stream_entities(Some(partition_key.to_string()))
+    }
+
+    async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        let mut inner = self.lock().await;
+
+        let removed_row = match inner.remove_row(partition_key, row_key) {
+            Some(removed_row) => removed_row,
+            None => return Ok(None),
+        };
+
+        self.delete_from_reader(std::slice::from_ref(&removed_row))
+            .await;
+
+        Ok(Some(to_entity(&removed_row)?))
+    }
+
+    async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        let mut removed_rows = Vec::new();
+
+        for partition_key in partition_keys {
+            removed_rows.extend(inner.remove_partition(partition_key));
+        }
+
+        self.delete_from_reader(&removed_rows).await;
+        Ok(())
+    }
+
+    async fn bulk_delete_rows(&self, rows: &[(&str, &str)]) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        let mut removed_rows = Vec::new();
+
+        for (partition_key, row_key) in rows {
+            if let Some(removed_row) = inner.remove_row(partition_key, row_key) {
+                removed_rows.push(removed_row);
+            }
+        }
+
+        self.delete_from_reader(&removed_rows).await;
+        Ok(())
+    }
+
+    /// Rows are removed before the next operation after the moment. None removes the expiration
+    async fn set_rows_expiration(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        for row_key in row_keys {
+            let row_exists = inner
+                .table
+                .get_partition(partition_key)
+                .and_then(|db_partition| db_partition.get_row(row_key))
+                .is_some();
+
+            if row_exists {
+                inner.set_row_expires(partition_key, row_key, expires);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Partitions are removed before the next operation after the moment. None removes the expiration
+    async fn set_partitions_expiration(
+        &self,
+        partition_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        for partition_key in partition_keys {
+            if inner.table.get_partition(partition_key).is_none() {
+                continue;
+            }
+
+            match expires {
+                Some(expires) => {
+                    inner
+                        .partitions_expires
+                        .insert(partition_key.to_string(), expires);
+                }
+                None => {
+                    inner.partitions_expires.remove(*partition_key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        let removed_rows = inner.remove_all();
+        self.delete_from_reader(&removed_rows).await;
+
+        self.bulk_insert_or_replace_rows(&mut inner, entities).await
+    }
+
+    async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        let mut inner = self.lock().await;
+
+        let removed_rows = inner.remove_partition(partition_key);
+        self.delete_from_reader(&removed_rows).await;
+
+        self.bulk_insert_or_replace_rows(&mut inner, entities).await
+    }
+}
+
+/// Expires of the entity is returned along with the row
+fn to_db_row<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    entity: &TEntity,
+    now: &JsonTimeStamp,
+) -> Result<(Arc<DbRow>, Option<DateTimeAsMicroseconds>), DataWriterError> {
+    let raw = super::execution::serialize_entity(entity)?;
+
+    let expires = match DbJsonEntity::from_slice(raw.as_slice()) {
+        Ok(db_json_entity) => db_json_entity.expires_value,
+        Err(err) => {
+            return Err(DataWriterError::ServerCouldNotParseJson(format!(
+                "{:?}",
+                err
+            )))
+        }
+    };
+
+    match DbJsonEntity::parse_into_db_row(JsonFirstLineIterator::new(raw.as_slice()), now) {
+        Ok(db_row) => Ok((Arc::new(db_row), expires)),
+        Err(err) => Err(DataWriterError::ServerCouldNotParseJson(format!(
+            "{:?}",
+            err
+        ))),
+    }
+}
+
+fn to_entity<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    db_row: &DbRow,
+) -> Result<TEntity, DataWriterError> {
+    super::execution::deserialize_entity(db_row.get_src_as_slice())
+}
+
+fn to_lenient_entities<'s, TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer>(
+    db_rows: impl Iterator<Item = &'s Arc<DbRow>>,
+) -> LenientEntities<TEntity> {
+    let mut result = LenientEntities {
+        entities: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for db_row in db_rows {
+        match TEntity::deserialize_entity(db_row.get_src_as_slice()) {
+            Ok(entity) => result.entities.push(entity),
+            Err(reason) => result.failed.push(EntityDeserializationFail::new(
+                db_row.get_src_as_slice(),
+                reason,
+            )),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp};
+    use my_no_sql_tcp_reader::{MyNoSqlDataReader, MyNoSqlDataReaderMock};
+    use rust_extensions::date_time::DateTimeAsMicroseconds;
+    use serde::Serialize;
+    use serde_derive::Deserialize;
+
+    use crate::{CreateTableParams, DataWriterError, MyNoSqlDataWriterApi};
+
+    use super::MyNoSqlDataWriterMock;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TestEntity {
+        partition_key: String,
+        row_key: String,
+        value: i32,
+    }
+
+    impl MyNoSqlEntity for TestEntity {
+        const TABLE_NAME: &'static str = "test";
+        const LAZY_DESERIALIZATION: bool = false;
+
+        fn get_partition_key(&self) -> &str {
+            &self.partition_key
+        }
+
+        fn get_row_key(&self) -> &str {
+            &self.row_key
+        }
+
+        fn get_time_stamp(&self) -> Timestamp {
+            Timestamp::default()
+        }
+    }
+
+    impl MyNoSqlEntitySerializer for TestEntity {
+        fn serialize_entity(&self) -> Vec<u8> {
+            my_no_sql_core::entity_serializer::serialize(self)
+        }
+
+        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+            my_no_sql_core::entity_serializer::deserialize(src)
+        }
+    }
+
+    fn create_entity(partition_key: &str, row_key: &str, value: i32) -> TestEntity {
+        TestEntity {
+            partition_key: partition_key.to_string(),
+            row_key: row_key.to_string(),
+            value,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_twice() {
+        let writer = MyNoSqlDataWriterMock::<TestEntity>::new();
+
+        writer
+            .insert_entity(&create_entity("pk", "rk", 1))
+            .await
+            .unwrap();
+
+        let result = writer.insert_entity(&create_entity("pk", "rk", 2)).await;
+
+        assert!(matches!(
+            result,
+            Err(DataWriterError::RecordAlreadyExists(_))
+        ));
+
+        let entity = writer.get_entity("pk", "rk", None).await.unwrap().unwrap();
+        assert_eq!(1, entity.value);
+    }
+
+    #[tokio::test]
+    async fn test_expired_rows_and_partitions_are_removed() {
+        let reader = MyNoSqlDataReaderMock::<TestEntity>::new();
+        let writer = MyNoSqlDataWriterMock::new_with_reader(&reader);
+
+        writer
+            .bulk_insert_or_replace(&[
+                create_entity("pk1", "rk1", 1),
+                create_entity("pk1", "rk2", 2),
+                create_entity("pk2", "rk1", 3),
+            ])
+            .await
+            .unwrap();
+
+        let now = DateTimeAsMicroseconds::now();
+        let expired = DateTimeAsMicroseconds::new(now.unix_microseconds - 1_000_000);
+        let not_expired = now.add(Duration::from_secs(60));
+
+        writer
+            .set_rows_expiration("pk1", &["rk1"], Some(expired))
+            .await
+            .unwrap();
+        writer
+            .set_rows_expiration("pk1", &["rk2"], Some(not_expired))
+            .await
+            .unwrap();
+
+        assert!(writer
+            .get_entity("pk1", "rk1", None)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(writer
+            .get_entity("pk1", "rk2", None)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(reader.get_entity("pk1", "rk1").await.is_none());
+
+        writer
+            .set_partitions_expiration(&["pk2"], Some(expired))
+            .await
+            .unwrap();
+
+        assert!(writer
+            .get_by_partition_key("pk2", None)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(reader.get_by_partition_key_as_vec("pk2").await.is_none());
+        assert_eq!(1, writer.get_rows_amount().await);
+    }
+
+    #[tokio::test]
+    async fn test_query_lenient_and_stream() {
+        let writer = MyNoSqlDataWriterMock::<TestEntity>::new();
+
+        writer
+            .bulk_insert_or_replace(&[
+                create_entity("pk1", "rk1", 1),
+                create_entity("pk1", "rk2", 2),
+                create_entity("pk2", "rk1", 3),
+            ])
+            .await
+            .unwrap();
+
+        let result = writer
+            .query(None, "Value > 1", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, result.len());
+
+        let result = writer
+            .query(Some("pk1"), "Value > 1", None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, result.len());
+        assert_eq!("rk2", result[0].row_key);
+
+        assert!(matches!(
+            writer.query(None, "Value >", None).await,
+            Err(DataWriterError::InvalidFilter(_))
+        ));
+
+        let result = writer.get_all_lenient().await.unwrap().unwrap();
+        assert_eq!(3, result.entities.len());
+        assert!(!result.has_failed());
+
+        let result: Vec<TestEntity> = writer
+            .stream_partition("pk1", 1)
+            .map(|itm| itm.unwrap())
+            .collect()
+            .await;
+        assert_eq!(2, result.len());
+    }
+
+    #[tokio::test]
+    async fn test_create_table() {
+        let writer = MyNoSqlDataWriterMock::<TestEntity>::new();
+
+        let params = CreateTableParams {
+            persist: true,
+            max_partitions_amount: None,
+            max_rows_per_partition_amount: None,
+            max_table_size_bytes: None,
+        };
+
+        writer.create_table_if_not_exists(&params).await.unwrap();
+
+        assert!(matches!(
+            writer.create_table(params).await,
+            Err(DataWriterError::TableAlreadyExists(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_visible_through_reader() {
+        let reader = MyNoSqlDataReaderMock::<TestEntity>::new();
+        let writer = MyNoSqlDataWriterMock::new_with_reader(&reader);
+
+        writer
+            .bulk_insert_or_replace(&[
+                create_entity("pk1", "rk1", 1),
+                create_entity("pk1", "rk2", 2),
+                create_entity("pk2", "rk1", 3),
+            ])
+            .await
+            .unwrap();
+
+        let entity = reader.get_entity("pk1", "rk2").await.unwrap();
+        assert_eq!(2, entity.value);
+
+        writer.delete_row("pk1", "rk2").await.unwrap();
+        assert!(reader.get_entity("pk1", "rk2").await.is_none());
+
+        writer.delete_partitions(&["pk2"]).await.unwrap();
+        assert!(reader.get_by_partition_key_as_vec("pk2").await.is_none());
+
+        assert_eq!(
+            vec!["pk1".to_string()],
+            writer.get_partition_keys(None, None).await.unwrap()
+        );
+        assert_eq!(1, writer.get_rows_amount().await);
+    }
+}
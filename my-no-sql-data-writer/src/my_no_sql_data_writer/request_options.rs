@@ -0,0 +1,115 @@
+use std::{future::Future, time::Duration};
+
+use my_no_sql_abstractions::DataSynchronizationPeriod;
+
+use super::{DataWriterError, WriterRequest};
+
+/// Overrides of the writer settings applied to a single call
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    pub sync_period: Option<DataSynchronizationPeriod>,
+    pub timeout: Option<Duration>,
+    pub headers: Vec<(String, String)>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sync_period(mut self, sync_period: DataSynchronizationPeriod) -> Self {
+        self.sync_period = Some(sync_period);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn headers(
+        mut self,
+        headers: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        for (name, value) in headers {
+            self.headers.push((name.into(), value.into()));
+        }
+        self
+    }
+
+    pub fn get_sync_period(&self, default: DataSynchronizationPeriod) -> DataSynchronizationPeriod {
+        self.sync_period.unwrap_or(default)
+    }
+
+    pub fn fill_fields(&self, mut fl_url_request: WriterRequest) -> WriterRequest {
+        for (name, value) in &self.headers {
+            fl_url_request = fl_url_request.with_header(name.as_str(), value.as_str());
+        }
+
+        fl_url_request
+    }
+
+    pub async fn execute<TResult>(
+        &self,
+        operation: impl Future<Output = Result<TResult, DataWriterError>>,
+    ) -> Result<TResult, DataWriterError> {
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return operation.await,
+        };
+
+        match tokio::time::timeout(timeout, operation).await {
+            Ok(result) => result,
+            Err(_) => Err(DataWriterError::Timeout(timeout)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use my_no_sql_abstractions::DataSynchronizationPeriod;
+
+    use crate::DataWriterError;
+
+    use super::RequestOptions;
+
+    #[test]
+    fn test_sync_period_is_overridden() {
+        let options = RequestOptions::new();
+        assert_eq!(
+            "5",
+            options
+                .get_sync_period(DataSynchronizationPeriod::Sec5)
+                .as_str()
+        );
+
+        let options = options.sync_period(DataSynchronizationPeriod::Immediately);
+        assert_eq!(
+            "i",
+            options
+                .get_sync_period(DataSynchronizationPeriod::Sec5)
+                .as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let options = RequestOptions::new().timeout(Duration::from_millis(10));
+
+        let result: Result<(), DataWriterError> = options
+            .execute(async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(())
+            })
+            .await;
+
+        assert!(matches!(result, Err(DataWriterError::Timeout(_))));
+    }
+}
@@ -0,0 +1,114 @@
+use super::WriterHttpRequest;
+
+pub const TRACE_ID_HEADER: &str = "X-Trace-Id";
+
+#[cfg(feature = "tracing")]
+static TRACE_ID_PROVIDER: std::sync::OnceLock<fn() -> Option<String>> = std::sync::OnceLock::new();
+
+/// Overrides the X-Trace-Id of the outgoing request.
+/// By default the OpenTelemetry trace id of the operation span is sent. Can be set once per application.
+#[cfg(feature = "tracing")]
+pub fn set_trace_id_provider(provider: fn() -> Option<String>) -> bool {
+    TRACE_ID_PROVIDER.set(provider).is_ok()
+}
+
+#[cfg(feature = "tracing")]
+struct HeadersInjector<'s>(&'s mut Vec<(String, String)>);
+
+#[cfg(feature = "tracing")]
+impl<'s> opentelemetry::propagation::Injector for HeadersInjector<'s> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.push((key.to_string(), value));
+    }
+}
+
+/// Headers of the global propagator (W3C traceparent) are taken from the context of the current span.
+/// Called right before the request is sent, so the span of the writer operation is the current one
+pub(crate) fn inject_trace_headers(request: &mut WriterHttpRequest) {
+    #[cfg(feature = "tracing")]
+    {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = tracing::Span::current().context();
+
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&context, &mut HeadersInjector(&mut request.headers))
+        });
+
+        let trace_id = match TRACE_ID_PROVIDER.get() {
+            Some(provider) => provider(),
+            None => {
+                let span = context.span();
+                let span_context = span.span_context();
+
+                if span_context.is_valid() {
+                    Some(span_context.trace_id().to_string())
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let Some(trace_id) = trace_id {
+            request
+                .headers
+                .push((TRACE_ID_HEADER.to_string(), trace_id));
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = request;
+}
+
+/// Runs the writer operation inside of its span: writer_operation!("get_all", [table = TEntity::TABLE_NAME], async move { ... }).
+/// Span has the name my_no_sql.{operation} and the status and bytes fields which are recorded while the request is executed
+macro_rules! writer_operation {
+    ($operation:literal, [$($field:ident = $value:expr),* $(,)?], $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let body = {
+            use tracing::Instrument;
+
+            let span = tracing::info_span!(
+                concat!("my_no_sql.", $operation),
+                operation = $operation,
+                $($field = $value,)*
+                status = tracing::field::Empty,
+                bytes = tracing::field::Empty,
+            );
+
+            $body.instrument(span)
+        };
+
+        #[cfg(not(feature = "tracing"))]
+        let body = $body;
+
+        body.await
+    }};
+}
+
+pub(crate) use writer_operation;
+
+/// Status code is recorded to the span of the writer operation
+pub(crate) fn record_status(status_code: u16) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("status", status_code);
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = status_code;
+}
+
+/// Size of the request body or of the received response
+pub(crate) fn record_bytes(bytes: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("bytes", bytes);
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = bytes;
+}
+
+pub(crate) fn record_body(body: &Option<Vec<u8>>) {
+    if let Some(body) = body {
+        record_bytes(body.len());
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use super::DataWriterError;
+
+pub const DEFAULT_RETRY_INITIAL_DELAY: Duration = Duration::from_millis(100);
+pub const DEFAULT_RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    Retry,
+    Fail,
+}
+
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: usize,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: u32,
+    pub jitter: bool,
+    pub deadline: Option<Duration>,
+    classifier: Arc<dyn Fn(&DataWriterError) -> RetryDecision + Send + Sync + 'static>,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts,
+            initial_delay: DEFAULT_RETRY_INITIAL_DELAY,
+            max_delay: DEFAULT_RETRY_MAX_DELAY,
+            multiplier: 2,
+            jitter: true,
+            deadline: None,
+            classifier: Arc::new(default_classifier),
+        }
+    }
+
+    pub fn with_backoff(mut self, initial_delay: Duration, max_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_classifier(
+        mut self,
+        classifier: impl Fn(&DataWriterError) -> RetryDecision + Send + Sync + 'static,
+    ) -> Self {
+        self.classifier = Arc::new(classifier);
+        self
+    }
+
+    pub fn classify(&self, err: &DataWriterError) -> RetryDecision {
+        (self.classifier)(err)
+    }
+
+    /// Delay before the attempt with the given number. Attempts start from 1.
+    pub fn get_delay(&self, attempt_no: usize) -> Duration {
+        let mut delay = self.initial_delay;
+
+        for _ in 1..attempt_no {
+            delay = delay.saturating_mul(self.multiplier);
+            if delay >= self.max_delay {
+                delay = self.max_delay;
+                break;
+            }
+        }
+
+        if !self.jitter {
+            return delay;
+        }
+
+        let half = delay / 2;
+        let half_as_micros = half.as_micros() as u64;
+
+        if half_as_micros == 0 {
+            return delay;
+        }
+
+        half + Duration::from_micros(get_random() % half_as_micros)
+    }
+
+    pub async fn execute<TResult, TFuture: Future<Output = Result<TResult, DataWriterError>>>(
+        &self,
+        mut operation: impl FnMut() -> TFuture,
+    ) -> Result<TResult, DataWriterError> {
+        let started = Instant::now();
+        let mut attempt_no = 1;
+
+        loop {
+            let err = match operation().await {
+                Ok(result) => return Ok(result),
+                Err(err) => err,
+            };
+
+            if attempt_no >= self.max_attempts {
+                return Err(err);
+            }
+
+            if self.classify(&err) == RetryDecision::Fail {
+                return Err(err);
+            }
+
+            let delay = self.get_delay(attempt_no);
+
+            if let Some(deadline) = self.deadline {
+                if started.elapsed() + delay > deadline {
+                    return Err(err);
+                }
+            }
+
+            tokio::time::sleep(delay).await;
+            attempt_no += 1;
+        }
+    }
+}
+
+pub fn default_classifier(err: &DataWriterError) -> RetryDecision {
+    if err.is_retryable() {
+        RetryDecision::Retry
+    } else {
+        RetryDecision::Fail
+    }
+}
+
+fn get_random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_i64(DateTimeAsMicroseconds::now().unix_microseconds);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use crate::DataWriterError;
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn test_backoff_without_jitter() {
+        let policy = RetryPolicy::new(10)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .with_jitter(false);
+
+        assert_eq!(Duration::from_millis(100), policy.get_delay(1));
+        assert_eq!(Duration::from_millis(200), policy.get_delay(2));
+        assert_eq!(Duration::from_millis(400), policy.get_delay(3));
+        assert_eq!(Duration::from_millis(500), policy.get_delay(4));
+        assert_eq!(Duration::from_millis(500), policy.get_delay(9));
+    }
+
+    #[test]
+    fn test_backoff_with_jitter_stays_in_range() {
+        let policy = RetryPolicy::new(10)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(500));
+
+        for attempt_no in 1..5 {
+            let delay = policy.get_delay(attempt_no);
+            assert!(delay >= Duration::from_millis(50));
+            assert!(delay <= Duration::from_millis(500));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permanent_error_is_not_retried() {
+        let policy = RetryPolicy::new(5).with_backoff(Duration::ZERO, Duration::ZERO);
+        let attempts = AtomicUsize::new(0);
+        let attempts = &attempts;
+
+        let result: Result<(), DataWriterError> = policy
+            .execute(move || async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(DataWriterError::TableNotFound("test".to_string()))
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+    }
+}
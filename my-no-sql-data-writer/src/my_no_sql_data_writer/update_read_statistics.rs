@@ -1,6 +1,7 @@
-use flurl::FlUrl;
 use rust_extensions::date_time::DateTimeAsMicroseconds;
 
+use super::WriterRequest;
+
 pub struct UpdateReadStatistics {
     pub update_partition_read_access: bool,
     pub update_row_read_access: bool,
@@ -9,7 +10,7 @@ pub struct UpdateReadStatistics {
 }
 
 impl UpdateReadStatistics {
-    pub fn fill_fields(&self, mut fl_url_request: FlUrl) -> FlUrl {
+    pub fn fill_fields(&self, mut fl_url_request: WriterRequest) -> WriterRequest {
         if self.update_partition_read_access {
             fl_url_request = fl_url_request.with_header("updatePartitionLastReadTime", "true");
         }
@@ -0,0 +1,785 @@
+use std::{marker::PhantomData, time::Duration};
+
+use futures::Stream;
+use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_core::query::FilterExpression;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::{
+    DataWriterError, MyNoSqlOutbox, OutboxOperation, OutboxOperationType, UpdateReadStatistics,
+};
+
+use super::{
+    BulkChunksSettings, BulkWriteResult, CreateTableParams, FlUrlFactory, LenientEntities,
+    MyNoSqlDataWriter, RequestOptions, WriterRequest,
+};
+
+/// Writer which applies the same RequestOptions to every call.
+/// Created by MyNoSqlDataWriter::with_options. Calls go through the outbox, bulk chunks and validation rules of the writer.
+/// Operations replayed from the outbox later are sent without the headers and the timeout
+pub struct MyNoSqlDataWriterWithOptions<'s, TEntity: MyNoSqlEntity + Sync + Send> {
+    fl_url_factory: &'s FlUrlFactory,
+    outbox: Option<&'s MyNoSqlOutbox>,
+    bulk_chunks: Option<&'s BulkChunksSettings>,
+    sync_period: DataSynchronizationPeriod,
+    options: RequestOptions,
+    phantom: PhantomData<TEntity>,
+}
+
+impl<'s, TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
+    MyNoSqlDataWriterWithOptions<'s, TEntity>
+{
+    pub fn new(writer: &'s MyNoSqlDataWriter<TEntity>, options: RequestOptions) -> Self {
+        Self::from_parts(
+            &writer.fl_url_factory,
+            writer.outbox.as_deref(),
+            writer.bulk_chunks.as_ref(),
+            writer.sync_period,
+            options,
+        )
+    }
+
+    /// Writer with retries keeps the parts of the writer it was created from
+    pub(crate) fn from_parts(
+        fl_url_factory: &'s FlUrlFactory,
+        outbox: Option<&'s MyNoSqlOutbox>,
+        bulk_chunks: Option<&'s BulkChunksSettings>,
+        sync_period: DataSynchronizationPeriod,
+        options: RequestOptions,
+    ) -> Self {
+        Self {
+            fl_url_factory,
+            outbox,
+            bulk_chunks,
+            sync_period,
+            options,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn sync_period(mut self, sync_period: DataSynchronizationPeriod) -> Self {
+        self.options = self.options.sync_period(sync_period);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options = self.options.timeout(timeout);
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options = self.options.header(name, value);
+        self
+    }
+
+    pub fn headers(
+        mut self,
+        headers: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.options = self.options.headers(headers);
+        self
+    }
+
+    pub fn get_options(&self) -> &RequestOptions {
+        &self.options
+    }
+
+    fn get_sync_period(&self) -> DataSynchronizationPeriod {
+        self.options.get_sync_period(self.sync_period)
+    }
+
+    async fn get_writer_request(&self) -> Result<(WriterRequest, String), DataWriterError> {
+        let (fl_url, url) = self.fl_url_factory.get_writer_request().await?;
+        Ok((self.options.fill_fields(fl_url), url))
+    }
+
+    async fn execute_or_enqueue(
+        &self,
+        outbox: &MyNoSqlOutbox,
+        operation: OutboxOperationType,
+        partition_key: Option<&str>,
+        row_key: Option<&str>,
+        payload: Option<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, DataWriterError> {
+        let operation = OutboxOperation::new(
+            operation,
+            TEntity::TABLE_NAME,
+            self.get_sync_period(),
+            partition_key,
+            row_key,
+            payload,
+        )?;
+
+        outbox
+            .execute_or_enqueue(self.fl_url_factory, operation, &self.options)
+            .await
+    }
+
+    pub async fn create_table(&self, params: CreateTableParams) -> Result<(), DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, url) = self.get_writer_request().await?;
+                super::execution::create_table(
+                    fl_url,
+                    url.as_str(),
+                    TEntity::TABLE_NAME,
+                    params,
+                    &self.get_sync_period(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn create_table_if_not_exists(
+        &self,
+        params: &CreateTableParams,
+    ) -> Result<(), DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, url) = self.get_writer_request().await?;
+                super::execution::create_table_if_not_exists(
+                    fl_url,
+                    url.as_str(),
+                    TEntity::TABLE_NAME,
+                    params,
+                    self.get_sync_period(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.fl_url_factory
+            .validate_entities(std::slice::from_ref(entity))?;
+
+        if let Some(outbox) = self.outbox {
+            self.execute_or_enqueue(
+                outbox,
+                OutboxOperationType::Insert,
+                Some(entity.get_partition_key()),
+                Some(entity.get_row_key()),
+                Some(super::execution::serialize_entity(entity)?),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::insert_entity(fl_url, entity, &self.get_sync_period()).await
+            })
+            .await
+    }
+
+    pub async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
+        self.fl_url_factory
+            .validate_entities(std::slice::from_ref(entity))?;
+
+        if let Some(outbox) = self.outbox {
+            self.execute_or_enqueue(
+                outbox,
+                OutboxOperationType::InsertOrReplace,
+                Some(entity.get_partition_key()),
+                Some(entity.get_row_key()),
+                Some(super::execution::serialize_entity(entity)?),
+            )
+            .await?;
+            return Ok(());
+        }
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::insert_or_replace_entity(fl_url, entity, &self.get_sync_period())
+                    .await
+            })
+            .await
+    }
+
+    pub async fn bulk_insert_or_replace(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.fl_url_factory.validate_entities(entities)?;
+
+        if let Some(outbox) = self.outbox {
+            if entities.is_empty() {
+                return Ok(());
+            }
+
+            let bodies = match self.bulk_chunks {
+                Some(settings) => super::split_into_chunks(entities, settings)?
+                    .into_iter()
+                    .map(|chunk| chunk.body)
+                    .collect(),
+                None => super::execution::serialize_entities_to_body(entities)?
+                    .into_iter()
+                    .collect(),
+            };
+
+            // Chunks are enqueued one by one, so they reach the server in order
+            for body in bodies {
+                self.execute_or_enqueue(
+                    outbox,
+                    OutboxOperationType::BulkInsertOrReplace,
+                    None,
+                    None,
+                    Some(body),
+                )
+                .await?;
+            }
+
+            return Ok(());
+        }
+
+        if let Some(settings) = self.bulk_chunks {
+            return self
+                .bulk_insert_or_replace_chunked(entities, settings)
+                .await
+                .into_result();
+        }
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::bulk_insert_or_replace(
+                    fl_url,
+                    entities,
+                    &self.get_sync_period(),
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+
+    /// Timeout is applied to every chunk
+    pub async fn bulk_insert_or_replace_chunked(
+        &self,
+        entities: &[TEntity],
+        settings: &BulkChunksSettings,
+    ) -> BulkWriteResult {
+        super::bulk_insert_or_replace_chunked(
+            self.fl_url_factory,
+            entities,
+            &self.get_sync_period(),
+            settings,
+            &self.options,
+        )
+        .await
+    }
+
+    pub async fn get_entity(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_entity(
+                    fl_url,
+                    partition_key,
+                    row_key,
+                    update_read_statistics.as_ref(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_by_partition_key(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_by_partition_key(
+                    fl_url,
+                    partition_key,
+                    update_read_statistics.as_ref(),
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_by_partition_key_lenient(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_by_partition_key_lenient(
+                    fl_url,
+                    partition_key,
+                    update_read_statistics.as_ref(),
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_enum_case_models_by_partition_key<
+        TResult: MyNoSqlEntity
+            + my_no_sql_abstractions::GetMyNoSqlEntitiesByPartitionKey
+            + From<TEntity>
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<Vec<TResult>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_enum_case_models_by_partition_key(
+                    fl_url,
+                    update_read_statistics.as_ref(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_enum_case_model<
+        TResult: MyNoSqlEntity
+            + From<TEntity>
+            + my_no_sql_abstractions::GetMyNoSqlEntity
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_enum_case_model(fl_url, update_read_statistics.as_ref()).await
+            })
+            .await
+    }
+
+    pub async fn get_by_row_key(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_by_row_key(fl_url, row_key).await
+            })
+            .await
+    }
+
+    pub async fn get_by_row_key_lenient(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_by_row_key_lenient(fl_url, row_key).await
+            })
+            .await
+    }
+
+    pub async fn get_partition_keys(
+        &self,
+        skip: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<String>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_partition_keys(fl_url, TEntity::TABLE_NAME, skip, limit).await
+            })
+            .await
+    }
+
+    pub async fn delete_enum_case<
+        TResult: MyNoSqlEntity
+            + From<TEntity>
+            + my_no_sql_abstractions::GetMyNoSqlEntity
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::delete_enum_case(fl_url).await
+            })
+            .await
+    }
+
+    pub async fn delete_enum_case_with_row_key<
+        TResult: MyNoSqlEntity
+            + From<TEntity>
+            + my_no_sql_abstractions::GetMyNoSqlEntitiesByPartitionKey
+            + Sync
+            + Send
+            + 'static,
+    >(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<TResult>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::delete_enum_case_with_row_key(fl_url, row_key).await
+            })
+            .await
+    }
+
+    pub async fn delete_row(
+        &self,
+        partition_key: &str,
+        row_key: &str,
+    ) -> Result<Option<TEntity>, DataWriterError> {
+        if let Some(outbox) = self.outbox {
+            let deleted = self
+                .execute_or_enqueue(
+                    outbox,
+                    OutboxOperationType::DeleteRow,
+                    Some(partition_key),
+                    Some(row_key),
+                    None,
+                )
+                .await?;
+
+            return match deleted {
+                Some(payload) => Ok(Some(super::execution::deserialize_entity(
+                    payload.as_slice(),
+                )?)),
+                None => Ok(None),
+            };
+        }
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::delete_row(fl_url, partition_key, row_key).await
+            })
+            .await
+    }
+
+    pub async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::delete_partitions(fl_url, TEntity::TABLE_NAME, partition_keys)
+                    .await
+            })
+            .await
+    }
+
+    pub async fn bulk_delete_rows(&self, rows: &[(&str, &str)]) -> Result<(), DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::bulk_delete_rows(
+                    fl_url,
+                    TEntity::TABLE_NAME,
+                    rows,
+                    &self.get_sync_period(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn set_rows_expiration(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::set_rows_expiration(
+                    fl_url,
+                    TEntity::TABLE_NAME,
+                    partition_key,
+                    row_keys,
+                    expires,
+                    &self.get_sync_period(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn set_partitions_expiration(
+        &self,
+        partition_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::set_partitions_expiration(
+                    fl_url,
+                    TEntity::TABLE_NAME,
+                    partition_keys,
+                    expires,
+                    &self.get_sync_period(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_all(fl_url, self.fl_url_factory.get_compression()).await
+            })
+            .await
+    }
+
+    pub async fn query(
+        &self,
+        partition_key: Option<&str>,
+        filter: &str,
+        limit: Option<i32>,
+    ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
+        let filter = FilterExpression::parse(filter)?;
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::query(
+                    fl_url,
+                    partition_key,
+                    &filter,
+                    limit,
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_all_lenient(
+        &self,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::get_all_lenient(fl_url, self.fl_url_factory.get_compression())
+                    .await
+            })
+            .await
+    }
+
+    /// Timeout is applied to the request of every page
+    pub fn stream_all(
+        &self,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<TEntity, DataWriterError>> {
+        super::stream_entities(
+            self.fl_url_factory.clone(),
+            self.options.clone(),
+            None,
+            page_size,
+        )
+    }
+
+    pub fn stream_partition(
+        &self,
+        partition_key: &str,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<TEntity, DataWriterError>> {
+        super::stream_entities(
+            self.fl_url_factory.clone(),
+            self.options.clone(),
+            Some(partition_key.to_string()),
+            page_size,
+        )
+    }
+
+    pub async fn clean_table_and_bulk_insert(
+        &self,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.fl_url_factory.validate_entities(entities)?;
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::clean_table_and_bulk_insert(
+                    fl_url,
+                    entities,
+                    &self.get_sync_period(),
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn clean_partition_and_bulk_insert(
+        &self,
+        partition_key: &str,
+        entities: &[TEntity],
+    ) -> Result<(), DataWriterError> {
+        self.fl_url_factory.validate_entities(entities)?;
+
+        self.options
+            .execute(async {
+                let (fl_url, _) = self.get_writer_request().await?;
+                super::execution::clean_partition_and_bulk_insert(
+                    fl_url,
+                    partition_key,
+                    entities,
+                    &self.get_sync_period(),
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use my_no_sql_abstractions::{
+        DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer, Timestamp,
+    };
+    use serde::Serialize;
+    use serde_derive::Deserialize;
+
+    use crate::{
+        BulkChunksSettings, DataWriterError, MyNoSqlDataWriter, MyNoSqlWriterSettings,
+        WriterHttpRequest, WriterHttpResponse, WriterTransport,
+    };
+
+    struct TestSettings;
+
+    #[async_trait::async_trait]
+    impl MyNoSqlWriterSettings for TestSettings {
+        async fn get_url(&self) -> String {
+            "http://localhost:5123".to_string()
+        }
+
+        fn get_app_name(&self) -> &'static str {
+            "test"
+        }
+
+        fn get_app_version(&self) -> &'static str {
+            "0.1.0"
+        }
+    }
+
+    #[derive(Default)]
+    struct TestTransport {
+        requests: Mutex<Vec<WriterHttpRequest>>,
+    }
+
+    #[async_trait::async_trait]
+    impl WriterTransport for TestTransport {
+        async fn execute(
+            &self,
+            request: WriterHttpRequest,
+        ) -> Result<WriterHttpResponse, DataWriterError> {
+            let url = request.get_url();
+            self.requests.lock().unwrap().push(request);
+
+            Ok(WriterHttpResponse {
+                url,
+                status_code: 200,
+                headers: Vec::new(),
+                body: vec![],
+            })
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TestEntity {
+        partition_key: String,
+        row_key: String,
+    }
+
+    impl MyNoSqlEntity for TestEntity {
+        const TABLE_NAME: &'static str = "test";
+        const LAZY_DESERIALIZATION: bool = false;
+
+        fn get_partition_key(&self) -> &str {
+            &self.partition_key
+        }
+
+        fn get_row_key(&self) -> &str {
+            &self.row_key
+        }
+
+        fn get_time_stamp(&self) -> Timestamp {
+            Timestamp::default()
+        }
+    }
+
+    impl MyNoSqlEntitySerializer for TestEntity {
+        fn serialize_entity(&self) -> Vec<u8> {
+            my_no_sql_core::entity_serializer::serialize(self)
+        }
+
+        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+            my_no_sql_core::entity_serializer::deserialize(src)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_chunks_of_writer_are_used() {
+        let transport = Arc::new(TestTransport::default());
+
+        let mut writer = MyNoSqlDataWriter::<TestEntity>::new(
+            Arc::new(TestSettings),
+            None,
+            DataSynchronizationPeriod::Sec1,
+        );
+        writer.set_transport(transport.clone());
+        writer.set_bulk_chunks(BulkChunksSettings::new().with_max_rows_per_chunk(1));
+
+        let entities: Vec<TestEntity> = (0..3)
+            .map(|no| TestEntity {
+                partition_key: "pk".to_string(),
+                row_key: no.to_string(),
+            })
+            .collect();
+
+        writer
+            .with_options()
+            .sync_period(DataSynchronizationPeriod::Immediately)
+            .header("x-request-id", "12345")
+            .bulk_insert_or_replace(&entities)
+            .await
+            .unwrap();
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(3, requests.len());
+
+        for request in requests.iter() {
+            assert_eq!(Some("12345"), request.get_header("x-request-id"));
+            assert!(request.get_url().contains("syncPeriod=i"));
+        }
+    }
+}
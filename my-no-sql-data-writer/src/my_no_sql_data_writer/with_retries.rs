@@ -1,16 +1,29 @@
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_core::db_json_entity::consts;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
 
-use crate::{DataWriterError, UpdateReadStatistics};
+use crate::{DataWriterError, MyNoSqlOutbox, UpdateReadStatistics};
 
-use super::fl_url_factory::FlUrlFactory;
+use super::{
+    fl_url_factory::FlUrlFactory, BulkChunksSettings, LenientEntities,
+    MyNoSqlDataWriterWithOptions, RequestOptions, RetryPolicy,
+};
 
 pub struct MyNoSqlDataWriterWithRetries<TEntity: MyNoSqlEntity + Sync + Send> {
     fl_url_factory: FlUrlFactory,
     sync_period: DataSynchronizationPeriod,
     phantom: PhantomData<TEntity>,
-    max_attempts: usize,
+    retry_policy: RetryPolicy,
+    outbox: Option<Arc<MyNoSqlOutbox>>,
+    bulk_chunks: Option<BulkChunksSettings>,
 }
 
 impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
@@ -19,36 +32,98 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     pub fn new(
         fl_url_factory: FlUrlFactory,
         sync_period: DataSynchronizationPeriod,
-        max_attempts: usize,
+        retry_policy: RetryPolicy,
     ) -> Self {
         Self {
             phantom: PhantomData,
             sync_period,
-
-            max_attempts,
+            retry_policy,
             fl_url_factory,
+            outbox: None,
+            bulk_chunks: None,
         }
     }
 
+    /// Bulk writes go through the outbox and the bulk chunks of the writer the retries are created for
+    pub(crate) fn set_outbox_and_bulk_chunks(
+        mut self,
+        outbox: Option<Arc<MyNoSqlOutbox>>,
+        bulk_chunks: Option<BulkChunksSettings>,
+    ) -> Self {
+        self.outbox = outbox;
+        self.bulk_chunks = bulk_chunks;
+        self
+    }
+
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Insert is not idempotent: the attempt which failed on transport level could have been applied by the server.
+    /// In that case the retry gets RecordAlreadyExists and we treat it as a success if the stored row is ours.
     pub async fn insert_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::insert_entity(fl_url, entity, &self.sync_period).await
+        self.fl_url_factory
+            .validate_entities(std::slice::from_ref(entity))?;
+
+        let may_be_applied = AtomicBool::new(false);
+        let may_be_applied = &may_be_applied;
+
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+
+                let err = match super::execution::insert_entity(fl_url, entity, &self.sync_period)
+                    .await
+                {
+                    Ok(()) => return Ok(()),
+                    Err(err) => err,
+                };
+
+                if let DataWriterError::RecordAlreadyExists(_) = &err {
+                    if may_be_applied.load(Ordering::SeqCst) && self.is_stored(entity).await? {
+                        return Ok(());
+                    }
+                }
+
+                if err.is_retryable() {
+                    may_be_applied.store(true, Ordering::SeqCst);
+                }
+
+                Err(err)
+            })
+            .await
     }
 
     pub async fn insert_or_replace_entity(&self, entity: &TEntity) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::insert_or_replace_entity(fl_url, entity, &self.sync_period).await
+        self.fl_url_factory
+            .validate_entities(std::slice::from_ref(entity))?;
+
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::insert_or_replace_entity(fl_url, entity, &self.sync_period).await
+            })
+            .await
     }
 
+    /// Call is the same as the one of the writer without retries: it goes through the outbox and the bulk chunks of the writer
     pub async fn bulk_insert_or_replace(
         &self,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::bulk_insert_or_replace(fl_url, entities, &self.sync_period).await
+        let writer = MyNoSqlDataWriterWithOptions::<TEntity>::from_parts(
+            &self.fl_url_factory,
+            self.outbox.as_deref(),
+            self.bulk_chunks.as_ref(),
+            self.sync_period,
+            RequestOptions::new(),
+        );
+
+        let writer = &writer;
+
+        self.retry_policy
+            .execute(move || writer.bulk_insert_or_replace(entities))
+            .await
     }
 
     pub async fn get_entity(
@@ -57,15 +132,14 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         row_key: &str,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<TEntity>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::get_entity(
-            fl_url,
-            partition_key,
-            row_key,
-            update_read_statistics.as_ref(),
-        )
-        .await
+        let update_read_statistics = update_read_statistics.as_ref();
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_entity(fl_url, partition_key, row_key, update_read_statistics)
+                    .await
+            })
+            .await
     }
 
     pub async fn get_by_partition_key(
@@ -73,14 +147,39 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         partition_key: &str,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::get_by_partition_key(
-            fl_url,
-            partition_key,
-            update_read_statistics.as_ref(),
-        )
-        .await
+        let update_read_statistics = update_read_statistics.as_ref();
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_by_partition_key(
+                    fl_url,
+                    partition_key,
+                    update_read_statistics,
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn get_by_partition_key_lenient(
+        &self,
+        partition_key: &str,
+        update_read_statistics: Option<UpdateReadStatistics>,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        let update_read_statistics = update_read_statistics.as_ref();
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_by_partition_key_lenient(
+                    fl_url,
+                    partition_key,
+                    update_read_statistics,
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
     }
 
     pub async fn get_enum_case_models_by_partition_key<
@@ -94,13 +193,17 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         &self,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<Vec<TResult>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::get_enum_case_models_by_partition_key(
-            fl_url,
-            update_read_statistics.as_ref(),
-        )
-        .await
+        let update_read_statistics = update_read_statistics.as_ref();
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_enum_case_models_by_partition_key(
+                    fl_url,
+                    update_read_statistics,
+                )
+                .await
+            })
+            .await
     }
 
     pub async fn get_enum_case_model<
@@ -114,18 +217,37 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         &self,
         update_read_statistics: Option<UpdateReadStatistics>,
     ) -> Result<Option<TResult>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::get_enum_case_model(fl_url, update_read_statistics.as_ref()).await
+        let update_read_statistics = update_read_statistics.as_ref();
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_enum_case_model(fl_url, update_read_statistics).await
+            })
+            .await
     }
 
     pub async fn get_by_row_key(
         &self,
         row_key: &str,
     ) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::get_by_row_key(fl_url, row_key).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_by_row_key(fl_url, row_key).await
+            })
+            .await
+    }
+
+    pub async fn get_by_row_key_lenient(
+        &self,
+        row_key: &str,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_by_row_key_lenient(fl_url, row_key).await
+            })
+            .await
     }
 
     pub async fn delete_enum_case<
@@ -138,9 +260,12 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
     >(
         &self,
     ) -> Result<Option<TResult>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::delete_enum_case(fl_url).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::delete_enum_case(fl_url).await
+            })
+            .await
     }
 
     pub async fn delete_enum_case_with_row_key<
@@ -154,9 +279,12 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         &self,
         row_key: &str,
     ) -> Result<Option<TResult>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::delete_enum_case_with_row_key(fl_url, row_key).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::delete_enum_case_with_row_key(fl_url, row_key).await
+            })
+            .await
     }
 
     pub async fn delete_row(
@@ -164,30 +292,120 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         partition_key: &str,
         row_key: &str,
     ) -> Result<Option<TEntity>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::delete_row(fl_url, partition_key, row_key).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::delete_row(fl_url, partition_key, row_key).await
+            })
+            .await
     }
 
     pub async fn delete_partitions(&self, partition_keys: &[&str]) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::delete_partitions(fl_url, TEntity::TABLE_NAME, partition_keys).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::delete_partitions(fl_url, TEntity::TABLE_NAME, partition_keys)
+                    .await
+            })
+            .await
+    }
+
+    pub async fn bulk_delete_rows(&self, rows: &[(&str, &str)]) -> Result<(), DataWriterError> {
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::bulk_delete_rows(
+                    fl_url,
+                    TEntity::TABLE_NAME,
+                    rows,
+                    &self.sync_period,
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn set_rows_expiration(
+        &self,
+        partition_key: &str,
+        row_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::set_rows_expiration(
+                    fl_url,
+                    TEntity::TABLE_NAME,
+                    partition_key,
+                    row_keys,
+                    expires,
+                    &self.sync_period,
+                )
+                .await
+            })
+            .await
+    }
+
+    pub async fn set_partitions_expiration(
+        &self,
+        partition_keys: &[&str],
+        expires: Option<DateTimeAsMicroseconds>,
+    ) -> Result<(), DataWriterError> {
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::set_partitions_expiration(
+                    fl_url,
+                    TEntity::TABLE_NAME,
+                    partition_keys,
+                    expires,
+                    &self.sync_period,
+                )
+                .await
+            })
+            .await
     }
 
     pub async fn get_all(&self) -> Result<Option<Vec<TEntity>>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::get_all(fl_url).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_all(fl_url, self.fl_url_factory.get_compression()).await
+            })
+            .await
+    }
+
+    pub async fn get_all_lenient(
+        &self,
+    ) -> Result<Option<LenientEntities<TEntity>>, DataWriterError> {
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_all_lenient(fl_url, self.fl_url_factory.get_compression())
+                    .await
+            })
+            .await
     }
 
     pub async fn clean_table_and_bulk_insert(
         &self,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::clean_table_and_bulk_insert(fl_url, entities, &self.sync_period).await
+        self.fl_url_factory.validate_entities(entities)?;
+
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::clean_table_and_bulk_insert(
+                    fl_url,
+                    entities,
+                    &self.sync_period,
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
     }
 
     pub async fn clean_partition_and_bulk_insert(
@@ -195,15 +413,21 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         partition_key: &str,
         entities: &[TEntity],
     ) -> Result<(), DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        let fl_url = fl_url.with_retries(self.max_attempts);
-        super::execution::clean_partition_and_bulk_insert(
-            fl_url,
-            partition_key,
-            entities,
-            &self.sync_period,
-        )
-        .await
+        self.fl_url_factory.validate_entities(entities)?;
+
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::clean_partition_and_bulk_insert(
+                    fl_url,
+                    partition_key,
+                    entities,
+                    &self.sync_period,
+                    self.fl_url_factory.get_compression(),
+                )
+                .await
+            })
+            .await
     }
 
     pub async fn get_partition_keys(
@@ -211,7 +435,91 @@ impl<TEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Sync + Send>
         skip: Option<i32>,
         limit: Option<i32>,
     ) -> Result<Vec<String>, DataWriterError> {
-        let (fl_url, _) = self.fl_url_factory.get_fl_url().await?;
-        super::execution::get_partition_keys(fl_url, TEntity::TABLE_NAME, skip, limit).await
+        self.retry_policy
+            .execute(move || async move {
+                let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+                super::execution::get_partition_keys(fl_url, TEntity::TABLE_NAME, skip, limit).await
+            })
+            .await
+    }
+
+    /// Row is compared the way the node stores it, not the way the entity is serialized back:
+    /// the entity can keep TimeStamp inside of its encoded fields (MessagePack), the node changes only the system fields
+    async fn is_stored(&self, entity: &TEntity) -> Result<bool, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        let stored = super::execution::get_row(
+            fl_url,
+            TEntity::TABLE_NAME,
+            entity.get_partition_key(),
+            entity.get_row_key(),
+            None,
+        )
+        .await?;
+
+        let stored = match stored {
+            Some(stored) => stored,
+            None => return Ok(false),
+        };
+
+        let sent = super::execution::serialize_entity(entity)?;
+
+        Ok(is_the_same_user_fields(stored.as_slice(), sent.as_slice()))
+    }
+}
+
+/// TimeStamp is set by the server, so it is not taken into account
+pub(crate) fn is_the_same_content(left: &[u8], right: &[u8]) -> bool {
+    is_the_same_json_object(left, right, &[consts::TIME_STAMP])
+}
+
+/// Only the fields of the entity are compared. TimeStamp and Expires are written by the node in its own format
+pub(crate) fn is_the_same_user_fields(stored: &[u8], sent: &[u8]) -> bool {
+    is_the_same_json_object(stored, sent, &[consts::TIME_STAMP, consts::EXPIRES])
+}
+
+fn is_the_same_json_object(left: &[u8], right: &[u8], fields_to_skip: &[&str]) -> bool {
+    let left = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(left);
+    let right = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(right);
+
+    match (left, right) {
+        (Ok(mut left), Ok(mut right)) => {
+            for field in fields_to_skip {
+                left.remove(*field);
+                right.remove(*field);
+            }
+
+            left == right
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_the_same_content, is_the_same_user_fields};
+
+    #[test]
+    fn test_content_is_compared_without_timestamp() {
+        let stored =
+            r#"{"PartitionKey":"pk","RowKey":"rk","TimeStamp":"2024-01-01T00:00:00","Value":1}"#;
+        let sent = r#"{"Value":1,"PartitionKey":"pk","RowKey":"rk"}"#;
+        let other = r#"{"PartitionKey":"pk","RowKey":"rk","Value":2}"#;
+
+        assert!(is_the_same_content(stored.as_bytes(), sent.as_bytes()));
+        assert!(!is_the_same_content(stored.as_bytes(), other.as_bytes()));
+    }
+
+    #[test]
+    fn test_user_fields_are_compared_without_system_fields() {
+        let stored = r#"{"PartitionKey":"pk","RowKey":"rk","Expires":"2024-01-01T00:00:00.000000Z","$mp":["pk","rk","2023-12-31T00:00:00",1],"TimeStamp":"2024-01-01T00:00:00.123456"}"#;
+        let sent = r#"{"PartitionKey":"pk","RowKey":"rk","Expires":"2024-01-01T00:00:00","$mp":["pk","rk","2023-12-31T00:00:00",1]}"#;
+        let other =
+            r#"{"PartitionKey":"pk","RowKey":"rk","$mp":["pk","rk","2023-12-31T00:00:00",2]}"#;
+
+        assert!(is_the_same_user_fields(stored.as_bytes(), sent.as_bytes()));
+        assert!(!is_the_same_user_fields(
+            stored.as_bytes(),
+            other.as_bytes()
+        ));
     }
 }
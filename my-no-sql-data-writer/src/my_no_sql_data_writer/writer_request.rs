@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::{
+    DataWriterError, WriterHttpRequest, WriterHttpResponse, WriterHttpStreamedResponse,
+    WriterRequestMethod, WriterTransport,
+};
+
+/// Builds the request and sends it with the transport of the writer
+#[derive(Clone)]
+pub struct WriterRequest {
+    transport: Arc<dyn WriterTransport + Send + Sync + 'static>,
+    request: WriterHttpRequest,
+}
+
+impl WriterRequest {
+    pub fn new(
+        url: impl Into<String>,
+        transport: Arc<dyn WriterTransport + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            transport,
+            request: WriterHttpRequest::new(url),
+        }
+    }
+
+    pub fn append_path_segment(mut self, segment: impl Into<String>) -> Self {
+        self.request.path_segments.push(segment.into());
+        self
+    }
+
+    pub fn append_query_param(
+        mut self,
+        name: impl Into<String>,
+        value: Option<impl Into<String>>,
+    ) -> Self {
+        self.request
+            .query_params
+            .push((name.into(), value.map(|itm| itm.into())));
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.request.retries = retries;
+        self
+    }
+
+    pub fn get_request(&self) -> &WriterHttpRequest {
+        &self.request
+    }
+
+    pub async fn get(self) -> Result<WriterHttpResponse, DataWriterError> {
+        self.execute(WriterRequestMethod::Get, None).await
+    }
+
+    /// Body of the response is received chunk by chunk, if the transport supports it
+    pub async fn get_streamed(mut self) -> Result<WriterHttpStreamedResponse, DataWriterError> {
+        self.request.method = WriterRequestMethod::Get;
+        super::request_tracing::inject_trace_headers(&mut self.request);
+        self.transport.execute_streamed(self.request).await
+    }
+
+    pub async fn post(self, body: Option<Vec<u8>>) -> Result<WriterHttpResponse, DataWriterError> {
+        self.execute(WriterRequestMethod::Post, body).await
+    }
+
+    pub async fn post_json(
+        self,
+        body: &impl Serialize,
+    ) -> Result<WriterHttpResponse, DataWriterError> {
+        let body = match serde_json::to_vec(body) {
+            Ok(body) => body,
+            Err(err) => {
+                return Err(DataWriterError::Error(format!(
+                    "Failed to serialize: {:?}",
+                    err
+                )))
+            }
+        };
+
+        self.with_header("Content-Type", "application/json")
+            .execute(WriterRequestMethod::Post, Some(body))
+            .await
+    }
+
+    pub async fn put(self, body: Option<Vec<u8>>) -> Result<WriterHttpResponse, DataWriterError> {
+        self.execute(WriterRequestMethod::Put, body).await
+    }
+
+    pub async fn delete(self) -> Result<WriterHttpResponse, DataWriterError> {
+        self.execute(WriterRequestMethod::Delete, None).await
+    }
+
+    async fn execute(
+        mut self,
+        method: WriterRequestMethod,
+        body: Option<Vec<u8>>,
+    ) -> Result<WriterHttpResponse, DataWriterError> {
+        self.request.method = method;
+        self.request.body = body;
+        super::request_tracing::inject_trace_headers(&mut self.request);
+        self.transport.execute(self.request).await
+    }
+}
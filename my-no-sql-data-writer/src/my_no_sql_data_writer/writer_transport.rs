@@ -0,0 +1,220 @@
+use std::pin::Pin;
+
+use futures::Stream;
+
+use super::DataWriterError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriterRequestMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl WriterRequestMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WriterRequestMethod::Get => "GET",
+            WriterRequestMethod::Post => "POST",
+            WriterRequestMethod::Put => "PUT",
+            WriterRequestMethod::Delete => "DELETE",
+        }
+    }
+}
+
+/// Transport independent request to MyNoSql server
+#[derive(Debug, Clone)]
+pub struct WriterHttpRequest {
+    pub method: WriterRequestMethod,
+    pub base_url: String,
+    pub path_segments: Vec<String>,
+    pub query_params: Vec<(String, Option<String>)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    /// Amount of retries transport does on connection errors. 0 - no retries
+    pub retries: usize,
+}
+
+impl WriterHttpRequest {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            method: WriterRequestMethod::Get,
+            base_url: base_url.into(),
+            path_segments: Vec::new(),
+            query_params: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+            retries: 0,
+        }
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// Path and query of the request. Path segments, query names and values are percent-encoded
+    pub fn get_path_and_query(&self) -> String {
+        let mut result = String::new();
+
+        for segment in &self.path_segments {
+            result.push('/');
+            push_percent_encoded(&mut result, segment);
+        }
+
+        for (index, (name, value)) in self.query_params.iter().enumerate() {
+            result.push(if index == 0 { '?' } else { '&' });
+            push_percent_encoded(&mut result, name);
+
+            if let Some(value) = value {
+                result.push('=');
+                push_percent_encoded(&mut result, value);
+            }
+        }
+
+        result
+    }
+
+    pub fn get_url(&self) -> String {
+        format!(
+            "{}{}",
+            self.base_url.trim_end_matches('/'),
+            self.get_path_and_query()
+        )
+    }
+}
+
+fn find_header<'s>(headers: &'s [(String, String)], name: &str) -> Option<&'s str> {
+    headers
+        .iter()
+        .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Unreserved characters of RFC 3986 are kept as is, the rest are written as %XX of their utf8 bytes
+fn push_percent_encoded(out: &mut String, value: &str) {
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => {
+                out.push('%');
+                out.push_str(format!("{:02X}", b).as_str());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WriterHttpResponse {
+    pub url: String,
+    pub status_code: u16,
+    /// Content-Encoding is used to decompress the body
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl WriterHttpResponse {
+    pub fn get_status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn get_body_as_slice(&self) -> &[u8] {
+        self.body.as_slice()
+    }
+
+    pub fn receive_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+/// Body of the response which is received chunk by chunk
+pub type WriterBodyStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, DataWriterError>> + Send>>;
+
+pub struct WriterHttpStreamedResponse {
+    pub url: String,
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: WriterBodyStream,
+}
+
+impl WriterHttpStreamedResponse {
+    pub fn get_status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    pub async fn receive_body(mut self) -> Result<WriterHttpResponse, DataWriterError> {
+        use futures::StreamExt;
+
+        let mut body = Vec::new();
+
+        while let Some(chunk) = self.body.next().await {
+            body.extend_from_slice(chunk?.as_slice());
+        }
+
+        Ok(WriterHttpResponse {
+            url: self.url,
+            status_code: self.status_code,
+            headers: self.headers,
+            body,
+        })
+    }
+}
+
+impl From<WriterHttpResponse> for WriterHttpStreamedResponse {
+    fn from(src: WriterHttpResponse) -> Self {
+        Self {
+            url: src.url,
+            status_code: src.status_code,
+            headers: src.headers,
+            body: Box::pin(futures::stream::once(async move { Ok(src.body) })),
+        }
+    }
+}
+
+/// Sends the requests of the writer to MyNoSql server. FlUrlTransport is used by default.
+#[async_trait::async_trait]
+pub trait WriterTransport {
+    async fn execute(
+        &self,
+        request: WriterHttpRequest,
+    ) -> Result<WriterHttpResponse, DataWriterError>;
+
+    /// Used by the streams of the entities. Default implementation receives the whole body
+    /// and returns it as one chunk, transports which can read the body while it arrives override it
+    async fn execute_streamed(
+        &self,
+        request: WriterHttpRequest,
+    ) -> Result<WriterHttpStreamedResponse, DataWriterError>
+    where
+        Self: Sync,
+    {
+        let response = self.execute(request).await?;
+        Ok(response.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WriterHttpRequest;
+
+    #[test]
+    fn test_query_values_are_encoded() {
+        let mut request = WriterHttpRequest::new("http://localhost:5123/");
+        request.path_segments.push("Row".to_string());
+        request
+            .query_params
+            .push(("partitionKey".to_string(), Some("a&b #c+d/é".to_string())));
+        request.query_params.push(("persist".to_string(), None));
+
+        assert_eq!(
+            "http://localhost:5123/Row?partitionKey=a%26b%20%23c%2Bd%2F%C3%A9&persist",
+            request.get_url()
+        );
+    }
+}
@@ -0,0 +1,8 @@
+mod my_no_sql_outbox;
+mod outbox_file;
+mod outbox_operation;
+mod outbox_settings;
+pub use my_no_sql_outbox::*;
+pub use outbox_file::*;
+pub use outbox_operation::*;
+pub use outbox_settings::*;
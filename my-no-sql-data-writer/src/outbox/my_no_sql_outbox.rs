@@ -0,0 +1,643 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use my_logger::LogEventCtx;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{
+    my_no_sql_data_writer::execution::execute_outbox_operation, DataWriterError, FlUrlFactory,
+    RequestOptions,
+};
+
+use super::{OutboxFile, OutboxOperation, OutboxOperationType, OutboxSettings};
+
+/// Sent with every operation of the outbox, so the duplicates can be traced. MyNoSql server does not deduplicate by it
+pub const IDEMPOTENCY_KEY_HEADER: &str = "X-Idempotency-Key";
+
+struct MyNoSqlOutboxInner {
+    items: VecDeque<OutboxOperation>,
+    size: usize,
+    next_id: u64,
+}
+
+impl MyNoSqlOutboxInner {
+    fn new(items: Vec<OutboxOperation>) -> Self {
+        let mut size = 0;
+        let mut next_id = 1;
+
+        for item in items.iter() {
+            size += item.get_size();
+            if item.id >= next_id {
+                next_id = item.id + 1;
+            }
+        }
+
+        Self {
+            items: items.into(),
+            size,
+            next_id,
+        }
+    }
+
+    fn has_idempotency_key(&self, idempotency_key: &str) -> bool {
+        self.items
+            .iter()
+            .any(|itm| itm.idempotency_key == idempotency_key)
+    }
+}
+
+/// Durable queue of write operations which could not be delivered to the server.
+/// Operations are persisted to the file before the call returns and are replayed
+/// in the same order by the background task once the server answers again.
+/// Delivery is at least once: operation which reached the server, but is not marked as delivered, is sent once again.
+pub struct MyNoSqlOutbox {
+    max_size_bytes: usize,
+    replay_interval: std::time::Duration,
+    file: OutboxFile,
+    inner: Mutex<MyNoSqlOutboxInner>,
+    direct_send_gate: RwLock<()>,
+}
+
+impl MyNoSqlOutbox {
+    pub async fn load(settings: OutboxSettings) -> Result<Self, DataWriterError> {
+        let file = OutboxFile::new(settings.file_path);
+        let items = file.load().await?;
+
+        // Records of the delivered operations and the torn last line are removed.
+        // Otherwise ids of the delivered operations are reused and the next append continues the torn line
+        file.rewrite(items.iter()).await?;
+
+        Ok(Self {
+            max_size_bytes: settings.max_size_bytes,
+            replay_interval: settings.replay_interval,
+            file,
+            inner: Mutex::new(MyNoSqlOutboxInner::new(items)),
+            direct_send_gate: RwLock::new(()),
+        })
+    }
+
+    pub async fn push(&self, operation: OutboxOperation) -> Result<(), DataWriterError> {
+        let mut inner = self.inner.lock().await;
+        self.push_locked(&mut inner, operation).await
+    }
+
+    async fn push_locked(
+        &self,
+        inner: &mut MyNoSqlOutboxInner,
+        mut operation: OutboxOperation,
+    ) -> Result<(), DataWriterError> {
+        if !operation.idempotency_key.is_empty()
+            && inner.has_idempotency_key(operation.idempotency_key.as_str())
+        {
+            return Ok(());
+        }
+
+        let operation_size = operation.get_size();
+
+        if inner.size + operation_size > self.max_size_bytes {
+            return Err(DataWriterError::OutboxIsFull(format!(
+                "Outbox {} has {} bytes pending. Max size is {} bytes",
+                self.file.get_file_path(),
+                inner.size,
+                self.max_size_bytes
+            )));
+        }
+
+        operation.id = inner.next_id;
+
+        if operation.idempotency_key.is_empty() {
+            operation.idempotency_key = format!(
+                "{}-{}-{}",
+                operation.table_name, operation.created, operation.id
+            );
+        }
+
+        self.file.append(&operation).await?;
+
+        inner.next_id += 1;
+        inner.size += operation_size;
+        inner.items.push_back(operation);
+
+        Ok(())
+    }
+
+    pub async fn has_pending(&self) -> bool {
+        let inner = self.inner.lock().await;
+        inner.items.len() > 0
+    }
+
+    pub async fn get_pending_amount(&self) -> usize {
+        let inner = self.inner.lock().await;
+        inner.items.len()
+    }
+
+    pub async fn get_size(&self) -> usize {
+        let inner = self.inner.lock().await;
+        inner.size
+    }
+
+    pub async fn get_pending(&self) -> Vec<OutboxOperation> {
+        let inner = self.inner.lock().await;
+        inner.items.iter().cloned().collect()
+    }
+
+    /// Removes all the pending operations without sending them.
+    pub async fn drain(&self) -> Result<Vec<OutboxOperation>, DataWriterError> {
+        let mut inner = self.inner.lock().await;
+
+        self.file.rewrite(std::iter::empty()).await?;
+
+        inner.size = 0;
+        Ok(inner.items.drain(..).collect())
+    }
+
+    /// Headers and timeout of the options are applied to the first attempt only.
+    /// Operation which is timed out is not enqueued, since it may be applied by the server already: the error is returned
+    pub(crate) async fn execute_or_enqueue(
+        &self,
+        fl_url_factory: &FlUrlFactory,
+        operation: OutboxOperation,
+        options: &RequestOptions,
+    ) -> Result<Option<Vec<u8>>, DataWriterError> {
+        // Operations must reach the server in the order they were made, so nothing is sent directly while operations are pending.
+        // Replay waits for the direct sends in flight, so the operation which fails is enqueued before the replay starts
+        let _direct_send = self.direct_send_gate.read().await;
+
+        {
+            let mut inner = self.inner.lock().await;
+
+            if !inner.items.is_empty() {
+                self.push_locked(&mut inner, operation).await?;
+                return Ok(None);
+            }
+        }
+
+        let fl_url = match fl_url_factory.get_writer_request().await {
+            Ok((fl_url, _)) => options.fill_fields(fl_url),
+            Err(err) => {
+                if err.is_transport_error() {
+                    self.push(operation).await?;
+                    return Ok(None);
+                }
+
+                return Err(err);
+            }
+        };
+
+        match options
+            .execute(execute_outbox_operation(
+                fl_url,
+                &operation,
+                fl_url_factory.get_compression(),
+            ))
+            .await
+        {
+            Ok(result) => Ok(result),
+            Err(err) => {
+                if err.is_transport_error() {
+                    self.push(operation).await?;
+                    return Ok(None);
+                }
+
+                Err(err)
+            }
+        }
+    }
+
+    async fn replay(&self, fl_url_factory: &FlUrlFactory) -> Result<usize, DataWriterError> {
+        let mut replayed = 0;
+        let mut result = Ok(());
+
+        drop(self.direct_send_gate.write().await);
+
+        loop {
+            let operation = {
+                let inner = self.inner.lock().await;
+                match inner.items.front() {
+                    Some(operation) => operation.clone(),
+                    None => break,
+                }
+            };
+
+            let fl_url = match fl_url_factory.get_writer_request().await {
+                Ok((fl_url, _)) => fl_url,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+
+            match execute_outbox_operation(fl_url, &operation, fl_url_factory.get_compression())
+                .await
+            {
+                Ok(_) => {}
+                Err(err) => {
+                    if err.is_retryable() {
+                        break;
+                    }
+
+                    // Operation stays in the outbox until the server answers with the definitive rejection
+                    if !err.is_rejected_by_server() {
+                        result = Err(err);
+                        break;
+                    }
+
+                    let delivered_before = operation.operation == OutboxOperationType::Insert
+                        && matches!(err, DataWriterError::RecordAlreadyExists(_));
+
+                    if !delivered_before {
+                        my_logger::LOGGER.write_error(
+                            "MyNoSqlOutbox::replay",
+                            format!(
+                                "Operation is rejected by server and dropped. Err: {:?}",
+                                err
+                            ),
+                            LogEventCtx::new()
+                                .add("TableName", operation.table_name.as_str())
+                                .add("IdempotencyKey", operation.idempotency_key.as_str()),
+                        );
+                    }
+                }
+            }
+
+            let mut inner = self.inner.lock().await;
+
+            let is_the_same = match inner.items.front() {
+                Some(front) => front.id == operation.id,
+                None => false,
+            };
+
+            if is_the_same {
+                if let Some(removed) = inner.items.pop_front() {
+                    inner.size -= removed.get_size();
+                }
+                replayed += 1;
+
+                // If the record is not written, the operation is sent once again after the restart
+                if let Err(err) = self.file.append_delivered(operation.id).await {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        if replayed > 0 {
+            let inner = self.inner.lock().await;
+            self.file.rewrite(inner.items.iter()).await?;
+        }
+
+        result.map(|_| replayed)
+    }
+
+    pub(crate) fn start_replay_loop(outbox: &Arc<Self>, fl_url_factory: FlUrlFactory) {
+        let outbox = Arc::downgrade(outbox);
+
+        tokio::spawn(async move {
+            loop {
+                let replay_interval = match outbox.upgrade() {
+                    Some(outbox) => {
+                        if outbox.has_pending().await {
+                            if let Err(err) = outbox.replay(&fl_url_factory).await {
+                                if !err.is_transport_error() {
+                                    my_logger::LOGGER.write_error(
+                                        "MyNoSqlOutbox::replay",
+                                        format!("{:?}", err),
+                                        LogEventCtx::new()
+                                            .add("FilePath", outbox.file.get_file_path()),
+                                    );
+                                }
+                            }
+                        }
+
+                        outbox.replay_interval
+                    }
+                    None => return,
+                };
+
+                tokio::time::sleep(replay_interval).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    };
+
+    use my_no_sql_abstractions::DataSynchronizationPeriod;
+
+    use crate::{
+        DataWriterError, FlUrlFactory, MyNoSqlWriterSettings, OutboxFile, OutboxOperation,
+        OutboxOperationType, OutboxSettings, RequestOptions, WriterHttpRequest, WriterHttpResponse,
+        WriterTransport,
+    };
+
+    use super::MyNoSqlOutbox;
+
+    struct TestSettings;
+
+    #[async_trait::async_trait]
+    impl MyNoSqlWriterSettings for TestSettings {
+        async fn get_url(&self) -> String {
+            "http://localhost:5123".to_string()
+        }
+
+        fn get_app_name(&self) -> &'static str {
+            "test"
+        }
+
+        fn get_app_version(&self) -> &'static str {
+            "0.1.0"
+        }
+    }
+
+    /// Delivers the given amount of requests, the rest fail as the server is not reachable.
+    /// Status codes of the failed answers are returned first
+    struct TestTransport {
+        delivered: Mutex<Vec<String>>,
+        can_deliver: Mutex<usize>,
+        failed_status_codes: Mutex<VecDeque<u16>>,
+    }
+
+    #[async_trait::async_trait]
+    impl WriterTransport for TestTransport {
+        async fn execute(
+            &self,
+            request: WriterHttpRequest,
+        ) -> Result<WriterHttpResponse, DataWriterError> {
+            if let Some(status_code) = self.failed_status_codes.lock().unwrap().pop_front() {
+                return Ok(WriterHttpResponse {
+                    url: request.get_url(),
+                    status_code,
+                    headers: Vec::new(),
+                    body: b"Service Unavailable".to_vec(),
+                });
+            }
+
+            let mut can_deliver = self.can_deliver.lock().unwrap();
+
+            if *can_deliver == 0 {
+                return Err(DataWriterError::TransportError(
+                    "Connection refused".to_string(),
+                ));
+            }
+
+            *can_deliver -= 1;
+
+            let payload = String::from_utf8(request.body.clone().unwrap()).unwrap();
+            self.delivered.lock().unwrap().push(payload);
+
+            Ok(WriterHttpResponse {
+                url: request.get_url(),
+                status_code: 200,
+                headers: Vec::new(),
+                body: vec![],
+            })
+        }
+    }
+
+    fn create_operation(no: usize) -> OutboxOperation {
+        OutboxOperation::new(
+            OutboxOperationType::InsertOrReplace,
+            "test",
+            DataSynchronizationPeriod::Sec1,
+            None,
+            None,
+            Some(format!(r#"{{"PartitionKey":"pk","RowKey":"{}"}}"#, no).into_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn get_file_path(name: &str) -> String {
+        let file_path = std::env::temp_dir().join(format!(
+            "my-no-sql-outbox-{}-{}.log",
+            name,
+            rust_extensions::date_time::DateTimeAsMicroseconds::now().unix_microseconds
+        ));
+
+        file_path.to_str().unwrap().to_string()
+    }
+
+    async fn create_outbox(name: &str) -> (MyNoSqlOutbox, FlUrlFactory, Arc<TestTransport>) {
+        let outbox = MyNoSqlOutbox::load(OutboxSettings::new(get_file_path(name).as_str()))
+            .await
+            .unwrap();
+
+        let transport = Arc::new(TestTransport {
+            delivered: Mutex::new(Vec::new()),
+            can_deliver: Mutex::new(0),
+            failed_status_codes: Mutex::new(VecDeque::new()),
+        });
+
+        let mut fl_url_factory = FlUrlFactory::new(
+            Arc::new(TestSettings),
+            None,
+            "test",
+            DataSynchronizationPeriod::Sec1,
+        );
+        fl_url_factory.transport = Some(transport.clone());
+
+        (outbox, fl_url_factory, transport)
+    }
+
+    fn get_delivered(transport: &TestTransport) -> Vec<String> {
+        transport.delivered.lock().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_operations_are_delivered_in_order() {
+        let (outbox, fl_url_factory, transport) = create_outbox("order").await;
+
+        for no in 0..2 {
+            let result = outbox
+                .execute_or_enqueue(
+                    &fl_url_factory,
+                    create_operation(no),
+                    &RequestOptions::new(),
+                )
+                .await
+                .unwrap();
+            assert!(result.is_none());
+        }
+
+        *transport.can_deliver.lock().unwrap() = 10;
+
+        // Server is reachable again, but the operation must wait for the pending ones
+        outbox
+            .execute_or_enqueue(&fl_url_factory, create_operation(2), &RequestOptions::new())
+            .await
+            .unwrap();
+
+        assert_eq!(0, get_delivered(&transport).len());
+        assert_eq!(3, outbox.get_pending_amount().await);
+
+        assert_eq!(3, outbox.replay(&fl_url_factory).await.unwrap());
+
+        let expected: Vec<String> = (0..3)
+            .map(|no| String::from_utf8(create_operation(no).get_payload().unwrap()).unwrap())
+            .collect();
+        assert_eq!(expected, get_delivered(&transport));
+        assert_eq!(0, outbox.get_pending_amount().await);
+        assert_eq!(0, outbox.file.load().await.unwrap().len());
+
+        // Nothing is pending - operation is sent right away
+        outbox
+            .execute_or_enqueue(&fl_url_factory, create_operation(3), &RequestOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(4, get_delivered(&transport).len());
+
+        tokio::fs::remove_file(outbox.file.get_file_path())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_progress_is_persisted() {
+        let (outbox, fl_url_factory, transport) = create_outbox("progress").await;
+
+        for no in 0..3 {
+            outbox
+                .execute_or_enqueue(
+                    &fl_url_factory,
+                    create_operation(no),
+                    &RequestOptions::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        *transport.can_deliver.lock().unwrap() = 1;
+
+        assert_eq!(1, outbox.replay(&fl_url_factory).await.unwrap());
+        assert_eq!(2, outbox.get_pending_amount().await);
+
+        let file = OutboxFile::new(outbox.file.get_file_path().to_string());
+        let pending = file.load().await.unwrap();
+        assert_eq!(2, pending.len());
+        assert_eq!(
+            create_operation(1).payload,
+            pending[0].payload,
+            "Delivered operation is not replayed after restart"
+        );
+
+        tokio::fs::remove_file(outbox.file.get_file_path())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_operations_keep_order() {
+        let (outbox, fl_url_factory, transport) = create_outbox("concurrent").await;
+        *transport.can_deliver.lock().unwrap() = 1;
+
+        let outbox = Arc::new(outbox);
+
+        let mut handles = Vec::new();
+
+        for no in 0..3 {
+            let outbox = outbox.clone();
+            let fl_url_factory = fl_url_factory.clone();
+            handles.push(tokio::spawn(async move {
+                outbox
+                    .execute_or_enqueue(
+                        &fl_url_factory,
+                        create_operation(no),
+                        &RequestOptions::new(),
+                    )
+                    .await
+                    .unwrap();
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // One operation is delivered, the others are pending. None of them is lost
+        assert_eq!(1, get_delivered(&transport).len());
+        assert_eq!(2, outbox.get_pending_amount().await);
+
+        tokio::fs::remove_file(outbox.file.get_file_path())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_operation_is_kept_while_server_is_unavailable() {
+        let (outbox, fl_url_factory, transport) = create_outbox("unavailable").await;
+
+        outbox
+            .execute_or_enqueue(&fl_url_factory, create_operation(0), &RequestOptions::new())
+            .await
+            .unwrap();
+
+        *transport.can_deliver.lock().unwrap() = 1;
+        transport.failed_status_codes.lock().unwrap().push_back(503);
+
+        assert_eq!(0, outbox.replay(&fl_url_factory).await.unwrap());
+        assert_eq!(1, outbox.get_pending_amount().await);
+
+        assert_eq!(1, outbox.replay(&fl_url_factory).await.unwrap());
+        assert_eq!(1, get_delivered(&transport).len());
+        assert_eq!(0, outbox.get_pending_amount().await);
+
+        tokio::fs::remove_file(outbox.file.get_file_path())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_operations_enqueued_after_restart_are_not_lost() {
+        let file_path = get_file_path("restart");
+        let file = OutboxFile::new(file_path.clone());
+
+        // Process died after the delivery was recorded, but before the file was rewritten
+        for no in 0..2 {
+            let mut operation = create_operation(no);
+            operation.id = no as u64 + 1;
+            file.append(&operation).await.unwrap();
+            file.append_delivered(operation.id).await.unwrap();
+        }
+
+        let outbox = MyNoSqlOutbox::load(OutboxSettings::new(file_path.as_str()))
+            .await
+            .unwrap();
+        assert_eq!(0, outbox.get_pending_amount().await);
+
+        outbox.push(create_operation(2)).await.unwrap();
+
+        let pending = file.load().await.unwrap();
+        assert_eq!(1, pending.len());
+        assert_eq!(create_operation(2).payload, pending[0].payload);
+
+        tokio::fs::remove_file(file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_torn_line_is_removed_on_load() {
+        let file_path = get_file_path("torn");
+        let file = OutboxFile::new(file_path.clone());
+
+        let mut operation = create_operation(0);
+        operation.id = 1;
+        file.append(&operation).await.unwrap();
+
+        let mut content = tokio::fs::read(file_path.as_str()).await.unwrap();
+        content.extend_from_slice(br#"{"id":2,"idempotency_key":"#);
+        tokio::fs::write(file_path.as_str(), content).await.unwrap();
+
+        let outbox = MyNoSqlOutbox::load(OutboxSettings::new(file_path.as_str()))
+            .await
+            .unwrap();
+        outbox.push(create_operation(1)).await.unwrap();
+
+        let pending = file.load().await.unwrap();
+        assert_eq!(2, pending.len());
+        assert_eq!(create_operation(1).payload, pending[1].payload);
+
+        tokio::fs::remove_file(file_path).await.unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+
+use my_logger::LogEventCtx;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::DataWriterError;
+
+use super::OutboxOperation;
+
+/// Appended once the operation is delivered, so the progress of the replay survives the restart
+#[derive(Debug, Serialize, Deserialize)]
+struct OutboxDeliveredRecord {
+    delivered_id: u64,
+}
+
+/// Append-only log of pending operations. One JSON document per line.
+/// Lines of the delivered operations are removed when the file is rewritten.
+pub struct OutboxFile {
+    file_path: String,
+}
+
+impl OutboxFile {
+    pub fn new(file_path: String) -> Self {
+        Self { file_path }
+    }
+
+    pub fn get_file_path(&self) -> &str {
+        self.file_path.as_str()
+    }
+
+    pub async fn load(&self) -> Result<Vec<OutboxOperation>, DataWriterError> {
+        let content = match tokio::fs::read(self.file_path.as_str()).await {
+            Ok(content) => content,
+            Err(err) => {
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    return Ok(vec![]);
+                }
+
+                return Err(err.into());
+            }
+        };
+
+        let mut result = Vec::new();
+        let mut delivered = HashSet::new();
+
+        for line in content.split(|b| *b == b'\n') {
+            if line.iter().all(|b| *b <= 32) {
+                continue;
+            }
+
+            if let Ok(record) = serde_json::from_slice::<OutboxDeliveredRecord>(line) {
+                delivered.insert(record.delivered_id);
+                continue;
+            }
+
+            match serde_json::from_slice::<OutboxOperation>(line) {
+                Ok(operation) => result.push(operation),
+                Err(err) => {
+                    // Last line can be torn if the process died in the middle of the append
+                    my_logger::LOGGER.write_error(
+                        "OutboxFile::load",
+                        format!("Skipping broken outbox record. Err: {:?}", err),
+                        LogEventCtx::new().add("FilePath", self.file_path.as_str()),
+                    );
+                }
+            }
+        }
+
+        result.retain(|itm| !delivered.contains(&itm.id));
+
+        Ok(result)
+    }
+
+    pub async fn append(&self, operation: &OutboxOperation) -> Result<(), DataWriterError> {
+        self.append_line(to_line(operation)?).await
+    }
+
+    pub async fn append_delivered(&self, id: u64) -> Result<(), DataWriterError> {
+        self.append_line(to_line(&OutboxDeliveredRecord { delivered_id: id })?)
+            .await
+    }
+
+    async fn append_line(&self, line: Vec<u8>) -> Result<(), DataWriterError> {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_path.as_str())
+            .await?;
+
+        file.write_all(line.as_slice()).await?;
+        file.sync_data().await?;
+
+        Ok(())
+    }
+
+    pub async fn rewrite<'s>(
+        &self,
+        operations: impl Iterator<Item = &'s OutboxOperation>,
+    ) -> Result<(), DataWriterError> {
+        let mut content = Vec::new();
+
+        for operation in operations {
+            content.extend_from_slice(to_line(operation)?.as_slice());
+        }
+
+        let tmp_file_path = format!("{}.tmp", self.file_path);
+
+        let mut file = tokio::fs::File::create(tmp_file_path.as_str()).await?;
+        file.write_all(content.as_slice()).await?;
+        file.sync_data().await?;
+
+        tokio::fs::rename(tmp_file_path.as_str(), self.file_path.as_str()).await?;
+
+        Ok(())
+    }
+}
+
+fn to_line(src: &impl Serialize) -> Result<Vec<u8>, DataWriterError> {
+    let mut line = match serde_json::to_vec(src) {
+        Ok(line) => line,
+        Err(err) => {
+            return Err(DataWriterError::Error(format!(
+                "Failed to serialize outbox record: {:?}",
+                err
+            )))
+        }
+    };
+
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_abstractions::DataSynchronizationPeriod;
+
+    use crate::{OutboxOperation, OutboxOperationType};
+
+    use super::OutboxFile;
+
+    #[tokio::test]
+    async fn test_append_and_load() {
+        let file_path = std::env::temp_dir().join(format!(
+            "my-no-sql-outbox-test-{}.log",
+            rust_extensions::date_time::DateTimeAsMicroseconds::now().unix_microseconds
+        ));
+
+        let outbox_file = OutboxFile::new(file_path.to_str().unwrap().to_string());
+
+        let mut operation = OutboxOperation::new(
+            OutboxOperationType::InsertOrReplace,
+            "test-table",
+            DataSynchronizationPeriod::Sec1,
+            Some("pk"),
+            Some("rk"),
+            Some(r#"{"PartitionKey":"pk","RowKey":"rk"}"#.as_bytes().to_vec()),
+        )
+        .unwrap();
+
+        operation.id = 1;
+        outbox_file.append(&operation).await.unwrap();
+
+        operation.id = 2;
+        operation.operation = OutboxOperationType::DeleteRow;
+        operation.payload = None;
+        outbox_file.append(&operation).await.unwrap();
+
+        let loaded = outbox_file.load().await.unwrap();
+
+        assert_eq!(2, loaded.len());
+        assert_eq!(1, loaded[0].id);
+        assert_eq!(OutboxOperationType::InsertOrReplace, loaded[0].operation);
+        assert_eq!(OutboxOperationType::DeleteRow, loaded[1].operation);
+
+        outbox_file.rewrite(loaded[1..].iter()).await.unwrap();
+
+        let loaded = outbox_file.load().await.unwrap();
+        assert_eq!(1, loaded.len());
+        assert_eq!(2, loaded[0].id);
+
+        outbox_file.append_delivered(2).await.unwrap();
+        assert_eq!(0, outbox_file.load().await.unwrap().len());
+
+        tokio::fs::remove_file(file_path).await.unwrap();
+    }
+}
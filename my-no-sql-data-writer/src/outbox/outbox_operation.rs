@@ -0,0 +1,88 @@
+use my_no_sql_abstractions::DataSynchronizationPeriod;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+use serde::{Deserialize, Serialize};
+
+use crate::DataWriterError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxOperationType {
+    Insert,
+    InsertOrReplace,
+    BulkInsertOrReplace,
+    DeleteRow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxOperation {
+    pub id: u64,
+    pub idempotency_key: String,
+    pub table_name: String,
+    pub operation: OutboxOperationType,
+    pub sync_period: String,
+    pub partition_key: Option<String>,
+    pub row_key: Option<String>,
+    pub payload: Option<String>,
+    pub created: i64,
+}
+
+impl OutboxOperation {
+    pub fn new(
+        operation: OutboxOperationType,
+        table_name: &str,
+        sync_period: DataSynchronizationPeriod,
+        partition_key: Option<&str>,
+        row_key: Option<&str>,
+        payload: Option<Vec<u8>>,
+    ) -> Result<Self, DataWriterError> {
+        let payload = match payload {
+            Some(payload) => Some(String::from_utf8(payload)?),
+            None => None,
+        };
+
+        let result = Self {
+            id: 0,
+            idempotency_key: String::new(),
+            table_name: table_name.to_string(),
+            operation,
+            sync_period: sync_period.as_str().to_string(),
+            partition_key: partition_key.map(|itm| itm.to_string()),
+            row_key: row_key.map(|itm| itm.to_string()),
+            payload,
+            created: DateTimeAsMicroseconds::now().unix_microseconds,
+        };
+
+        Ok(result)
+    }
+
+    pub fn get_sync_period(&self) -> DataSynchronizationPeriod {
+        DataSynchronizationPeriod::try_from_str(self.sync_period.as_str())
+            .unwrap_or(DataSynchronizationPeriod::Sec5)
+    }
+
+    pub fn get_created(&self) -> DateTimeAsMicroseconds {
+        DateTimeAsMicroseconds::new(self.created)
+    }
+
+    pub fn get_payload(&self) -> Option<Vec<u8>> {
+        let payload = self.payload.as_ref()?;
+        Some(payload.as_bytes().to_vec())
+    }
+
+    pub fn get_size(&self) -> usize {
+        let mut result = self.table_name.len() + self.idempotency_key.len();
+
+        if let Some(partition_key) = &self.partition_key {
+            result += partition_key.len();
+        }
+
+        if let Some(row_key) = &self.row_key {
+            result += row_key.len();
+        }
+
+        if let Some(payload) = &self.payload {
+            result += payload.len();
+        }
+
+        result
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+pub const DEFAULT_OUTBOX_MAX_SIZE_BYTES: usize = 64 * 1024 * 1024;
+pub const DEFAULT_OUTBOX_REPLAY_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct OutboxSettings {
+    pub file_path: String,
+    pub max_size_bytes: usize,
+    pub replay_interval: Duration,
+}
+
+impl OutboxSettings {
+    pub fn new(file_path: impl Into<String>) -> Self {
+        Self {
+            file_path: file_path.into(),
+            max_size_bytes: DEFAULT_OUTBOX_MAX_SIZE_BYTES,
+            replay_interval: DEFAULT_OUTBOX_REPLAY_INTERVAL,
+        }
+    }
+}
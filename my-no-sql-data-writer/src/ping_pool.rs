@@ -1,15 +1,43 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
+use rust_extensions::ApplicationStates;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
 
 use crate::{FlUrlFactory, MyNoSqlWriterSettings};
 
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub enum PingStatus {
+    Ok {
+        name: &'static str,
+        version: &'static str,
+        url: String,
+        tables: Vec<String>,
+    },
+    Error {
+        name: &'static str,
+        version: &'static str,
+        url: String,
+        error: String,
+    },
+}
+
 pub struct PingDataItem {
     pub name: &'static str,
     pub version: &'static str,
 
     pub table_settings: Vec<(
+        u64,
         String,
         Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
     )>,
@@ -18,6 +46,9 @@ pub struct PingDataItem {
 pub struct PingPoolInner {
     items: Vec<PingDataItem>,
     started: bool,
+    ping_interval: Duration,
+    app_states: Option<Arc<dyn ApplicationStates + Send + Sync + 'static>>,
+    status_callback: Option<Arc<dyn Fn(PingStatus) + Send + Sync + 'static>>,
 }
 
 impl PingPoolInner {
@@ -25,30 +56,45 @@ impl PingPoolInner {
         Self {
             items: Vec::new(),
             started: false,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            app_states: None,
+            status_callback: None,
         }
     }
 }
 
+/// Pings MyNoSql server on behalf of all the writers of the application.
+/// Background loop is started by the first registration and stopped when the last table is unregistered,
+/// by shutdown or by the application states.
 pub struct PingPool {
     data: Mutex<PingPoolInner>,
+    next_id: AtomicU64,
+    shutting_down: AtomicBool,
 }
 
 impl PingPool {
     pub fn new() -> Self {
         Self {
             data: Mutex::new(PingPoolInner::new()),
+            next_id: AtomicU64::new(0),
+            shutting_down: AtomicBool::new(false),
         }
     }
 
-    pub async fn register(
-        &self,
-
+    /// Returns the id to unregister the table with
+    pub fn register(
+        &'static self,
         settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
         table: &str,
-    ) {
-        let mut data = self.data.lock().await;
+    ) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        // Loop is started and stopped under the same lock, see keep_running
+        let mut data = self.data.lock().unwrap();
+        self.shutting_down.store(false, Ordering::SeqCst);
+
         if !data.started {
-            tokio::spawn(async move { ping_loop().await });
+            tokio::spawn(async move { ping_loop(self).await });
             data.started = true;
         }
 
@@ -58,74 +104,309 @@ impl PingPool {
 
         if let Some(index) = index {
             let item = &mut data.items[index];
-            item.table_settings.push((table.to_string(), settings));
+            item.table_settings.push((id, table.to_string(), settings));
         } else {
             let item = PingDataItem {
                 name: settings.get_app_name(),
                 version: settings.get_app_version(),
 
-                table_settings: vec![((table.to_string(), settings))],
+                table_settings: vec![(id, table.to_string(), settings)],
             };
 
             data.items.push(item);
         }
+
+        id
+    }
+
+    pub fn unregister(&self, id: u64) {
+        let mut data = self.data.lock().unwrap();
+
+        for item in data.items.iter_mut() {
+            item.table_settings.retain(|(itm_id, _, _)| *itm_id != id);
+        }
+
+        data.items.retain(|item| !item.table_settings.is_empty());
+    }
+
+    pub fn get_registered_tables_amount(&self) -> usize {
+        let data = self.data.lock().unwrap();
+        data.items.iter().map(|itm| itm.table_settings.len()).sum()
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.data.lock().unwrap().started
+    }
+
+    pub fn set_ping_interval(&self, ping_interval: Duration) {
+        self.data.lock().unwrap().ping_interval = ping_interval;
+    }
+
+    /// Ping loop is stopped as soon as application is shutting down
+    pub fn set_app_states(&self, app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>) {
+        self.data.lock().unwrap().app_states = Some(app_states);
+    }
+
+    /// Results of the pings are reported to the callback instead of the logger
+    pub fn set_status_callback(&self, callback: impl Fn(PingStatus) + Send + Sync + 'static) {
+        self.data.lock().unwrap().status_callback = Some(Arc::new(callback));
+    }
+
+    pub fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    fn should_stop(&self, data: &PingPoolInner) -> bool {
+        if self.shutting_down.load(Ordering::SeqCst) || data.items.is_empty() {
+            return true;
+        }
+
+        match data.app_states.as_ref() {
+            Some(app_states) => app_states.is_shutting_down(),
+            None => false,
+        }
+    }
+
+    fn is_stopping(&self) -> bool {
+        let data = self.data.lock().unwrap();
+        self.should_stop(&data)
+    }
+
+    /// Check and the reset of the started flag are done under the lock of the registration,
+    /// so the registration either sees the stopped loop and starts the new one, or keeps this one running
+    fn keep_running(&self) -> bool {
+        let mut data = self.data.lock().unwrap();
+
+        if self.should_stop(&data) {
+            data.started = false;
+            return false;
+        }
+
+        true
+    }
+
+    fn report(&self, status: PingStatus) {
+        let callback = self.data.lock().unwrap().status_callback.clone();
+
+        if let Some(callback) = callback {
+            callback(status);
+            return;
+        }
+
+        if let PingStatus::Error {
+            name,
+            version,
+            url,
+            error,
+        } = status
+        {
+            my_logger::LOGGER.write_error(
+                format!("MyNoSql ping {}:{}", name, version),
+                format!("Url: {}. Err: {}", url, error),
+                None.into(),
+            );
+        }
+    }
+
+    /// Wakes up earlier if pool is shutting down or there is nothing to ping
+    async fn sleep(&self) {
+        let ping_interval = self.data.lock().unwrap().ping_interval;
+        let mut slept = Duration::ZERO;
+
+        while slept < ping_interval {
+            if self.is_stopping() {
+                return;
+            }
+
+            let delay = (ping_interval - slept).min(SHUTDOWN_CHECK_INTERVAL);
+            tokio::time::sleep(delay).await;
+            slept += delay;
+        }
+    }
+
+    fn get_items_to_ping(
+        &self,
+    ) -> Vec<(
+        &'static str,
+        &'static str,
+        Vec<(
+            String,
+            Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
+        )>,
+    )> {
+        let data = self.data.lock().unwrap();
+
+        data.items
+            .iter()
+            .map(|itm| {
+                (
+                    itm.name,
+                    itm.version,
+                    itm.table_settings
+                        .iter()
+                        .map(|(_, table, settings)| (table.to_string(), settings.clone()))
+                        .collect(),
+                )
+            })
+            .collect()
     }
 }
 
-async fn ping_loop() {
-    let delay = Duration::from_secs(30);
+async fn ping_loop(pool: &'static PingPool) {
     loop {
-        tokio::time::sleep(delay).await;
+        pool.sleep().await;
 
-        let access = crate::PING_POOL.data.lock().await;
+        if !pool.keep_running() {
+            return;
+        }
 
-        for itm in access.items.iter() {
+        for (name, version, table_settings) in pool.get_items_to_ping() {
             let mut url_to_ping = HashMap::new();
-            for (table, settings) in itm.table_settings.iter() {
+            for (table, settings) in table_settings {
                 let url = settings.get_url().await;
                 let entry = url_to_ping
                     .entry(url)
-                    .or_insert_with(|| ((settings.clone(), Vec::new())));
-                entry.1.push(table.to_string());
+                    .or_insert_with(|| (settings.clone(), Vec::new()));
+                entry.1.push(table);
             }
 
-            for (_, (settings, tables)) in url_to_ping {
-                let factory = FlUrlFactory::new(settings, None, "");
-
-                let ping_model = PingModel {
-                    name: itm.name.to_string(),
-                    version: itm.version.to_string(),
-                    tables,
-                };
-
-                let fl_url = factory.get_fl_url().await;
-
-                if let Err(err) = &fl_url {
-                    println!("{}:{} ping error: {:?}", itm.name, itm.version, err);
-                    continue;
-                }
-
-                let fl_url_response = fl_url
-                    .unwrap()
-                    .0
-                    .with_retries(3)
-                    .append_path_segment("api")
-                    .append_path_segment("ping")
-                    .post_json(&ping_model)
-                    .await;
-
-                if let Err(err) = &fl_url_response {
-                    println!("{}:{} ping error: {:?}", itm.name, itm.version, err);
-                    continue;
-                }
+            for (url, (settings, tables)) in url_to_ping {
+                let status = ping(name, version, url, settings, tables).await;
+                pool.report(status);
             }
         }
     }
 }
 
+async fn ping(
+    name: &'static str,
+    version: &'static str,
+    url: String,
+    settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
+    tables: Vec<String>,
+) -> PingStatus {
+    let factory = FlUrlFactory::new(
+        settings,
+        None,
+        "",
+        my_no_sql_abstractions::DataSynchronizationPeriod::Sec1,
+    );
+
+    let ping_model = PingModel {
+        name: name.to_string(),
+        version: version.to_string(),
+        tables,
+    };
+
+    let fl_url = match factory.get_writer_request().await {
+        Ok((fl_url, _)) => fl_url,
+        Err(err) => {
+            return PingStatus::Error {
+                name,
+                version,
+                url,
+                error: format!("{:?}", err),
+            }
+        }
+    };
+
+    let fl_url_response = fl_url
+        .with_retries(3)
+        .append_path_segment("api")
+        .append_path_segment("ping")
+        .post_json(&ping_model)
+        .await;
+
+    if let Err(err) = fl_url_response {
+        return PingStatus::Error {
+            name,
+            version,
+            url,
+            error: format!("{:?}", err),
+        };
+    }
+
+    PingStatus::Ok {
+        name,
+        version,
+        url,
+        tables: ping_model.tables,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PingModel {
     pub name: String,
     pub version: String,
     pub tables: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::MyNoSqlWriterSettings;
+
+    use super::PingPool;
+
+    struct TestSettings;
+
+    #[async_trait::async_trait]
+    impl MyNoSqlWriterSettings for TestSettings {
+        async fn get_url(&self) -> String {
+            "http://127.0.0.1:1".to_string()
+        }
+
+        fn get_app_name(&self) -> &'static str {
+            "test-app"
+        }
+
+        fn get_app_version(&self) -> &'static str {
+            "1.0.0"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unregister_and_shutdown() {
+        let pool: &'static PingPool = Box::leak(Box::new(PingPool::new()));
+        pool.set_ping_interval(Duration::from_secs(3600));
+
+        let id1 = pool.register(Arc::new(TestSettings), "table1");
+        let id2 = pool.register(Arc::new(TestSettings), "table2");
+
+        assert!(pool.is_started());
+        assert_eq!(2, pool.get_registered_tables_amount());
+
+        pool.unregister(id1);
+        assert_eq!(1, pool.get_registered_tables_amount());
+
+        pool.unregister(id2);
+        assert_eq!(0, pool.get_registered_tables_amount());
+
+        pool.shutdown();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+
+        assert!(!pool.is_started());
+    }
+
+    #[tokio::test]
+    async fn test_loop_is_stopped_without_registrations() {
+        let pool: &'static PingPool = Box::leak(Box::new(PingPool::new()));
+        pool.set_ping_interval(Duration::from_secs(3600));
+
+        let id = pool.register(Arc::new(TestSettings), "table1");
+        assert!(pool.is_started());
+
+        pool.unregister(id);
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!pool.is_started());
+
+        // Next registration starts the loop again
+        let id = pool.register(Arc::new(TestSettings), "table1");
+        assert!(pool.is_started());
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(pool.is_started());
+
+        pool.unregister(id);
+    }
+}
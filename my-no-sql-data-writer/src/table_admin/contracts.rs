@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableDescriptionContract {
+    pub name: String,
+    #[serde(default)]
+    pub persist: bool,
+    #[serde(rename = "maxPartitionsAmount")]
+    pub max_partitions_amount: Option<usize>,
+    #[serde(rename = "maxRowsPerPartitionAmount")]
+    pub max_rows_per_partition_amount: Option<usize>,
+    #[serde(rename = "maxTableSizeBytes", default)]
+    pub max_table_size_bytes: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerStatusContract {
+    #[serde(default)]
+    pub tables: Vec<TableStatusContract>,
+    #[serde(default)]
+    pub readers: Vec<ReaderContract>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableStatusContract {
+    pub name: String,
+    #[serde(rename = "partitionsCount", default)]
+    pub partitions_count: usize,
+    #[serde(rename = "recordsAmount", default)]
+    pub records_amount: usize,
+    #[serde(rename = "dataSize", default)]
+    pub data_size: usize,
+    #[serde(rename = "lastUpdateTime")]
+    pub last_update_time: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReaderContract {
+    pub id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub ip: String,
+    #[serde(default)]
+    pub tables: Vec<String>,
+    #[serde(rename = "connectedTime")]
+    pub connected_time: Option<String>,
+    #[serde(rename = "lastIncomingTime")]
+    pub last_incoming_time: Option<String>,
+    #[serde(rename = "pendingToSend", default)]
+    pub pending_to_send: usize,
+}
@@ -0,0 +1,8 @@
+mod contracts;
+pub use contracts::*;
+mod table_metadata;
+pub use table_metadata::*;
+mod table_attributes_drift;
+pub use table_attributes_drift::*;
+mod my_no_sql_table_admin;
+pub use my_no_sql_table_admin::*;
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use my_no_sql_abstractions::{DataSynchronizationPeriod, MyNoSqlEntity, MyNoSqlTableAttributes};
+
+use crate::{
+    my_no_sql_data_writer::execution, CreateTableParams, DataWriterError, FlUrlFactory,
+    MyNoSqlWriterSettings,
+};
+
+use super::{
+    ReaderContract, TableAttributesDrift, TableDescriptionContract, TableDriftPolicy,
+    TableMetadata, TableProvisionResult,
+};
+
+/// Operations on the tables of MyNoSql server which are not bound to the entity type
+pub struct MyNoSqlTableAdmin {
+    fl_url_factory: FlUrlFactory,
+    sync_period: DataSynchronizationPeriod,
+}
+
+impl MyNoSqlTableAdmin {
+    pub fn new(
+        settings: Arc<dyn MyNoSqlWriterSettings + Send + Sync + 'static>,
+        sync_period: DataSynchronizationPeriod,
+    ) -> Self {
+        Self {
+            fl_url_factory: FlUrlFactory::new(settings, None, "", sync_period),
+            sync_period,
+        }
+    }
+
+    pub async fn list_tables(&self) -> Result<Vec<TableDescriptionContract>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        execution::get_tables(fl_url).await
+    }
+
+    pub async fn get_table_metadata(
+        &self,
+        table_name: &str,
+    ) -> Result<Option<TableMetadata>, DataWriterError> {
+        let description = self
+            .list_tables()
+            .await?
+            .into_iter()
+            .find(|itm| itm.name == table_name);
+
+        let description = match description {
+            Some(description) => description,
+            None => return Ok(None),
+        };
+
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        let status = execution::get_server_status(fl_url).await?;
+
+        let table_status = status.tables.iter().find(|itm| itm.name == table_name);
+
+        Ok(Some(TableMetadata::new(description, table_status)))
+    }
+
+    pub async fn update_table_attributes(
+        &self,
+        table_name: &str,
+        attributes: &CreateTableParams,
+    ) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        execution::update_table_attributes(fl_url, table_name, attributes, &self.sync_period).await
+    }
+
+    pub async fn clean_table(&self, table_name: &str) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        execution::clean_table(fl_url, table_name, &self.sync_period).await
+    }
+
+    /// Server requires api key to delete the table. Key is sent as X-Api-Key header
+    pub async fn delete_table(
+        &self,
+        table_name: &str,
+        api_key: &str,
+    ) -> Result<(), DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        execution::delete_table(fl_url, table_name, api_key).await
+    }
+
+    pub async fn list_readers(&self) -> Result<Vec<ReaderContract>, DataWriterError> {
+        let (fl_url, _) = self.fl_url_factory.get_writer_request().await?;
+        let status = execution::get_server_status(fl_url).await?;
+        Ok(status.readers)
+    }
+
+    /// Creates the table if it does not exist. Existing table attributes are compared with the expected ones
+    /// and the drift is handled according to the policy
+    pub async fn provision_table(
+        &self,
+        table_name: &str,
+        expected: &MyNoSqlTableAttributes,
+        policy: TableDriftPolicy,
+    ) -> Result<TableProvisionResult, DataWriterError> {
+        let actual = self
+            .list_tables()
+            .await?
+            .into_iter()
+            .find(|itm| itm.name == table_name);
+
+        let actual = match actual {
+            Some(actual) => actual,
+            None => {
+                let (fl_url, url) = self.fl_url_factory.get_writer_request().await?;
+                execution::create_table_if_not_exists(
+                    fl_url,
+                    url.as_str(),
+                    table_name,
+                    &expected.into(),
+                    self.sync_period,
+                )
+                .await?;
+
+                return Ok(TableProvisionResult::Created);
+            }
+        };
+
+        let drift = match TableAttributesDrift::detect(table_name, expected, &actual) {
+            Some(drift) => drift,
+            None => return Ok(TableProvisionResult::UpToDate),
+        };
+
+        match policy {
+            TableDriftPolicy::Update => {
+                // Entity does not declare the table size limit, so the one the table has is kept
+                let mut attributes: CreateTableParams = expected.into();
+                attributes.max_table_size_bytes = actual.max_table_size_bytes;
+
+                self.update_table_attributes(table_name, &attributes)
+                    .await?;
+                Ok(TableProvisionResult::Updated(drift))
+            }
+            TableDriftPolicy::Fail => Err(DataWriterError::TableAttributesMismatch(drift)),
+            TableDriftPolicy::Ignore => Ok(TableProvisionResult::DriftIgnored(drift)),
+        }
+    }
+
+    pub async fn provision_entity_table<TEntity: MyNoSqlEntity>(
+        &self,
+        policy: TableDriftPolicy,
+    ) -> Result<TableProvisionResult, DataWriterError> {
+        let expected = match TEntity::TABLE_ATTRIBUTES {
+            Some(expected) => expected,
+            None => {
+                return Err(DataWriterError::Error(format!(
+                    "Table attributes are not declared for the table {}",
+                    TEntity::TABLE_NAME
+                )))
+            }
+        };
+
+        self.provision_table(TEntity::TABLE_NAME, &expected, policy)
+            .await
+    }
+}
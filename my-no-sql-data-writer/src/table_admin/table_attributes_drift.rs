@@ -0,0 +1,117 @@
+use my_no_sql_abstractions::MyNoSqlTableAttributes;
+
+use super::TableDescriptionContract;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableDriftPolicy {
+    /// Attributes of the table on the server are updated to the declared ones
+    Update,
+    /// Provisioning fails with DataWriterError::TableAttributesMismatch
+    Fail,
+    /// Drift is reported in the result but nothing is changed
+    Ignore,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableAttributesDrift {
+    pub table_name: String,
+    pub expected: MyNoSqlTableAttributes,
+    pub actual: MyNoSqlTableAttributes,
+}
+
+impl TableAttributesDrift {
+    pub fn detect(
+        table_name: &str,
+        expected: &MyNoSqlTableAttributes,
+        actual: &TableDescriptionContract,
+    ) -> Option<Self> {
+        let actual = MyNoSqlTableAttributes {
+            persist: actual.persist,
+            max_partitions_amount: actual.max_partitions_amount,
+            max_rows_per_partition_amount: actual.max_rows_per_partition_amount,
+        };
+
+        if expected == &actual {
+            return None;
+        }
+
+        Some(Self {
+            table_name: table_name.to_string(),
+            expected: *expected,
+            actual,
+        })
+    }
+
+    pub fn get_drifted_fields(&self) -> Vec<&'static str> {
+        let mut result = Vec::new();
+
+        if self.expected.persist != self.actual.persist {
+            result.push("persist");
+        }
+
+        if self.expected.max_partitions_amount != self.actual.max_partitions_amount {
+            result.push("max_partitions_amount");
+        }
+
+        if self.expected.max_rows_per_partition_amount != self.actual.max_rows_per_partition_amount
+        {
+            result.push("max_rows_per_partition_amount");
+        }
+
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TableProvisionResult {
+    Created,
+    UpToDate,
+    Updated(TableAttributesDrift),
+    DriftIgnored(TableAttributesDrift),
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_abstractions::MyNoSqlTableAttributes;
+
+    use crate::TableDescriptionContract;
+
+    use super::TableAttributesDrift;
+
+    fn get_actual() -> TableDescriptionContract {
+        TableDescriptionContract {
+            name: "test".to_string(),
+            persist: true,
+            max_partitions_amount: Some(100),
+            max_rows_per_partition_amount: None,
+            max_table_size_bytes: None,
+        }
+    }
+
+    #[test]
+    fn test_no_drift() {
+        let expected = MyNoSqlTableAttributes {
+            persist: true,
+            max_partitions_amount: Some(100),
+            max_rows_per_partition_amount: None,
+        };
+
+        assert!(TableAttributesDrift::detect("test", &expected, &get_actual()).is_none());
+    }
+
+    #[test]
+    fn test_drift_is_detected() {
+        let expected = MyNoSqlTableAttributes {
+            persist: false,
+            max_partitions_amount: Some(100),
+            max_rows_per_partition_amount: Some(10),
+        };
+
+        let drift = TableAttributesDrift::detect("test", &expected, &get_actual()).unwrap();
+
+        assert_eq!(
+            vec!["persist", "max_rows_per_partition_amount"],
+            drift.get_drifted_fields()
+        );
+    }
+}
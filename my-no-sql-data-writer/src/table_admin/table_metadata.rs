@@ -0,0 +1,31 @@
+use super::{TableDescriptionContract, TableStatusContract};
+
+#[derive(Debug, Clone)]
+pub struct TableMetadata {
+    pub name: String,
+    pub persist: bool,
+    pub max_partitions_amount: Option<usize>,
+    pub max_rows_per_partition_amount: Option<usize>,
+    pub max_table_size_bytes: Option<usize>,
+    pub partitions_amount: usize,
+    pub rows_amount: usize,
+    pub data_size: usize,
+}
+
+impl TableMetadata {
+    pub fn new(
+        description: TableDescriptionContract,
+        status: Option<&TableStatusContract>,
+    ) -> Self {
+        Self {
+            name: description.name,
+            persist: description.persist,
+            max_partitions_amount: description.max_partitions_amount,
+            max_rows_per_partition_amount: description.max_rows_per_partition_amount,
+            max_table_size_bytes: description.max_table_size_bytes,
+            partitions_amount: status.map(|itm| itm.partitions_count).unwrap_or_default(),
+            rows_amount: status.map(|itm| itm.records_amount).unwrap_or_default(),
+            data_size: status.map(|itm| itm.data_size).unwrap_or_default(),
+        }
+    }
+}
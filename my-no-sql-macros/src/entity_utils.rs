@@ -4,6 +4,8 @@ use quote::{quote, ToTokens};
 use syn::Ident;
 use types_reader::StructProperty;
 
+use crate::my_no_sql_entity::EntityEncoding;
+
 pub fn compile_src_with_new_fields(
     ast: &proc_macro2::TokenStream,
     add_pk: bool,
@@ -80,6 +82,7 @@ pub fn compile_struct_with_new_fields(
     derive: proc_macro2::TokenStream,
     fields: &[StructProperty],
     render_expires: bool,
+    encoding: EntityEncoding,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
     let mut structure_fields = Vec::new();
 
@@ -96,6 +99,17 @@ pub fn compile_struct_with_new_fields(
         }
 
         if let Some(rename_attr) = field.attrs.try_get_attr("serde") {
+            // MessagePack keeps fields by position, so every field has to be serialized
+            if encoding == EntityEncoding::MessagePack
+                && rename_attr
+                    .try_get_named_param("skip_serializing_if")
+                    .is_some()
+            {
+                return field.throw_error(
+                    "skip_serializing_if is not supported with message_pack encoding",
+                );
+            }
+
             let param_rename = rename_attr.try_get_named_param("rename");
             if let Some(param_rename) = param_rename {
                 let param_rename = param_rename.unwrap_any_value_as_str()?;
@@ -127,6 +141,13 @@ pub fn compile_struct_with_new_fields(
         });
     }
 
+    let skip_time_stamp = match encoding {
+        EntityEncoding::Json => quote! {
+            #[serde(skip_serializing_if = "my_no_sql_sdk::abstractions::skip_timestamp_serializing")]
+        },
+        EntityEncoding::MessagePack => quote!(),
+    };
+
     // #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
     let result = quote! {
 
@@ -137,7 +158,7 @@ pub fn compile_struct_with_new_fields(
             #[serde(rename="RowKey")]
             pub row_key: String,
             #[serde(rename="TimeStamp")]
-            #[serde(skip_serializing_if = "my_no_sql_sdk::abstractions::skip_timestamp_serializing")]
+            #skip_time_stamp
             pub time_stamp: my_no_sql_sdk::abstractions::Timestamp,
             #(#structure_fields)*
         }
@@ -205,3 +226,44 @@ pub fn get_fn_standard_serialize_deserialize() -> proc_macro2::TokenStream {
         }
     }
 }
+
+/// TimeStamp and Expires are taken from the envelope, since the node changes them without touching the encoded entity
+pub fn get_fn_message_pack_serialize_deserialize(render_expires: bool) -> proc_macro2::TokenStream {
+    let (expires, update_expires) = if render_expires {
+        (
+            quote::quote!(Some(self.expires)),
+            quote::quote!(result.expires = envelope.get_expires().unwrap_or_default();),
+        )
+    } else {
+        (quote::quote!(None), quote::quote!())
+    };
+
+    quote::quote! {
+        fn serialize_entity(&self) -> Vec<u8> {
+            match self.try_serialize_entity() {
+                Ok(result) => result,
+                Err(err) => panic!("{}", err),
+            }
+        }
+
+        fn try_serialize_entity(&self) -> Result<Vec<u8>, String> {
+            my_no_sql_sdk::core::message_pack_entity::serialize(self, #expires)
+        }
+
+
+        fn deserialize_entity(src: &[u8]) -> Result<Self, String> {
+            let (mut result, envelope) =
+                my_no_sql_sdk::core::message_pack_entity::deserialize::<Self>(src)?;
+
+            if let Some(envelope) = envelope {
+                if let Some(time_stamp) = envelope.get_time_stamp() {
+                    result.time_stamp = time_stamp;
+                }
+
+                #update_expires
+            }
+
+            Ok(result)
+        }
+    }
+}
@@ -12,6 +12,10 @@ struct MyNoSqlEntityParameters<'s> {
     #[default]
     pub table_name: &'s str,
     pub with_expires: Option<bool>,
+    pub persist: Option<bool>,
+    pub max_partitions_amount: Option<&'s str>,
+    pub max_rows_per_partition_amount: Option<&'s str>,
+    pub encoding: Option<&'s str>,
 }
 
 #[proc_macro_attribute]
@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityEncoding {
+    Json,
+    MessagePack,
+}
+
+impl EntityEncoding {
+    pub fn parse(value: Option<&str>) -> Result<Self, syn::Error> {
+        match value {
+            None | Some("json") => Ok(Self::Json),
+            Some("message_pack") => Ok(Self::MessagePack),
+            Some(value) => Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!("encoding must be json or message_pack. Value: {}", value),
+            )),
+        }
+    }
+}
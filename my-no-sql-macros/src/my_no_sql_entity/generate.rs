@@ -24,13 +24,68 @@ pub fn generate(
 
     let params = MyNoSqlEntityParameters::try_from(&attr)?;
 
+    let table_attributes = get_table_attributes(&params)?;
+
+    let encoding = super::EntityEncoding::parse(params.encoding)?;
+
     let result = super::generate_base_impl(
         struct_name,
         derive,
         fields.as_slice(),
         params.table_name,
         params.with_expires.unwrap_or(false),
+        table_attributes,
+        encoding,
     )?;
 
     Ok(result.into())
 }
+
+fn get_table_attributes(
+    params: &MyNoSqlEntityParameters,
+) -> Result<Option<proc_macro2::TokenStream>, syn::Error> {
+    if params.persist.is_none()
+        && params.max_partitions_amount.is_none()
+        && params.max_rows_per_partition_amount.is_none()
+    {
+        return Ok(None);
+    }
+
+    let persist = params.persist.unwrap_or(true);
+
+    let max_partitions_amount =
+        render_optional_amount("max_partitions_amount", params.max_partitions_amount)?;
+
+    let max_rows_per_partition_amount = render_optional_amount(
+        "max_rows_per_partition_amount",
+        params.max_rows_per_partition_amount,
+    )?;
+
+    let result = quote::quote! {
+        my_no_sql_sdk::abstractions::MyNoSqlTableAttributes {
+            persist: #persist,
+            max_partitions_amount: #max_partitions_amount,
+            max_rows_per_partition_amount: #max_rows_per_partition_amount,
+        }
+    };
+
+    Ok(Some(result))
+}
+
+fn render_optional_amount(
+    param_name: &str,
+    value: Option<&str>,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(quote::quote!(None)),
+    };
+
+    match value.parse::<usize>() {
+        Ok(value) => Ok(quote::quote!(Some(#value))),
+        Err(_) => Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!("{} must be a positive number. Value: {}", param_name, value),
+        )),
+    }
+}
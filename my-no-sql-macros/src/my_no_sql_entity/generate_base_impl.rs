@@ -3,18 +3,33 @@ use types_reader::StructProperty;
 
 use crate::entity_utils::*;
 
+use super::EntityEncoding;
+
 pub fn generate_base_impl(
     struct_name: &Ident,
     derive: proc_macro2::TokenStream,
     fields: &[StructProperty],
     table_name: &str,
     render_expires: bool,
+    table_attributes: Option<proc_macro2::TokenStream>,
+    encoding: EntityEncoding,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
-    let new_struct = compile_struct_with_new_fields(struct_name, derive, fields, render_expires)?;
+    let new_struct =
+        compile_struct_with_new_fields(struct_name, derive, fields, render_expires, encoding)?;
 
     let fn_get_time_stamp = get_fn_get_time_stamp_token();
 
-    let fn_serialize_deserialize = get_fn_standard_serialize_deserialize();
+    let fn_serialize_deserialize = match encoding {
+        EntityEncoding::Json => get_fn_standard_serialize_deserialize(),
+        EntityEncoding::MessagePack => get_fn_message_pack_serialize_deserialize(render_expires),
+    };
+
+    let table_attributes = match table_attributes {
+        Some(table_attributes) => quote::quote! {
+            const TABLE_ATTRIBUTES: Option<my_no_sql_sdk::abstractions::MyNoSqlTableAttributes> = Some(#table_attributes);
+        },
+        None => quote::quote!(),
+    };
 
     let result = quote::quote! {
 
@@ -26,6 +41,8 @@ pub fn generate_base_impl(
 
             const LAZY_DESERIALIZATION: bool = false;
 
+            #table_attributes
+
             fn get_partition_key(&self) -> &str {
                 &self.partition_key
             }
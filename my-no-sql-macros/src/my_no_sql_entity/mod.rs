@@ -2,3 +2,5 @@ mod generate;
 pub use generate::generate;
 mod generate_base_impl;
 pub use generate_base_impl::*;
+mod entity_encoding;
+pub use entity_encoding::*;
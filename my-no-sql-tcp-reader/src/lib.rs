@@ -13,4 +13,4 @@ pub use subscribers::{
 };
 
 #[cfg(feature = "mocks")]
-pub use subscribers::MyNoSqlDataReaderMock;
+pub use subscribers::{MyNoSqlDataReaderMock, MyNoSqlDataReaderMockInner};
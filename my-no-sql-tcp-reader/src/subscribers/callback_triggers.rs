@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use my_no_sql_abstractions::{MyNoSqlEntity, MyNoSqlEntitySerializer};
+use my_no_sql_core::diff::{SortedDiffItem, SortedDiffIterator};
 
 use super::{LazyMyNoSqlEntity, MyNoSqlDataReaderCallBacks};
 
@@ -43,38 +44,42 @@ pub async fn trigger_brand_new_table<
     }
 }
 
+/// Partitions of the new version are reported first in the order of partition key. Deleted partitions are reported after them
 pub async fn trigger_old_and_new_table_difference<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
     TMyNoSqlDataReaderCallBacks: MyNoSqlDataReaderCallBacks<TMyNoSqlEntity>,
 >(
     callbacks: &TMyNoSqlDataReaderCallBacks,
-    mut before: BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
+    before: BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
     now_entities: &BTreeMap<String, BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
 ) {
-    for (now_partition_key, now_partition) in now_entities {
-        let before_partition = before.remove(now_partition_key);
-
-        trigger_partition_difference(
-            callbacks,
-            now_partition_key,
-            before_partition,
-            now_partition,
-        )
-        .await;
-    }
+    let before_partitions = before.iter().map(|(key, value)| (key.as_str(), value));
+    let now_partitions = now_entities
+        .iter()
+        .map(|(key, value)| (key.as_str(), value));
 
-    for (before_partition_key, before_partition) in before {
-        let mut deleted_entities = Vec::new();
+    let mut deleted_partitions = Vec::new();
 
-        for (_, db_row) in before_partition {
-            deleted_entities.push(db_row);
+    for (partition_key, item) in SortedDiffIterator::new(before_partitions, now_partitions) {
+        match item {
+            SortedDiffItem::Inserted(now_partition) => {
+                trigger_brand_new_partition(callbacks, partition_key, now_partition).await;
+            }
+            SortedDiffItem::Both {
+                before: before_partition,
+                now: now_partition,
+            } => {
+                trigger_rows_difference(callbacks, partition_key, before_partition, now_partition)
+                    .await;
+            }
+            SortedDiffItem::Deleted(before_partition) => {
+                deleted_partitions.push((partition_key, before_partition));
+            }
         }
+    }
 
-        if deleted_entities.len() > 0 {
-            callbacks
-                .deleted(before_partition_key.as_str(), deleted_entities)
-                .await;
-        }
+    for (partition_key, before_partition) in deleted_partitions {
+        trigger_rows_difference(callbacks, partition_key, before_partition, &BTreeMap::new()).await;
     }
 }
 
@@ -88,35 +93,9 @@ pub async fn trigger_partition_difference<
     now_partition: &BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>,
 ) {
     match before_partition {
-        Some(mut before_partition) => {
-            for (now_row_key, now_row) in now_partition {
-                let mut inserted_or_replaced = Vec::new();
-
-                match before_partition.remove(now_row_key) {
-                    Some(_) => {
-                        inserted_or_replaced.push(now_row.clone());
-                    }
-                    None => {
-                        inserted_or_replaced.push(now_row.clone());
-                    }
-                }
-
-                if inserted_or_replaced.len() > 0 {
-                    callbacks
-                        .inserted_or_replaced(partition_key, inserted_or_replaced)
-                        .await;
-                }
-            }
-
-            let mut deleted_entities = Vec::new();
-
-            for (_, before_row) in before_partition {
-                deleted_entities.push(before_row);
-            }
-
-            if deleted_entities.len() > 0 {
-                callbacks.deleted(partition_key, deleted_entities).await;
-            }
+        Some(before_partition) => {
+            trigger_rows_difference(callbacks, partition_key, &before_partition, now_partition)
+                .await;
         }
         None => {
             trigger_brand_new_partition(callbacks, partition_key, now_partition).await;
@@ -124,6 +103,43 @@ pub async fn trigger_partition_difference<
     }
 }
 
+/// Every row of the new version is reported as inserted or replaced with a separate call in the order of row key.
+/// Rows which are absent in the new version are reported as deleted with one call after them
+async fn trigger_rows_difference<
+    TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
+    TMyNoSqlDataReaderCallBacks: MyNoSqlDataReaderCallBacks<TMyNoSqlEntity>,
+>(
+    callbacks: &TMyNoSqlDataReaderCallBacks,
+    partition_key: &str,
+    before_partition: &BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>,
+    now_partition: &BTreeMap<String, LazyMyNoSqlEntity<TMyNoSqlEntity>>,
+) {
+    let before_rows = before_partition
+        .iter()
+        .map(|(key, value)| (key.as_str(), value));
+    let now_rows = now_partition
+        .iter()
+        .map(|(key, value)| (key.as_str(), value));
+
+    let mut deleted = Vec::new();
+
+    // Reader does not compare the content. Row which exists in both versions is always replaced
+    for (_, item) in SortedDiffIterator::new(before_rows, now_rows) {
+        match item {
+            SortedDiffItem::Inserted(entity) | SortedDiffItem::Both { now: entity, .. } => {
+                callbacks
+                    .inserted_or_replaced(partition_key, vec![entity.clone()])
+                    .await;
+            }
+            SortedDiffItem::Deleted(entity) => deleted.push(entity.clone()),
+        }
+    }
+
+    if !deleted.is_empty() {
+        callbacks.deleted(partition_key, deleted).await;
+    }
+}
+
 pub async fn trigger_brand_new_partition<
     TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static,
     TMyNoSqlDataReaderCallBacks: MyNoSqlDataReaderCallBacks<TMyNoSqlEntity>,
@@ -157,6 +173,7 @@ mod tests {
     struct TestCallbacksInner {
         inserted_or_replaced_entities: BTreeMap<String, Vec<LazyMyNoSqlEntity<TestRow>>>,
         deleted: BTreeMap<String, Vec<LazyMyNoSqlEntity<TestRow>>>,
+        calls: Vec<String>,
     }
 
     pub struct TestCallbacks {
@@ -169,6 +186,7 @@ mod tests {
                 data: Mutex::new(TestCallbacksInner {
                     inserted_or_replaced_entities: BTreeMap::new(),
                     deleted: BTreeMap::new(),
+                    calls: Vec::new(),
                 }),
             }
         }
@@ -182,6 +200,9 @@ mod tests {
             entities: Vec<LazyMyNoSqlEntity<TestRow>>,
         ) {
             let mut write_access = self.data.lock().await;
+            write_access
+                .calls
+                .push(format_call("inserted_or_replaced", &entities));
             match write_access
                 .inserted_or_replaced_entities
                 .get_mut(partition_key)
@@ -200,6 +221,7 @@ mod tests {
 
         async fn deleted(&self, partition_key: &str, entities: Vec<LazyMyNoSqlEntity<TestRow>>) {
             let mut write_access = self.data.lock().await;
+            write_access.calls.push(format_call("deleted", &entities));
             match write_access.deleted.get_mut(partition_key) {
                 Some(db_partition) => {
                     db_partition.extend(entities);
@@ -214,6 +236,32 @@ mod tests {
         }
     }
 
+    fn format_call(callback: &str, entities: &[LazyMyNoSqlEntity<TestRow>]) -> String {
+        let keys: Vec<String> = entities
+            .iter()
+            .map(|itm| format!("{}/{}", itm.get_partition_key(), itm.get_row_key()))
+            .collect();
+
+        format!("{}:{}", callback, keys.join(","))
+    }
+
+    fn create_partition(
+        partition_key: &str,
+        row_keys: &[&str],
+        timestamp: i64,
+    ) -> BTreeMap<String, LazyMyNoSqlEntity<TestRow>> {
+        let mut result = BTreeMap::new();
+
+        for row_key in row_keys {
+            result.insert(
+                row_key.to_string(),
+                TestRow::new(partition_key.to_string(), row_key.to_string(), timestamp).into(),
+            );
+        }
+
+        result
+    }
+
     #[derive(Serialize, Deserialize, Clone, Debug)]
     pub struct TestRow {
         partition_key: String,
@@ -356,4 +404,42 @@ mod tests {
         );
         assert_eq!(1, read_access.deleted.get("PK1").unwrap().len());
     }
+
+    #[tokio::test]
+    pub async fn test_callbacks_order() {
+        let test_callback = TestCallbacks::new();
+
+        let mut before = BTreeMap::new();
+        before.insert("PK1".to_string(), create_partition("PK1", &["RK1"], 1));
+        before.insert(
+            "PK2".to_string(),
+            create_partition("PK2", &["RK1", "RK3"], 1),
+        );
+        before.insert("PK3".to_string(), create_partition("PK3", &["RK1"], 1));
+
+        let mut after = BTreeMap::new();
+        after.insert(
+            "PK2".to_string(),
+            create_partition("PK2", &["RK1", "RK2"], 2),
+        );
+        after.insert(
+            "PK4".to_string(),
+            create_partition("PK4", &["RK1", "RK2"], 1),
+        );
+
+        super::trigger_table_difference(&test_callback, Some(before), &after).await;
+
+        let read_access = test_callback.data.lock().await;
+        assert_eq!(
+            vec![
+                "inserted_or_replaced:PK2/RK1",
+                "inserted_or_replaced:PK2/RK2",
+                "deleted:PK2/RK3",
+                "inserted_or_replaced:PK4/RK1,PK4/RK2",
+                "deleted:PK1/RK1",
+                "deleted:PK3/RK1",
+            ],
+            read_access.calls
+        );
+    }
 }
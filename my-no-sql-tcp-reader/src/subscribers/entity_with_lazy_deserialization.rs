@@ -47,8 +47,12 @@ impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'st
             LazyMyNoSqlEntity::Raw(_) => panic!("We should have deserialized it"),
         }
     }
+}
 
-    pub fn clone(&self) -> Self {
+impl<TMyNoSqlEntity: MyNoSqlEntity + MyNoSqlEntitySerializer + Send + Sync + 'static> Clone
+    for LazyMyNoSqlEntity<TMyNoSqlEntity>
+{
+    fn clone(&self) -> Self {
         match self {
             LazyMyNoSqlEntity::Deserialized(entity) => {
                 LazyMyNoSqlEntity::Deserialized(entity.clone())
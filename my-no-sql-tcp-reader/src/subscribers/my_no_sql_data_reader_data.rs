@@ -42,6 +42,18 @@ where
         self.callbacks = Some(Arc::new(pusher));
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "my_no_sql.reader.init_table",
+            skip_all,
+            fields(
+                table = TMyNoSqlEntity::TABLE_NAME,
+                partitions = data.len(),
+                rows = data.values().map(|itm| itm.len()).sum::<usize>(),
+            )
+        )
+    )]
     pub async fn init_table(
         &mut self,
         data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
@@ -58,6 +70,18 @@ where
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "my_no_sql.reader.init_partition",
+            skip_all,
+            fields(
+                table = TMyNoSqlEntity::TABLE_NAME,
+                partition_key = partition_key,
+                rows = src_entities.values().map(|itm| itm.len()).sum::<usize>(),
+            )
+        )
+    )]
     pub async fn init_partition(
         &mut self,
         partition_key: &str,
@@ -78,6 +102,18 @@ where
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "my_no_sql.reader.update_rows",
+            skip_all,
+            fields(
+                table = TMyNoSqlEntity::TABLE_NAME,
+                partitions = src_data.len(),
+                rows = src_data.values().map(|itm| itm.len()).sum::<usize>(),
+            )
+        )
+    )]
     pub fn update_rows(
         &mut self,
         src_data: BTreeMap<String, Vec<LazyMyNoSqlEntity<TMyNoSqlEntity>>>,
@@ -85,6 +121,14 @@ where
         self.entities.update_rows(src_data, &self.callbacks);
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "my_no_sql.reader.delete_rows",
+            skip_all,
+            fields(table = TMyNoSqlEntity::TABLE_NAME, rows = rows_to_delete.len())
+        )
+    )]
     pub fn delete_rows(&mut self, rows_to_delete: Vec<my_no_sql_tcp_shared::DeleteRowTcpContract>) {
         self.entities.delete_rows(rows_to_delete, &self.callbacks);
     }
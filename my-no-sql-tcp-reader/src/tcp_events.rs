@@ -52,6 +52,20 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
     }
 
     async fn payload(&self, _connection: &Arc<MyNoSqlTcpConnection>, contract: MyNoSqlTcpContract) {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+            let span = get_payload_span(&contract);
+            self.handle_payload(contract).instrument(span).await;
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        self.handle_payload(contract).await;
+    }
+}
+
+impl TcpEvents {
+    async fn handle_payload(&self, contract: MyNoSqlTcpContract) {
         match contract {
             MyNoSqlTcpContract::Ping => {}
             MyNoSqlTcpContract::Pong => {}
@@ -125,3 +139,40 @@ impl SocketEventCallback<MyNoSqlTcpContract, MyNoSqlReaderTcpSerializer, ()> for
         }
     }
 }
+
+/// Only the contracts which update the data of the readers are traced
+#[cfg(feature = "tracing")]
+fn get_payload_span(contract: &MyNoSqlTcpContract) -> tracing::Span {
+    match contract {
+        MyNoSqlTcpContract::InitTable { table_name, data } => tracing::info_span!(
+            "my_no_sql.reader.payload",
+            table = table_name.as_str(),
+            operation = "init_table",
+            bytes = data.len()
+        ),
+        MyNoSqlTcpContract::InitPartition {
+            table_name,
+            partition_key,
+            data,
+        } => tracing::info_span!(
+            "my_no_sql.reader.payload",
+            table = table_name.as_str(),
+            operation = "init_partition",
+            partition_key = partition_key.as_str(),
+            bytes = data.len()
+        ),
+        MyNoSqlTcpContract::UpdateRows { table_name, data } => tracing::info_span!(
+            "my_no_sql.reader.payload",
+            table = table_name.as_str(),
+            operation = "update_rows",
+            bytes = data.len()
+        ),
+        MyNoSqlTcpContract::DeleteRows { table_name, rows } => tracing::info_span!(
+            "my_no_sql.reader.payload",
+            table = table_name.as_str(),
+            operation = "delete_rows",
+            rows = rows.len()
+        ),
+        _ => tracing::Span::none(),
+    }
+}
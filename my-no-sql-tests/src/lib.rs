@@ -6,3 +6,7 @@ mod test_new_enum_cases_added;
 mod test_same_timestamp;
 #[cfg(test)]
 mod tests_from_real_life;
+#[cfg(test)]
+mod test_table_attributes;
+#[cfg(test)]
+mod test_message_pack_encoding;
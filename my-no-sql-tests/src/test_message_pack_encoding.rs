@@ -0,0 +1,144 @@
+use my_no_sql_macros::my_no_sql_entity;
+use serde::*;
+
+#[my_no_sql_entity(table_name:"test-table", with_expires:true, encoding:"message_pack")]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PriceMessagePackEntity {
+    pub bid_price_value: f64,
+    pub ask_price_value: f64,
+    pub last_trade_volume: i64,
+    pub instrument_description: Option<String>,
+}
+
+#[my_no_sql_entity(table_name:"test-table", with_expires:true)]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct PriceJsonEntity {
+    pub bid_price_value: f64,
+    pub ask_price_value: f64,
+    pub last_trade_volume: i64,
+    pub instrument_description: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_sdk::{
+        abstractions::MyNoSqlEntitySerializer,
+        core::{
+            db_json_entity::{DbJsonEntity, JsonTimeStamp},
+            rust_extensions::date_time::DateTimeAsMicroseconds,
+        },
+    };
+
+    use super::{PriceJsonEntity, PriceMessagePackEntity};
+
+    fn create_entity() -> PriceMessagePackEntity {
+        PriceMessagePackEntity {
+            partition_key: "EURUSD".to_string(),
+            row_key: "2024-01-01".to_string(),
+            time_stamp: Default::default(),
+            expires: DateTimeAsMicroseconds::from_str("2030-01-01T00:00:00")
+                .unwrap()
+                .into(),
+            bid_price_value: 1.10345,
+            ask_price_value: 1.10355,
+            last_trade_volume: 1_000_000,
+            instrument_description: None,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let entity = create_entity();
+
+        let payload = entity.serialize_entity();
+        let result = PriceMessagePackEntity::deserialize_entity(payload.as_slice()).unwrap();
+
+        assert_eq!(entity, result);
+    }
+
+    #[test]
+    fn test_fields_are_stored_by_position() {
+        let entity = create_entity();
+
+        let payload = entity.try_serialize_entity().unwrap();
+        let payload = std::str::from_utf8(payload.as_slice()).unwrap();
+
+        assert!(payload.contains(r#""$mp":["#));
+        assert!(payload.contains("1.10345,1.10355,1000000,null]"));
+        assert!(!payload.contains("bid_price_value"));
+
+        let json_entity = PriceJsonEntity {
+            partition_key: entity.partition_key.clone(),
+            row_key: entity.row_key.clone(),
+            time_stamp: Default::default(),
+            expires: entity.expires,
+            bid_price_value: entity.bid_price_value,
+            ask_price_value: entity.ask_price_value,
+            last_trade_volume: entity.last_trade_volume,
+            instrument_description: None,
+        };
+
+        assert!(payload.len() < json_entity.serialize_entity().len());
+    }
+
+    #[test]
+    fn test_keys_and_expires_are_readable_as_json() {
+        let entity = create_entity();
+        let payload = entity.serialize_entity();
+
+        let db_json_entity = DbJsonEntity::from_slice(payload.as_slice()).unwrap();
+
+        assert_eq!(
+            "EURUSD",
+            db_json_entity.get_partition_key(payload.as_slice())
+        );
+        assert_eq!("2024-01-01", db_json_entity.get_row_key(payload.as_slice()));
+        assert_eq!(
+            entity.expires.to_date_time().unix_microseconds,
+            db_json_entity.expires_value.unwrap().unix_microseconds
+        );
+    }
+
+    #[test]
+    fn test_time_stamp_injected_by_node_is_read() {
+        let entity = create_entity();
+        let payload = entity.serialize_entity();
+
+        let db_row =
+            DbJsonEntity::parse_into_db_row(payload.as_slice().into(), &JsonTimeStamp::now())
+                .unwrap();
+
+        let result = PriceMessagePackEntity::deserialize_entity(db_row.get_src_as_slice()).unwrap();
+
+        assert!(!result.time_stamp.is_default());
+        assert_eq!(entity.bid_price_value, result.bid_price_value);
+    }
+
+    #[test]
+    fn test_migration_between_json_and_message_pack() {
+        let entity = create_entity();
+
+        let json_entity = PriceJsonEntity {
+            partition_key: entity.partition_key.clone(),
+            row_key: entity.row_key.clone(),
+            time_stamp: Default::default(),
+            expires: entity.expires,
+            bid_price_value: entity.bid_price_value,
+            ask_price_value: entity.ask_price_value,
+            last_trade_volume: entity.last_trade_volume,
+            instrument_description: Some("Euro".to_string()),
+        };
+
+        // Rows written before the migration
+        let json_payload = json_entity.serialize_entity();
+        let result = PriceMessagePackEntity::deserialize_entity(json_payload.as_slice()).unwrap();
+        assert_eq!(Some("Euro"), result.instrument_description.as_deref());
+        assert_eq!(entity.last_trade_volume, result.last_trade_volume);
+
+        // Readers which are not migrated yet
+        let message_pack_payload = entity.serialize_entity();
+        let result = PriceJsonEntity::deserialize_entity(message_pack_payload.as_slice()).unwrap();
+        assert_eq!(entity.ask_price_value, result.ask_price_value);
+        assert_eq!(None, result.instrument_description);
+    }
+}
@@ -0,0 +1,35 @@
+use my_no_sql_macros::my_no_sql_entity;
+use serde::*;
+
+#[my_no_sql_entity(table_name:"test-table", persist:false, max_partitions_amount:"100")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntityWithTableAttributes {
+    pub value: String,
+}
+
+#[my_no_sql_entity(table_name:"test-table")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntityWithoutTableAttributes {
+    pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use my_no_sql_sdk::abstractions::{MyNoSqlEntity, MyNoSqlTableAttributes};
+
+    use super::{EntityWithTableAttributes, EntityWithoutTableAttributes};
+
+    #[test]
+    fn test_declared_table_attributes() {
+        assert_eq!(
+            Some(MyNoSqlTableAttributes {
+                persist: false,
+                max_partitions_amount: Some(100),
+                max_rows_per_partition_amount: None,
+            }),
+            EntityWithTableAttributes::TABLE_ATTRIBUTES
+        );
+
+        assert_eq!(None, EntityWithoutTableAttributes::TABLE_ATTRIBUTES);
+    }
+}